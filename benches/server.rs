@@ -0,0 +1,136 @@
+//! 对比每连接一个线程的 IMServer::run 与事件循环 IMServer::run_event_loop.
+//!
+//! cargo bench --features event-loop --bench server
+
+use cathy::proto::{Action, Login, MsgToUser, Package, Receipt};
+use cathy::{Connection, FileUserStore, IMServer, ServerConfig};
+use protobuf::Message;
+use std::net::TcpStream;
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const CLIENTS: u64 = 200;
+const MESSAGES_PER_CLIENT: u64 = 50;
+
+fn users() -> FileUserStore {
+    let lines: Vec<String> = (1..=CLIENTS)
+        .map(|uid| format!("{} user{} password", uid, uid))
+        .collect();
+    FileUserStore::parse(&lines.join("\n")).unwrap()
+}
+
+fn config(address: &str) -> ServerConfig {
+    ServerConfig {
+        listen_address: address.to_string(),
+        ..ServerConfig::default()
+    }
+}
+
+fn connect(address: &str) -> Connection {
+    for _ in 0..50 {
+        if let Ok(stream) = TcpStream::connect(address) {
+            let mut connection = Connection::new(stream);
+            connection.handshake(Duration::from_secs(1)).unwrap();
+            return connection;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    panic!("Couldn't connect to the server...")
+}
+
+fn send<M: Message>(connection: &mut Connection, action: Action, msg: &M) {
+    let mut package = Package::new();
+    package.set_action(action);
+    package.set_content(msg.write_to_bytes().unwrap());
+    connection
+        .write_package(package, Duration::from_secs(10))
+        .unwrap();
+}
+
+// 每个客户端给自己发消息, 等待 MSG_ACK 与推送回来的 MSG_TO_USER 后回复送达确认
+fn client(address: &str, uid: u64, barrier: &Barrier) {
+    let mut connection = connect(address);
+    let mut login = Login::new();
+    login.set_username(format!("user{}", uid));
+    login.set_password("password".to_string());
+    send(&mut connection, Action::LOGIN, &login);
+    assert_eq!(
+        connection.read_package().unwrap().get_action(),
+        Action::CONNECTED
+    );
+    barrier.wait();
+
+    for seq in 1..=MESSAGES_PER_CLIENT {
+        let mut msg = MsgToUser::new();
+        msg.set_seq(seq);
+        msg.set_receiver_uid(uid);
+        msg.set_content("hello".to_string());
+        send(&mut connection, Action::MSG_TO_USER, &msg);
+
+        let (mut acked, mut received) = (false, false);
+        while !acked || !received {
+            let package = connection.read_package().unwrap();
+            match package.get_action() {
+                Action::MSG_ACK => acked = true,
+                Action::MSG_TO_USER => {
+                    let msg = MsgToUser::parse_from_bytes(package.get_content()).unwrap();
+                    let mut receipt = Receipt::new();
+                    receipt.set_message_id(msg.get_message_id());
+                    receipt.set_sender_uid(msg.get_sender_uid());
+                    send(&mut connection, Action::DELIVERED_ACK, &receipt);
+                    received = true;
+                }
+                _ => {}
+            }
+        }
+    }
+    barrier.wait();
+}
+
+fn bench(name: &str, address: &'static str) {
+    // 客户端全部登录后开始计时, 全部发完后结束计时
+    let barrier = Arc::new(Barrier::new(CLIENTS as usize + 1));
+    let start = Instant::now();
+    let clients: Vec<_> = (1..=CLIENTS)
+        .map(|uid| {
+            let barrier = barrier.clone();
+            thread::spawn(move || client(address, uid, &barrier))
+        })
+        .collect();
+    barrier.wait();
+    let login_elapsed = start.elapsed();
+    let start = Instant::now();
+    barrier.wait();
+    let elapsed = start.elapsed();
+    for client in clients {
+        client.join().unwrap();
+    }
+
+    let messages = CLIENTS * MESSAGES_PER_CLIENT;
+    println!(
+        "{:<10} login {} clients: {:>8.2?}, {} messages: {:>8.2?} ({:.0} msg/s)",
+        name,
+        CLIENTS,
+        login_elapsed,
+        messages,
+        elapsed,
+        messages as f64 / elapsed.as_secs_f64()
+    );
+}
+
+fn main() {
+    thread::spawn(|| {
+        IMServer::new(config("127.0.0.1:18201"), Box::new(users()))
+            .unwrap()
+            .run()
+    });
+    thread::spawn(|| {
+        IMServer::new(config("127.0.0.1:18202"), Box::new(users()))
+            .unwrap()
+            .run_event_loop()
+    });
+
+    bench("thread", "127.0.0.1:18201");
+    bench("event-loop", "127.0.0.1:18202");
+}
@@ -0,0 +1,79 @@
+//! 10万个在线会话时 SessionManager 按 uid 查找会话的开销, 对比单个分片与默认分片数.
+//!
+//! cargo bench --bench sessions
+
+use cathy::{
+    Codec, Connection, MemoryTransport, OverflowPolicy, SessionManager, WriteQueue,
+    DEFAULT_SESSION_SHARDS,
+};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const SESSIONS: u64 = 100_000;
+const LOOKUPS_PER_THREAD: u64 = 1_000_000;
+const THREADS: u64 = 8;
+
+fn new_manager(shards: usize) -> (SessionManager, Duration) {
+    let manager = SessionManager::with_shards(shards);
+    let start = Instant::now();
+    for uid in 1..=SESSIONS {
+        // 内存管道不占用文件描述符
+        let (transport, _) = MemoryTransport::pair();
+        let connection = Connection::with_transport(Box::new(transport), Codec::default());
+        let write_queue = WriteQueue::new(16, OverflowPolicy::default());
+        manager.new_session(uid, "", connection, write_queue);
+    }
+    (manager, start.elapsed())
+}
+
+// 每个线程按伪随机顺序查找在线用户
+fn lookup(manager: &SessionManager, seed: u64) -> u64 {
+    let mut x = seed;
+    let mut found = 0;
+    for _ in 0..LOOKUPS_PER_THREAD {
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        found += manager.load_all(x % SESSIONS + 1).len() as u64;
+    }
+    found
+}
+
+fn bench(shards: usize) {
+    let (manager, insert_elapsed) = new_manager(shards);
+    let manager = Arc::new(manager);
+    assert_eq!(manager.len(), SESSIONS as usize);
+
+    let start = Instant::now();
+    assert_eq!(lookup(&manager, 1), LOOKUPS_PER_THREAD);
+    let single = start.elapsed();
+
+    let start = Instant::now();
+    let threads: Vec<_> = (1..=THREADS)
+        .map(|seed| {
+            let manager = manager.clone();
+            thread::spawn(move || lookup(&manager, seed))
+        })
+        .collect();
+    for thread in threads {
+        assert_eq!(thread.join().unwrap(), LOOKUPS_PER_THREAD);
+    }
+    let concurrent = start.elapsed();
+
+    println!(
+        "shards {:>3}: insert {} sessions {:>8.2?}, lookup {:>6.0} ns/op, \
+         {} threads {:>10.0} lookups/s",
+        shards,
+        SESSIONS,
+        insert_elapsed,
+        single.as_nanos() as f64 / LOOKUPS_PER_THREAD as f64,
+        THREADS,
+        (THREADS * LOOKUPS_PER_THREAD) as f64 / concurrent.as_secs_f64()
+    );
+}
+
+fn main() {
+    bench(1);
+    bench(DEFAULT_SESSION_SHARDS);
+}
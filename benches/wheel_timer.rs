@@ -0,0 +1,119 @@
+//! 100万个等待中的定时任务时, 单层时间轮与多层时间轮的开销对比.
+//!
+//! cargo bench --bench wheel_timer
+
+use cathy::{TimerTask, WheelTimer, DEFAULT_WHEEL_LEVELS};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const TIMEOUTS: u64 = 1_000_000;
+const PROBES: u64 = 200;
+const TICK_DURATION_MILLIS: u64 = 1;
+const TICKS_PER_WHEEL: u32 = 512;
+
+struct NoopTask;
+
+impl TimerTask for NoopTask {
+    fn run(&mut self) {}
+}
+
+// 记录到期任务的延迟, 单位毫秒
+struct ProbeTask {
+    deadline: u64,
+    fired: Arc<AtomicU64>,
+    lateness: Arc<AtomicU64>,
+}
+
+impl TimerTask for ProbeTask {
+    fn run(&mut self) {
+        let lateness = now().saturating_sub(self.deadline);
+        self.lateness.fetch_max(lateness, Ordering::SeqCst);
+        self.fired.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+fn wait_pending(timer: &WheelTimer, pending: u64) {
+    while timer.pending_timeouts() > pending {
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+fn bench(levels: u32) {
+    let mut timer = WheelTimer::with_levels(TICK_DURATION_MILLIS, TICKS_PER_WHEEL, levels).unwrap();
+
+    // 1小时到1天之后才到期的任务
+    let start = Instant::now();
+    let mut x: u64 = 88172645463325252;
+    let timeouts: Vec<_> = (0..TIMEOUTS)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            let delay = Duration::from_secs(3600 + x % (23 * 3600));
+            timer.new_timeout(Box::new(NoopTask), delay).unwrap()
+        })
+        .collect();
+    let schedule = start.elapsed();
+    // 等待所有任务放入时间轮
+    thread::sleep(Duration::from_millis(500));
+
+    // 大量远期任务存在时, 短延迟任务的执行延迟
+    let fired = Arc::new(AtomicU64::new(0));
+    let lateness = Arc::new(AtomicU64::new(0));
+    for _ in 0..PROBES {
+        let delay = Duration::from_millis(20);
+        let task = ProbeTask {
+            deadline: now() + 20,
+            fired: fired.clone(),
+            lateness: lateness.clone(),
+        };
+        timer.new_timeout(Box::new(task), delay).unwrap();
+        thread::sleep(Duration::from_millis(5));
+    }
+    while fired.load(Ordering::SeqCst) < PROBES {
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    let start = Instant::now();
+    for timeout in &timeouts {
+        timeout.cancel();
+    }
+    wait_pending(&timer, 0);
+    let cancel = start.elapsed();
+    drop(timeouts);
+
+    // 1秒内陆续到期的任务全部执行完的时间
+    let start = Instant::now();
+    for i in 0..TIMEOUTS {
+        let delay = Duration::from_millis(i * 1000 / TIMEOUTS);
+        timer.new_timeout(Box::new(NoopTask), delay).unwrap();
+    }
+    wait_pending(&timer, 0);
+    let expire = start.elapsed();
+    timer.stop();
+
+    println!(
+        "levels {}: schedule {} {:>8.2?}, probe max lateness {:>3} ms, \
+         cancel all {:>8.2?}, expire within 1s {:>8.2?}",
+        levels,
+        TIMEOUTS,
+        schedule,
+        lateness.load(Ordering::SeqCst),
+        cancel,
+        expire
+    );
+}
+
+fn main() {
+    bench(1);
+    bench(DEFAULT_WHEEL_LEVELS);
+}
@@ -0,0 +1,97 @@
+use crate::proto::Login;
+use crate::{IMError, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// 登录认证, Server在创建Session之前校验客户端提交的凭证.
+pub trait Authenticator: Send + Sync {
+    /// 校验登录凭证, 成功返回用户ID.
+    fn authenticate(&self, login: &Login) -> Result<u64>;
+}
+
+struct User {
+    uid: u64,
+    password: String,
+    token: Option<String>,
+}
+
+/// 基于文件的用户存储, 每行一个用户:
+/// -------------------------------------
+/// | uid username password [token]     |
+/// -------------------------------------
+/// 空行与 `#` 开头的行会被忽略.
+pub struct FileUserStore {
+    users: HashMap<String, User>,
+}
+
+impl FileUserStore {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<FileUserStore> {
+        let content = fs::read_to_string(path)?;
+        FileUserStore::parse(&content)
+    }
+
+    pub fn parse(content: &str) -> Result<FileUserStore> {
+        let mut users = HashMap::new();
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let items: Vec<&str> = line.split_whitespace().collect();
+            if items.len() != 3 && items.len() != 4 {
+                return Err(invalid_data(
+                    i + 1,
+                    "expected: uid username password [token]",
+                ));
+            }
+            let uid = match items[0].parse::<u64>() {
+                Ok(v) if v > 0 => v,
+                _ => return Err(invalid_data(i + 1, "uid must be a positive integer")),
+            };
+            let user = User {
+                uid,
+                password: items[2].to_string(),
+                token: items.get(3).map(|v| v.to_string()),
+            };
+            if users.insert(items[1].to_string(), user).is_some() {
+                return Err(invalid_data(i + 1, "duplicate username"));
+            }
+        }
+        Ok(FileUserStore { users })
+    }
+
+    pub fn len(&self) -> usize {
+        self.users.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+    }
+}
+
+impl Authenticator for FileUserStore {
+    fn authenticate(&self, login: &Login) -> Result<u64> {
+        if !login.get_token().is_empty() {
+            let token = Some(login.get_token().to_string());
+            return match self.users.values().find(|user| user.token == token) {
+                Some(user) => Ok(user.uid),
+                None => Err(IMError::Unauthorized("invalid token".to_string())),
+            };
+        }
+        match self.users.get(login.get_username()) {
+            Some(user) if user.password == login.get_password() => Ok(user.uid),
+            _ => Err(IMError::Unauthorized(
+                "invalid username or password".to_string(),
+            )),
+        }
+    }
+}
+
+fn invalid_data(line: usize, reason: &str) -> IMError {
+    IMError::Io(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("user store line {}: {}", line, reason),
+    ))
+}
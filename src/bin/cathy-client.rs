@@ -1,9 +1,164 @@
-use cathy::IMClient;
-use log::LevelFilter;
+use cathy::{ClientConfig, Event, IMClient};
+use log::{info, warn};
+use std::env;
+use std::io;
+use std::io::BufRead;
+use std::process;
 
 fn main() {
+    let (config, args) = match ClientConfig::load(env::args().skip(1)) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{}", e);
+            usage()
+        }
+    };
+    if args.len() != 2 {
+        usage()
+    }
     env_logger::builder()
-        .filter_level(LevelFilter::Debug)
+        .filter_level(config.level_filter().unwrap())
         .init();
-    IMClient::new().run();
+    let address = config.server_address.clone();
+    let mut client = match IMClient::connect(&address, config) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Couldn't connect to the server {}: {}", address, e);
+            process::exit(1);
+        }
+    };
+    client
+        .on_event(print_event)
+        .expect("Couldn't subscribe to the events...");
+    client
+        .login(&args[0], &args[1])
+        .expect("Couldn't send the login request...");
+    // 开启终端交互, 获取用户输入
+    start_terminal_interaction(&mut client);
+}
+
+fn print_event(event: Event) {
+    match event {
+        Event::Connected(msg) if msg.get_resumed() => {
+            info!("重连成功, 已恢复会话 uid = {}", msg.get_uid())
+        }
+        Event::Connected(msg) => info!(
+            "连接成功 uid = {}, session_id = {}",
+            msg.get_uid(),
+            msg.get_session_id()
+        ),
+        Event::Message(msg) => info!(
+            "收到用户 uid = {} 发来的消息 message_id = {}：{}",
+            msg.get_sender_uid(),
+            msg.get_message_id(),
+            msg.get_content()
+        ),
+        Event::Synced(msg) => info!(
+            "其他设备发送给用户 uid = {} 的消息 message_id = {}：{}",
+            msg.get_receiver_uid(),
+            msg.get_message_id(),
+            msg.get_content()
+        ),
+        Event::RoomMessage(msg) => info!(
+            "收到聊天室 room_id = {} 用户 uid = {} 的消息：{}",
+            msg.get_room_id(),
+            msg.get_sender_uid(),
+            msg.get_content()
+        ),
+        Event::RoomReply(reply) if reply.get_success() => info!(
+            "聊天室 room_id = {} {:?} 成功",
+            reply.get_room_id(),
+            reply.get_action()
+        ),
+        Event::RoomReply(reply) => warn!(
+            "聊天室 room_id = {} {:?} 失败：{}",
+            reply.get_room_id(),
+            reply.get_action(),
+            reply.get_reason()
+        ),
+        Event::PresenceList(presences) if presences.is_empty() => info!("没有在线用户"),
+        Event::PresenceList(presences) => {
+            for v in presences {
+                info!("用户 uid = {} {:?}", v.get_uid(), v.get_status());
+            }
+        }
+        Event::Presence(v) => info!("用户 uid = {} 状态变为 {:?}", v.get_uid(), v.get_status()),
+        Event::Accepted { .. } | Event::RoomAccepted { .. } => {}
+        Event::Delivered { seq, receiver_uid } => {
+            info!("消息 seq = {} 已送达用户 uid = {}", seq, receiver_uid)
+        }
+        Event::Read { seq, receiver_uid } => {
+            info!("消息 seq = {} 已被用户 uid = {} 阅读", seq, receiver_uid)
+        }
+        Event::Reconnecting { attempt, delay } => {
+            warn!("连接中断, {:?} 后第 {} 次重连", delay, attempt)
+        }
+        Event::Disconnected(reason) => {
+            warn!("连接已断开：{}", reason);
+            process::exit(0);
+        }
+    }
+}
+
+fn start_terminal_interaction(client: &mut IMClient) {
+    let stdin = io::stdin();
+    for line in stdin.lock().lines() {
+        let line = line.unwrap();
+        let items: Vec<&str> = line.splitn(3, ' ').collect();
+        let id = items
+            .get(1)
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(0);
+        let ret = match (items[0], items.len()) {
+            ("status", 1) => {
+                print_pending_messages(client);
+                Ok(())
+            }
+            ("read", 2) if id != 0 => client.read_receipt(id),
+            ("send", 3) if id != 0 => client.send_to_user(id, items[2]).map(|_| ()),
+            ("create", 2) => client.create_room(items[1]),
+            ("join", 2) if id != 0 => client.join_room(id),
+            ("leave", 2) if id != 0 => client.leave_room(id),
+            ("room", 3) if id != 0 => client.send_to_room(id, items[2]).map(|_| ()),
+            ("who", 1) => client.query_presence(&[]),
+            ("watch", 2) if id != 0 => client.subscribe_presence(&[id]),
+            ("away", 1) => client.set_away(true),
+            ("back", 1) => client.set_away(false),
+            _ => {
+                warn!(
+                    "支持的命令：send uid content | read message_id | status | \
+                     create name | join room_id | leave room_id | room room_id content | \
+                     who | watch uid | away | back"
+                );
+                Ok(())
+            }
+        };
+        if let Err(e) = ret {
+            warn!("{}：{}", items[0], e);
+        }
+    }
+}
+
+fn print_pending_messages(client: &IMClient) {
+    let pending = client.pending_messages();
+    if pending.is_empty() {
+        info!("没有未送达的消息");
+    }
+    for v in pending {
+        info!(
+            "seq = {}, message_id = {}, receiver_uid = {}, state = {:?}",
+            v.seq,
+            v.message_id,
+            v.msg.get_receiver_uid(),
+            v.state
+        );
+    }
+}
+
+fn usage() -> ! {
+    eprintln!(
+        "Usage: cathy-client [--config <file>] [--<option> <value>]... <username> <password>"
+    );
+    eprintln!("Options: see conf/client.toml, e.g. --server-address 127.0.0.1:8099");
+    process::exit(1);
 }
@@ -1,12 +1,74 @@
-use cathy::IMServer;
-use log::{info, LevelFilter};
-
-const DEFAULT_LISTENING_ADDRESS: &str = "127.0.0.1:8099";
+use cathy::{FileOfflineStore, FileUserStore, IMServer, LogMessageStore, ServerConfig};
+use log::{error, info};
+use std::env;
+use std::process;
 
 fn main() {
+    let config = match ServerConfig::load(env::args().skip(1)) {
+        Ok((config, args)) if args.is_empty() => config,
+        Ok(_) => usage(),
+        Err(e) => {
+            eprintln!("{}", e);
+            usage()
+        }
+    };
     env_logger::builder()
-        .filter_level(LevelFilter::Debug)
+        .filter_level(config.level_filter().unwrap())
         .init();
-    info!("Server listen on {}", DEFAULT_LISTENING_ADDRESS);
-    IMServer::new().run(DEFAULT_LISTENING_ADDRESS)
+    let user_store =
+        FileUserStore::open(&config.users_file).expect("Couldn't load the user store...");
+    info!(
+        "Loaded {} users from {}",
+        user_store.len(),
+        config.users_file
+    );
+    let message_store =
+        LogMessageStore::open(&config.message_dir).expect("Couldn't open the message store...");
+    let offline_store =
+        FileOfflineStore::open(&config.offline_dir).expect("Couldn't open the offline store...");
+
+    let address = config.listen_address.clone();
+    #[cfg(feature = "tls")]
+    let tls = match (&config.tls_cert_file, &config.tls_key_file) {
+        (Some(cert_file), Some(key_file)) => Some(
+            cathy::TlsAcceptor::from_files(cert_file, key_file)
+                .expect("Couldn't load the TLS certificate..."),
+        ),
+        _ => None,
+    };
+    let mut server = match IMServer::new(config, Box::new(user_store)) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Couldn't create the server: {}", e);
+            process::exit(1);
+        }
+    };
+    server.set_message_store(Box::new(message_store));
+    server.set_offline_store(Box::new(offline_store));
+    #[cfg(feature = "tls")]
+    if let Some(acceptor) = tls {
+        server.set_tls(acceptor);
+    }
+    // SIGINT/SIGTERM 时关闭服务端, 排空连接后 run 返回
+    let shutdown = server.shutdown_handle();
+    ctrlc::set_handler(move || {
+        info!("Received termination signal, shutting down...");
+        shutdown.shutdown();
+    })
+    .expect("Couldn't set the signal handler...");
+    info!("Server listen on {}", address);
+    #[cfg(feature = "event-loop")]
+    let ret = server.run_event_loop();
+    #[cfg(not(feature = "event-loop"))]
+    let ret = server.run();
+    if let Err(e) = ret {
+        error!("Couldn't run the server on {}: {}", address, e);
+        process::exit(1);
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: cathy-server [--config <file>] [--<option> <value>]...");
+    eprintln!("Options: see conf/server.toml, e.g. --listen-address 0.0.0.0:8099");
+    process::exit(1);
 }
@@ -1,45 +1,79 @@
 use crate::error::IMError;
 use crate::Result;
 use std::io::Read;
-use std::net::TcpStream;
 
-pub const BUFFER_MAX_LEN: usize = 4096;
+/// 缓冲区的初始大小, 放不下一个完整的数据包时按需扩容.
+pub const BUFFER_INIT_LEN: usize = 4096;
 
 pub struct Buffer {
     buf: Vec<u8>,
     start: usize,
     end: usize,
+    limit: usize, // 扩容的上限
+}
+
+impl Default for Buffer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Buffer {
     pub fn new() -> Buffer {
+        Self::with_limit(usize::MAX)
+    }
+
+    /// 最多扩容到 limit 个字节(不小于初始大小), 放不下一个完整的数据包时读取返回 ContentMaxLen.
+    pub fn with_limit(limit: usize) -> Buffer {
         Buffer {
-            buf: vec![0; BUFFER_MAX_LEN],
+            buf: vec![0; BUFFER_INIT_LEN],
             start: 0,
             end: 0,
+            limit: limit.max(BUFFER_INIT_LEN),
         }
     }
 
-    fn len(&self) -> usize {
+    /// 已读入未消费的字节数
+    pub fn len(&self) -> usize {
         self.end - self.start
     }
 
-    // 将有效的字节前移
-    fn grow(&mut self) {
-        if self.start == 0 {
-            return;
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 当前分配的容量
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    // 将有效的字节前移, 仍然没有空闲空间时扩容一倍. 数据已全部消费时释放扩容的空间.
+    fn grow(&mut self) -> Result<()> {
+        if self.is_empty() && self.buf.len() > BUFFER_INIT_LEN {
+            self.buf = vec![0; BUFFER_INIT_LEN];
+            self.start = 0;
+            self.end = 0;
+            return Ok(());
         }
-        let temp = Vec::from(&self.buf[self.start..self.end]);
-        let valid_num = self.end - self.start;
-        self.buf[..valid_num].copy_from_slice(&temp);
-        self.end -= self.start;
-        self.start = 0
+        if self.start > 0 {
+            self.buf.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+        }
+        if self.end == self.buf.len() {
+            if self.buf.len() >= self.limit {
+                return Err(IMError::ContentMaxLen);
+            }
+            let len = self.buf.len().saturating_mul(2).min(self.limit);
+            self.buf.resize(len, 0);
+        }
+        Ok(())
     }
 
-    // 从stream中读取字节，如果reader阻塞，发生阻塞
-    pub fn read_from_reader(&mut self, stream: &mut TcpStream) -> Result<()> {
-        self.grow();
-        let n = stream.read(&mut self.buf[self.end..])?;
+    // 从reader中读取字节，如果reader阻塞，发生阻塞
+    pub fn read_from_reader<R: Read>(&mut self, reader: &mut R) -> Result<()> {
+        self.grow()?;
+        let n = reader.read(&mut self.buf[self.end..])?;
         if n == 0 {
             return Err(IMError::TcpStreamEOF);
         }
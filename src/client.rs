@@ -1,177 +1,1017 @@
 use crate::proto::{
-    Action::CONNECTED, Action::HEARTBEAT, Action::MSG_TO_USER, ConnectedReply, MsgToUser, Package,
+    Action, Action::CONNECTED, Action::CREATE_ROOM, Action::DELIVERED_ACK, Action::HEARTBEAT,
+    Action::JOIN_ROOM, Action::LEAVE_ROOM, Action::LOGIN, Action::LOGIN_FAILED, Action::MSG_ACK,
+    Action::MSG_TO_ROOM, Action::MSG_TO_USER, Action::PRESENCE, Action::PRESENCE_LIST,
+    Action::PRESENCE_QUERY, Action::PRESENCE_SUBSCRIBE, Action::PRESENCE_UNSUBSCRIBE,
+    Action::READ_RECEIPT, Action::ROOM_REPLY, Action::SERVER_CLOSING, ConnectedReply, Login,
+    LoginFailedReply, MsgAck, MsgToRoom, MsgToUser, Package, Presence, PresenceList,
+    PresenceRequest, PresenceStatus, Receipt, RoomReply, RoomRequest, ServerClosing,
 };
-use crate::wheel_timer;
 use crate::wheel_timer::system_time_unix;
-use crate::Connection;
-use crate::{TimerTask, WheelTimer};
-use log::{debug, info, warn};
+#[cfg(feature = "tls")]
+use crate::TlsConnector;
+use crate::{
+    ClientConfig, Codec, Connection, IMError, Outbox, PendingMessage, RetransmitPolicy, SendState,
+};
+use crate::{Clock, SystemClock, TimerTask, WheelTimer, DEFAULT_WHEEL_LEVELS};
+use log::{debug, warn};
 use protobuf::Message;
-use std::io;
-use std::io::BufRead;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::net::TcpStream;
 use std::ops::Deref;
+use std::process;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// 记住最近收到的消息ID, 用于识别服务端重传的消息.
+const RECENT_MESSAGE_CAPACITY: usize = 1024;
+
+/// 重连等待时间的随机抖动使用的 xorshift 状态, 0 表示还没有设置种子.
+static JITTER_STATE: AtomicU64 = AtomicU64::new(0);
+
+/// 客户端事件, 由接收线程按收到的顺序产生.
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// 登录成功, 重连成功后再次产生, ConnectedReply::get_resumed 表示是否恢复了原来的会话
+    Connected(ConnectedReply),
+    /// 收到其他用户的消息, 服务端重传的重复消息只通知一次
+    Message(MsgToUser),
+    /// 当前用户在其他设备上发给别人的消息, 用于多设备同步
+    Synced(MsgToUser),
+    /// 收到已加入聊天室的消息
+    RoomMessage(MsgToRoom),
+    /// 创建、加入或离开聊天室的结果
+    RoomReply(RoomReply),
+    /// 查询或订阅在线状态的结果, 重连后自动重新订阅时同样产生
+    PresenceList(Vec<Presence>),
+    /// 订阅的用户上线、离线或离开
+    Presence(Presence),
+    /// 发出的消息已被服务端接收并分配消息ID
+    Accepted { seq: u64, message_id: u64 },
+    /// 发出的聊天室消息已被服务端接收, message_id 是聊天室内的消息ID, 与单聊消息的ID无关
+    RoomAccepted {
+        seq: u64,
+        room_id: u64,
+        message_id: u64,
+    },
+    /// 发出的消息已送达接收方
+    Delivered { seq: u64, receiver_uid: u64 },
+    /// 发出的消息已被接收方阅读
+    Read { seq: u64, receiver_uid: u64 },
+    /// 连接中断, 等待 delay 之后第 attempt 次重连
+    Reconnecting { attempt: u32, delay: Duration },
+    /// 连接断开(登录失败、服务端关闭或放弃重连), 之后不再有事件
+    Disconnected(String),
+}
+
+/// 断线重连策略, 第n次重连前等待 initial_delay * 2^(n-1), 不超过 max_delay,
+/// 实际等待时间在其一半到全部之间随机选取, 避免大量客户端同时重连.
+#[derive(Debug, Clone, Copy)]
+pub struct ReconnectPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub max_attempts: u32, // 连续重连失败的最多次数, 0 表示不重连
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            max_attempts: 10,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// 第 attempt 次重连之前等待的时间, attempt 从1开始.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u32 << attempt.saturating_sub(1).min(16);
+        let delay = self
+            .initial_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+        let half = delay / 2;
+        let jitter = next_jitter() % (half.as_millis() as u64 + 1);
+        half + Duration::from_millis(jitter)
+    }
+}
 
-/// Client链路write检测, 默认30秒, 30秒没有向链路写入任何数据时, Client会主动向Server发送心跳数据包.
-const WRITER_IDLE_TIME_SECONDS: u64 = 30;
-const DEFAULT_SERVER_ADDRESS: &str = "127.0.0.1:8099";
+// xorshift64 伪随机数, 第一次调用时以当前时间和进程号作为种子, 同时重连的多个进程错开等待时间
+fn next_jitter() -> u64 {
+    let mut current = JITTER_STATE.load(Ordering::Relaxed);
+    loop {
+        let mut x = current;
+        if x == 0 {
+            let nanos = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |v| v.as_nanos() as u64);
+            x = (nanos ^ (process::id() as u64) << 32) | 1;
+        }
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        match JITTER_STATE.compare_exchange_weak(current, x, Ordering::Relaxed, Ordering::Relaxed) {
+            Ok(_) => return x,
+            Err(v) => current = v,
+        }
+    }
+}
+
+/// send_to_user 发出的消息, 用于查询投递状态.
+#[derive(Clone)]
+pub struct MessageHandle {
+    seq: u64,
+    outbox: Arc<Mutex<Outbox>>,
+}
+
+impl MessageHandle {
+    /// 客户端分配的消息序列号, 与 Event::Accepted 等事件中的 seq 对应.
+    pub fn get_seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// 服务端分配的消息ID, 服务端确认之前为0.
+    pub fn get_message_id(&self) -> u64 {
+        let outbox = self.outbox.lock().unwrap();
+        outbox.get(self.seq).map(|v| v.message_id).unwrap_or(0)
+    }
+
+    /// 投递状态, 记录被淘汰后返回 None.
+    pub fn state(&self) -> Option<SendState> {
+        self.outbox.lock().unwrap().state(self.seq)
+    }
+}
 
+/// 可嵌入的客户端. connect 建立连接后, 在 login 之后通过事件接收消息与投递状态:
+///
+/// ```no_run
+/// use cathy::{ClientConfig, Event, IMClient};
+///
+/// let mut client = IMClient::connect("127.0.0.1:8099", ClientConfig::default())?;
+/// let events = client.events().unwrap();
+/// client.login("cathy", "cathy123")?;
+/// for event in events {
+///     if let Event::Connected(_) = event {
+///         client.send_to_user(2, "hello")?;
+///     }
+/// }
+/// # Ok::<(), cathy::IMError>(())
+/// ```
+///
+/// 登录之后连接中断或服务端通知即将关闭时按 ReconnectPolicy 自动重连, 并凭 session_id 与
+/// resume_token 恢复原来的会话, 断线期间发出的消息在重连后重发.
 pub struct IMClient {
-    connection: Connection,
+    link: Link,
     timer: WheelTimer,
     last_seq: AtomicU64,
+    outbox: Arc<Mutex<Outbox>>,
+    inbox: Arc<Mutex<HashMap<u64, u64>>>, // 已收到未读的消息, key => message_id, value => sender_uid
+    room_sends: Arc<Mutex<BTreeMap<u64, u64>>>, // 等待确认的聊天室消息, key => seq, value => room_id
+    resume: Arc<Mutex<Resume>>,
+    events: Option<Receiver<Event>>,
+    retransmit_policy: RetransmitPolicy,
+    config: ClientConfig,
 }
 
 impl IMClient {
-    pub fn new() -> IMClient {
-        let stream =
-            TcpStream::connect(DEFAULT_SERVER_ADDRESS).expect("Couldn't connect to the server...");
-        let connection = Connection::new(stream);
-        IMClient {
+    /// 连接 address 并完成协议握手, 配置中的 server_address 被 address 替换.
+    /// 连接建立后开始接收数据包, 但需要 login 之后服务端才会推送消息.
+    pub fn connect(address: &str, config: ClientConfig) -> crate::Result<IMClient> {
+        IMClient::connect_with_clock(address, config, Arc::new(SystemClock))
+    }
+
+    /// 与 connect 相同, 但写空闲检测和连接的读写时间使用 clock.
+    pub fn connect_with_clock(
+        address: &str,
+        mut config: ClientConfig,
+        clock: Arc<dyn Clock>,
+    ) -> crate::Result<IMClient> {
+        config.server_address = address.to_string();
+        let connection = open(&config, &clock)?;
+        let timer = WheelTimer::with_clock(
+            config.tick_duration_millis,
+            config.ticks_per_wheel,
+            DEFAULT_WHEEL_LEVELS,
+            clock.clone(),
+        )
+        .map_err(|e| IMError::InvalidConfig(e.to_string()))?;
+        timer.set_worker_threads(config.timer_threads);
+        let link = Link::new(connection.clone());
+        let outbox = Arc::new(Mutex::new(Outbox::new()));
+        let inbox = Arc::new(Mutex::new(HashMap::new()));
+        let room_sends = Arc::new(Mutex::new(BTreeMap::new()));
+        let resume = Arc::new(Mutex::new(Resume {
+            login: None,
+            policy: ReconnectPolicy::default(),
+            subscriptions: HashSet::new(),
+            away: false,
+        }));
+        let (sender, receiver) = mpsc::channel();
+        // 开启一个线程，接收消息
+        let mut subscriber = Subscriber {
+            link: link.clone(),
             connection,
-            timer: WheelTimer::new(100, 12).unwrap(),
+            outbox: outbox.clone(),
+            inbox: inbox.clone(),
+            room_sends: room_sends.clone(),
+            resume: resume.clone(),
+            events: sender,
+            config: config.clone(),
+            clock,
+            uid: 0,
+            recent_messages: HashSet::new(),
+            recent_message_order: VecDeque::new(),
+        };
+        thread::spawn(move || subscriber.run());
+        Ok(IMClient {
+            link,
+            timer,
             last_seq: AtomicU64::new(1),
-        }
+            outbox,
+            inbox,
+            room_sends,
+            resume,
+            events: Some(receiver),
+            retransmit_policy: RetransmitPolicy::default(),
+            config,
+        })
+    }
+
+    /// 事件流, 只能取出一次, 可以在其他线程中迭代. 连接断开后迭代结束.
+    pub fn events(&mut self) -> Option<Receiver<Event>> {
+        self.events.take()
+    }
+
+    /// 在单独的线程中依次处理每个事件, 与 events 只能二选一.
+    pub fn on_event<F>(&mut self, mut callback: F) -> crate::Result<()>
+    where
+        F: FnMut(Event) + Send + 'static,
+    {
+        let events = self.events().ok_or(IMError::ConnectionClosed)?;
+        thread::spawn(move || {
+            for event in events {
+                callback(event);
+            }
+        });
+        Ok(())
+    }
+
+    /// 发出的消息在未收到服务端确认时的重传策略.
+    pub fn set_retransmit_policy(&mut self, policy: RetransmitPolicy) {
+        self.retransmit_policy = policy;
+    }
+
+    /// 连接中断时的重连策略, 之后的重连生效.
+    pub fn set_reconnect_policy(&mut self, policy: ReconnectPolicy) {
+        self.resume.lock().unwrap().policy = policy;
     }
 
-    pub fn run(&mut self) {
+    /// 提交登录凭证, Server认证通过后产生 Event::Connected, 失败时产生 Event::Disconnected.
+    /// 凭证会被保留, 重连时会话已过期则重新认证.
+    pub fn login(&mut self, username: &str, password: &str) -> crate::Result<()> {
+        let mut login_pb = Login::new();
+        login_pb.set_username(username.to_string());
+        login_pb.set_password(password.to_string());
+        if let Some(device_id) = &self.config.device_id {
+            login_pb.set_device_id(device_id.clone());
+        }
+        self.write(LOGIN, &login_pb)?;
+        self.resume.lock().unwrap().login = Some(login_pb);
         // write空闲检测
         self.init_writer_idle_timeout();
-        // 开启一个线程，接收消息
-        let mut connection = self.connection.clone();
-        thread::spawn(move || loop {
-            match connection.read_package() {
-                Ok(p) => {
-                    match p.get_action() {
-                        CONNECTED => {
-                            let msg = ConnectedReply::parse_from_bytes(p.get_content()).unwrap();
-                            debug!(
-                                "连接成功 uid = {}, session_id = {}",
-                                msg.get_uid(),
-                                msg.get_session_id()
-                            );
-                        }
-                        HEARTBEAT => {
-                            // nothing to do
-                        }
-                        MSG_TO_USER => {
-                            let msg = MsgToUser::parse_from_bytes(p.get_content()).unwrap();
-                            info!(
-                                "收到用户 uid = {} 发来的消息：{}",
-                                msg.get_sender_uid(),
-                                msg.get_content()
-                            )
-                        }
+        Ok(())
+    }
+
+    /// 发送消息给用户 uid, 未收到服务端确认时按重传策略重发.
+    /// 连接中断等待重连时消息先保留, 重连成功后重发. 客户端已关闭时返回错误.
+    pub fn send_to_user(&mut self, uid: u64, text: &str) -> crate::Result<MessageHandle> {
+        if self.link.is_closed() {
+            return Err(IMError::ConnectionClosed);
+        }
+        let seq = self.last_seq.fetch_add(1, Ordering::SeqCst);
+        let mut msg_pb = MsgToUser::new();
+        msg_pb.set_seq(seq);
+        msg_pb.set_sender_uid(0);
+        msg_pb.set_receiver_uid(uid);
+        msg_pb.set_message_id(0);
+        msg_pb.set_content(text.to_string());
+        msg_pb.set_timestamp(system_time_unix());
+        // 先记录再发送, 避免确认先于记录到达
+        self.outbox.lock().unwrap().add(msg_pb.clone());
+        match self.write(MSG_TO_USER, &msg_pb) {
+            Ok(()) => debug!("发送消息 seq = {} 到用户 uid = {}", seq, uid),
+            Err(e) if self.reconnecting() => {
+                debug!("连接中断, 消息 seq = {} 在重连后重发: {}", seq, e);
+            }
+            Err(e) => {
+                self.outbox.lock().unwrap().failed(seq);
+                return Err(e);
+            }
+        }
+
+        let task = ResendTask {
+            seq,
+            attempt: 1,
+            link: self.link.clone(),
+            outbox: self.outbox.clone(),
+            timer: self.timer.clone(),
+            policy: self.retransmit_policy,
+            write_timeout: self.config.write_timeout(),
+        };
+        let ret = self
+            .timer
+            .new_timeout(Box::new(task), self.retransmit_policy.delay(1));
+        if let Err(e) = ret {
+            warn!("消息 seq = {} 无法安排重传: {}", seq, e);
+        }
+        Ok(MessageHandle {
+            seq,
+            outbox: self.outbox.clone(),
+        })
+    }
+
+    /// 发送聊天室消息, 返回消息序列号. 聊天室消息不做重传, 服务端确认后产生 Event::RoomAccepted.
+    pub fn send_to_room(&mut self, room_id: u64, text: &str) -> crate::Result<u64> {
+        let seq = self.last_seq.fetch_add(1, Ordering::SeqCst);
+        let mut msg_pb = MsgToRoom::new();
+        msg_pb.set_seq(seq);
+        msg_pb.set_room_id(room_id);
+        msg_pb.set_content(text.to_string());
+        msg_pb.set_timestamp(system_time_unix());
+        {
+            // 序列号递增, 超过容量时忘掉最早的未确认消息
+            let mut room_sends = self.room_sends.lock().unwrap();
+            room_sends.insert(seq, room_id);
+            if room_sends.len() > RECENT_MESSAGE_CAPACITY {
+                room_sends.pop_first();
+            }
+        }
+        self.write(MSG_TO_ROOM, &msg_pb)?;
+        Ok(seq)
+    }
+
+    /// 创建聊天室, 结果通过 Event::RoomReply 返回.
+    pub fn create_room(&mut self, name: &str) -> crate::Result<()> {
+        self.room_request(CREATE_ROOM, 0, name)
+    }
+
+    pub fn join_room(&mut self, room_id: u64) -> crate::Result<()> {
+        self.room_request(JOIN_ROOM, room_id, "")
+    }
+
+    pub fn leave_room(&mut self, room_id: u64) -> crate::Result<()> {
+        self.room_request(LEAVE_ROOM, room_id, "")
+    }
+
+    /// 查询用户的在线状态, uids 为空时查询所有在线用户, 结果通过 Event::PresenceList 返回.
+    pub fn query_presence(&mut self, uids: &[u64]) -> crate::Result<()> {
+        self.write(PRESENCE_QUERY, &presence_request(uids))
+    }
+
+    /// 订阅用户的在线状态, 先通过 Event::PresenceList 返回当前状态, 之后的变化通过 Event::Presence 通知.
+    /// 订阅在重连后自动恢复.
+    pub fn subscribe_presence(&mut self, uids: &[u64]) -> crate::Result<()> {
+        self.resume
+            .lock()
+            .unwrap()
+            .subscriptions
+            .extend(uids.iter().copied());
+        self.write(PRESENCE_SUBSCRIBE, &presence_request(uids))
+    }
+
+    pub fn unsubscribe_presence(&mut self, uids: &[u64]) -> crate::Result<()> {
+        let mut resume = self.resume.lock().unwrap();
+        for uid in uids {
+            resume.subscriptions.remove(uid);
+        }
+        drop(resume);
+        self.write(PRESENCE_UNSUBSCRIBE, &presence_request(uids))
+    }
+
+    /// 设置为离开或恢复在线, 订阅了当前用户的其他用户会收到通知.
+    pub fn set_away(&mut self, away: bool) -> crate::Result<()> {
+        self.resume.lock().unwrap().away = away;
+        self.write(PRESENCE, &presence_status(away))
+    }
+
+    /// 告知发送方消息已读, message_id 需是收到但还未回复已读的消息.
+    pub fn read_receipt(&mut self, message_id: u64) -> crate::Result<()> {
+        let sender_uid = match self.inbox.lock().unwrap().remove(&message_id) {
+            Some(v) => v,
+            None => return Err(IMError::MessageNotFound(message_id)),
+        };
+        let mut receipt = Receipt::new();
+        receipt.set_message_id(message_id);
+        receipt.set_sender_uid(sender_uid);
+        receipt.set_timestamp(system_time_unix());
+        self.write(READ_RECEIPT, &receipt)
+    }
+
+    /// 发出消息的投递状态, seq 为 send_to_user 时分配的消息序列号.
+    pub fn message_state(&self, seq: u64) -> Option<SendState> {
+        self.outbox.lock().unwrap().state(seq)
+    }
+
+    /// 尚未送达接收方的消息.
+    pub fn pending_messages(&self) -> Vec<PendingMessage> {
+        self.outbox.lock().unwrap().pending()
+    }
+
+    /// 客户端已关闭, 不会再重连.
+    pub fn is_closed(&self) -> bool {
+        self.link.is_closed()
+    }
+
+    /// 当前连接可用, 重连过程中返回 false.
+    pub fn is_connected(&self) -> bool {
+        self.link.is_connected()
+    }
+
+    /// 断开连接并停止定时任务与重连, 接收线程随后产生 Event::Disconnected.
+    pub fn close(&mut self) {
+        self.link.close();
+        self.timer.stop();
+    }
+
+    // 登录过并且允许重连, 连接中断后会自动重连
+    fn reconnecting(&self) -> bool {
+        let resume = self.resume.lock().unwrap();
+        resume.login.is_some() && resume.policy.max_attempts > 0
+    }
+
+    fn init_writer_idle_timeout(&mut self) {
+        let timeout_task = WriterIdleTimeoutTask::new(
+            self.link.clone(),
+            self.timer.clone(),
+            self.config.writer_idle_timeout(),
+            self.config.write_timeout(),
+        );
+        let ret = self
+            .timer
+            .new_timeout(Box::new(timeout_task), self.config.writer_idle_timeout());
+        if let Err(e) = ret {
+            warn!("无法开启写空闲检测: {}", e);
+        }
+    }
+
+    fn room_request(&mut self, action: Action, room_id: u64, name: &str) -> crate::Result<()> {
+        let mut request = RoomRequest::new();
+        request.set_room_id(room_id);
+        request.set_name(name.to_string());
+        self.write(action, &request)
+    }
+
+    fn write<M: Message>(&mut self, action: Action, msg: &M) -> crate::Result<()> {
+        self.link
+            .write_package(package(action, msg), self.config.write_timeout())
+    }
+}
+
+impl Drop for IMClient {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+// 客户端当前使用的连接, 重连成功后替换为新的连接. 所有写入都经过这里, 不会交错
+#[derive(Clone)]
+struct Link {
+    connection: Arc<Mutex<Connection>>,
+    closed: Arc<(Mutex<bool>, Condvar)>, // 调用 close 或放弃重连后为 true, 不再重连
+}
+
+impl Link {
+    fn new(connection: Connection) -> Link {
+        Link {
+            connection: Arc::new(Mutex::new(connection)),
+            closed: Arc::new((Mutex::new(false), Condvar::new())),
+        }
+    }
+
+    fn write_package(&self, package: Package, write_timeout: Duration) -> crate::Result<()> {
+        if self.is_closed() {
+            return Err(IMError::ConnectionClosed);
+        }
+        let mut connection = self.connection.lock().unwrap();
+        if connection.is_closed() {
+            return Err(IMError::ConnectionClosed);
+        }
+        connection.write_package(package, write_timeout)
+    }
+
+    fn get_last_write_time(&self) -> Instant {
+        self.connection.lock().unwrap().get_last_write_time()
+    }
+
+    fn replace(&self, connection: Connection) {
+        *self.connection.lock().unwrap() = connection;
+    }
+
+    fn is_connected(&self) -> bool {
+        !self.is_closed() && !self.connection.lock().unwrap().is_closed()
+    }
+
+    fn is_closed(&self) -> bool {
+        *self.closed.0.lock().unwrap()
+    }
+
+    // 等待 timeout 或者客户端关闭, 返回是否已关闭
+    fn wait_closed(&self, timeout: Duration) -> bool {
+        let (lock, condvar) = self.closed.deref();
+        let guard = lock.lock().unwrap();
+        let (guard, _) = condvar
+            .wait_timeout_while(guard, timeout, |closed| !*closed)
+            .unwrap();
+        *guard
+    }
+
+    fn close(&self) {
+        let (lock, condvar) = self.closed.deref();
+        *lock.lock().unwrap() = true;
+        condvar.notify_all();
+        self.connection.lock().unwrap().shutdown();
+    }
+}
+
+// 重连需要的状态
+struct Resume {
+    login: Option<Login>, // 登录凭证, 登录成功后带上 session_id
+    policy: ReconnectPolicy,
+    subscriptions: HashSet<u64>, // 订阅在线状态的用户, 重连后重新订阅
+    away: bool,                  // 设置为离开, 重连后重新设置
+}
+
+// 连接服务端并完成协议握手
+fn open(config: &ClientConfig, clock: &Arc<dyn Clock>) -> crate::Result<Connection> {
+    let stream = TcpStream::connect(&config.server_address)?;
+    let codec = Codec::new(config.max_frame_size, config.content_max_len);
+    #[cfg(feature = "tls")]
+    let mut connection = match &config.tls_ca_file {
+        Some(ca_file) => {
+            let connector = TlsConnector::from_file(ca_file)?;
+            let stream = connector.connect(&config.server_name(), stream)?;
+            Connection::with_transport(Box::new(stream), codec)
+        }
+        None => Connection::with_codec(stream, codec),
+    };
+    #[cfg(not(feature = "tls"))]
+    let mut connection = Connection::with_codec(stream, codec);
+    connection.set_clock(clock.clone());
+    connection.handshake(config.write_timeout())?;
+    Ok(connection)
+}
+
+// 接收停止的原因
+enum Stop {
+    // 不再重连, 例如登录失败或客户端主动关闭
+    Closed(String),
+    // 连接中断或服务端即将关闭, 可以重连
+    Broken(String),
+}
+
+// 接收线程, 处理服务端推送的数据包, 连接中断时负责重连
+struct Subscriber {
+    link: Link,
+    connection: Connection, // 当前连接的读端
+    outbox: Arc<Mutex<Outbox>>,
+    inbox: Arc<Mutex<HashMap<u64, u64>>>,
+    room_sends: Arc<Mutex<BTreeMap<u64, u64>>>,
+    resume: Arc<Mutex<Resume>>,
+    events: Sender<Event>,
+    config: ClientConfig,
+    clock: Arc<dyn Clock>,
+    uid: u64, // 登录成功后的用户ID
+    recent_messages: HashSet<u64>,
+    recent_message_order: VecDeque<u64>,
+}
+
+impl Subscriber {
+    fn run(&mut self) {
+        let reason = loop {
+            match self.receive() {
+                Stop::Closed(reason) => break reason,
+                Stop::Broken(reason) => {
+                    if !self.reconnect() {
+                        break reason;
                     }
                 }
+            }
+        };
+        self.link.close();
+        let _ = self.events.send(Event::Disconnected(reason));
+    }
+
+    // 接收数据包直到连接断开
+    fn receive(&mut self) -> Stop {
+        loop {
+            match self.connection.read_package() {
+                Ok(p) => match self.on_package(p) {
+                    Ok(None) => {}
+                    Ok(Some(stop)) => return stop,
+                    // 无法解析的数据包跳过, 违规次数过多时断开
+                    Err(e) => {
+                        warn!("丢弃数据包: {}", e);
+                        if let Err(e) = self.connection.protocol_violation() {
+                            warn!("断开连接: {}", e);
+                            self.connection.shutdown();
+                            return Stop::Closed(e.to_string());
+                        }
+                    }
+                },
                 Err(e) => {
-                    connection.set_closed();
+                    self.connection.set_closed();
                     debug!("Subscription interrupted {}", e);
-                    return;
+                    if self.link.is_closed() {
+                        return Stop::Closed("closed".to_string());
+                    }
+                    return Stop::Broken(e.to_string());
                 }
             }
-        });
-        thread::sleep(Duration::from_millis(10));
-        // 开启终端交互, 获取用户输入
-        self.start_terminal_interaction();
+        }
+    }
+
+    // 按策略重连并重新登录, 返回 false 时放弃
+    fn reconnect(&mut self) -> bool {
+        let (login, policy) = {
+            let resume = self.resume.lock().unwrap();
+            (resume.login.clone(), resume.policy)
+        };
+        // 还未登录时不重连
+        let login = match login {
+            Some(v) => v,
+            None => return false,
+        };
+        for attempt in 1..=policy.max_attempts {
+            let delay = policy.delay(attempt);
+            self.emit(Event::Reconnecting { attempt, delay });
+            if self.link.wait_closed(delay) {
+                return false;
+            }
+            let ret = open(&self.config, &self.clock).and_then(|mut connection| {
+                connection.write_package(package(LOGIN, &login), self.config.write_timeout())?;
+                // 登录之后紧接着恢复在线状态的订阅, 服务端按顺序处理
+                for package in self.presence_packages() {
+                    connection.write_package(package, self.config.write_timeout())?;
+                }
+                Ok(connection)
+            });
+            match ret {
+                Ok(connection) => {
+                    debug!("第 {} 次重连成功", attempt);
+                    self.link.replace(connection.clone());
+                    self.connection = connection;
+                    // 与 close 并发时, 新的连接也要关闭
+                    if self.link.is_closed() {
+                        self.connection.shutdown();
+                        return false;
+                    }
+                    return true;
+                }
+                Err(e) => debug!("第 {} 次重连失败: {}", attempt, e),
+            }
+        }
+        false
     }
 
-    fn start_terminal_interaction(&mut self) {
-        let stdin = io::stdin();
-        for line in stdin.lock().lines() {
-            let line = line.unwrap();
-            if !line.starts_with("send") {
-                warn!("仅支持消息命令：send uid content");
-                continue;
+    // 返回 Ok(Some(stop)) 时停止接收
+    fn on_package(&mut self, p: Package) -> crate::Result<Option<Stop>> {
+        match p.get_action() {
+            CONNECTED => {
+                let msg: ConnectedReply = parse(&p)?;
+                debug!(
+                    "连接成功 uid = {}, session_id = {}, version = {}, resumed = {}",
+                    msg.get_uid(),
+                    msg.get_session_id(),
+                    msg.get_version(),
+                    msg.get_resumed()
+                );
+                self.uid = msg.get_uid();
+                // 重连时带上 session_id 与 resume_token 恢复会话
+                if let Some(login) = self.resume.lock().unwrap().login.as_mut() {
+                    login.set_session_id(msg.get_session_id().to_string());
+                    login.set_resume_token(msg.get_resume_token().to_string());
+                }
+                self.replay();
+                self.emit(Event::Connected(msg));
             }
-            let items: Vec<&str> = line.split(' ').collect();
-            if items.len() != 3 {
-                warn!("仅支持消息命令：send uid content");
-                continue;
+            LOGIN_FAILED => {
+                let msg: LoginFailedReply = parse(&p)?;
+                debug!("登录失败：{}", msg.get_reason());
+                self.connection.shutdown();
+                return Ok(Some(Stop::Closed(msg.get_reason().to_string())));
             }
-            let mut uid = 0;
-            if let Ok(v) = items.get(1).unwrap().parse::<u64>() {
-                uid = v;
+            SERVER_CLOSING => {
+                let msg: ServerClosing = parse(&p)?;
+                debug!("服务端即将关闭：{}", msg.get_reason());
+                // 主动断开, 服务端不必等到超时; 之后按重连策略连接重启后的服务端
+                self.connection.shutdown();
+                return Ok(Some(Stop::Broken(msg.get_reason().to_string())));
             }
-            if uid == 0 {
-                warn!("仅支持消息命令：send uid content");
-                continue;
+            HEARTBEAT | LOGIN | CREATE_ROOM | JOIN_ROOM | LEAVE_ROOM | PRESENCE_QUERY
+            | PRESENCE_SUBSCRIBE | PRESENCE_UNSUBSCRIBE => {
+                // nothing to do
+            }
+            MSG_TO_USER => {
+                let msg: MsgToUser = parse(&p)?;
+                // 自己在其他设备发出的消息, 不需要回复确认
+                if msg.get_sender_uid() == self.uid && msg.get_receiver_uid() != self.uid {
+                    self.emit(Event::Synced(msg));
+                    return Ok(None);
+                }
+                // 服务端未收到送达确认时会重传, 重复的消息只回复确认
+                // 告知发送方消息已送达
+                let mut receipt = Receipt::new();
+                receipt.set_message_id(msg.get_message_id());
+                receipt.set_sender_uid(msg.get_sender_uid());
+                receipt.set_timestamp(system_time_unix());
+                let _ = self.link.write_package(
+                    package(DELIVERED_ACK, &receipt),
+                    self.config.write_timeout(),
+                );
+                if self.remember_message(msg.get_message_id()) {
+                    self.inbox
+                        .lock()
+                        .unwrap()
+                        .insert(msg.get_message_id(), msg.get_sender_uid());
+                    self.emit(Event::Message(msg));
+                }
+            }
+            MSG_ACK => {
+                let ack: MsgAck = parse(&p)?;
+                // 聊天室消息的ID由聊天室分配, 只确认自己发出的聊天室消息
+                if ack.get_room_id() != 0 {
+                    let mut room_sends = self.room_sends.lock().unwrap();
+                    if room_sends.get(&ack.get_seq()) == Some(&ack.get_room_id()) {
+                        room_sends.remove(&ack.get_seq());
+                        drop(room_sends);
+                        self.emit(Event::RoomAccepted {
+                            seq: ack.get_seq(),
+                            room_id: ack.get_room_id(),
+                            message_id: ack.get_message_id(),
+                        });
+                    }
+                    return Ok(None);
+                }
+                let mut outbox = self.outbox.lock().unwrap();
+                if let Some(v) = outbox.accepted(ack.get_seq(), ack.get_message_id()) {
+                    debug!(
+                        "消息 seq = {} 已被服务端接收 message_id = {}",
+                        v.seq, v.message_id
+                    );
+                    let (seq, message_id) = (v.seq, v.message_id);
+                    drop(outbox);
+                    self.emit(Event::Accepted { seq, message_id });
+                }
+            }
+            DELIVERED_ACK => {
+                let receipt: Receipt = parse(&p)?;
+                let mut outbox = self.outbox.lock().unwrap();
+                if let Some(v) = outbox.delivered(receipt.get_message_id()) {
+                    let seq = v.seq;
+                    drop(outbox);
+                    self.emit(Event::Delivered {
+                        seq,
+                        receiver_uid: receipt.get_receiver_uid(),
+                    });
+                }
+            }
+            READ_RECEIPT => {
+                let receipt: Receipt = parse(&p)?;
+                let mut outbox = self.outbox.lock().unwrap();
+                if let Some(v) = outbox.read(receipt.get_message_id()) {
+                    let seq = v.seq;
+                    drop(outbox);
+                    self.emit(Event::Read {
+                        seq,
+                        receiver_uid: receipt.get_receiver_uid(),
+                    });
+                }
+            }
+            ROOM_REPLY => {
+                let reply: RoomReply = parse(&p)?;
+                self.emit(Event::RoomReply(reply));
+            }
+            MSG_TO_ROOM => {
+                let msg: MsgToRoom = parse(&p)?;
+                self.emit(Event::RoomMessage(msg));
+            }
+            PRESENCE_LIST => {
+                let mut list: PresenceList = parse(&p)?;
+                self.emit(Event::PresenceList(list.take_presences().into_vec()));
+            }
+            PRESENCE => {
+                let presence: Presence = parse(&p)?;
+                self.emit(Event::Presence(presence));
             }
-            let content = items.get(2).unwrap().to_string();
-            self.msg_to_user(content, uid);
         }
+        Ok(None)
     }
 
-    fn init_writer_idle_timeout(&mut self) {
-        let timeout_task = WriterIdleTimeoutTask::new(self.connection.clone(), self.timer.clone());
-        self.timer.new_timeout(
-            Box::new(timeout_task),
-            Duration::from_secs(WRITER_IDLE_TIME_SECONDS),
-        );
+    // 重发还未被服务端确认的消息, 包括断线期间发出的消息. 恢复会话时服务端会识别重复的消息
+    fn replay(&mut self) {
+        let messages: Vec<MsgToUser> = {
+            let outbox = self.outbox.lock().unwrap();
+            let pending = outbox.pending().into_iter();
+            pending
+                .filter(|v| v.state == SendState::Sending)
+                .map(|v| v.msg)
+                .collect()
+        };
+        for msg in messages {
+            debug!("重发消息 seq = {}", msg.get_seq());
+            let package = package(MSG_TO_USER, &msg);
+            if let Err(e) = self
+                .link
+                .write_package(package, self.config.write_timeout())
+            {
+                debug!("重发消息 seq = {} 失败: {}", msg.get_seq(), e);
+                return;
+            }
+        }
     }
 
-    fn msg_to_user(&mut self, content: String, receiver_id: u64) {
-        let seq = self.last_seq.fetch_add(1, Ordering::SeqCst);
-        let mut msg_pb = MsgToUser::new();
-        msg_pb.set_seq(seq);
-        msg_pb.set_sender_uid(0);
-        msg_pb.set_receiver_uid(receiver_id);
-        msg_pb.set_message_id(0);
-        msg_pb.set_content(content);
-        msg_pb.set_timestamp(wheel_timer::system_time_unix());
-        let package_content = msg_pb.write_to_bytes().unwrap();
-
-        let mut package = Package::new();
-        package.set_action(MSG_TO_USER);
-        package.set_content(package_content);
-        self.connection
-            .write_package(package, Duration::from_secs(10))
-            .unwrap();
+    // 重连时重新订阅在线状态并恢复离开状态, 用户所有设备都离线过时服务端已经清除
+    fn presence_packages(&self) -> Vec<Package> {
+        let resume = self.resume.lock().unwrap();
+        let mut packages = Vec::new();
+        if !resume.subscriptions.is_empty() {
+            let subscriptions: Vec<u64> = resume.subscriptions.iter().copied().collect();
+            packages.push(package(
+                PRESENCE_SUBSCRIBE,
+                &presence_request(&subscriptions),
+            ));
+        }
+        if resume.away {
+            packages.push(package(PRESENCE, &presence_status(true)));
+        }
+        packages
+    }
+
+    // 没有人接收事件时丢弃
+    fn emit(&self, event: Event) {
+        let _ = self.events.send(event);
+    }
+
+    // 记录收到的消息ID, 第一次收到时返回 true
+    fn remember_message(&mut self, message_id: u64) -> bool {
+        if !self.recent_messages.insert(message_id) {
+            return false;
+        }
+        self.recent_message_order.push_back(message_id);
+        if self.recent_message_order.len() > RECENT_MESSAGE_CAPACITY {
+            if let Some(message_id) = self.recent_message_order.pop_front() {
+                self.recent_messages.remove(&message_id);
+            }
+        }
+        true
     }
 }
 
+fn parse<M: Message>(p: &Package) -> crate::Result<M> {
+    M::parse_from_bytes(p.get_content()).map_err(|_| IMError::MalformedPayload(p.get_action()))
+}
+
+fn presence_request(uids: &[u64]) -> PresenceRequest {
+    let mut request = PresenceRequest::new();
+    request.set_uids(uids.to_vec());
+    request
+}
+
+fn presence_status(away: bool) -> Presence {
+    let mut presence = Presence::new();
+    presence.set_status(match away {
+        true => PresenceStatus::AWAY,
+        false => PresenceStatus::ONLINE,
+    });
+    presence.set_timestamp(system_time_unix());
+    presence
+}
+
+fn package<M: Message>(action: Action, msg: &M) -> Package {
+    let mut package = Package::new();
+    package.set_action(action);
+    package.set_content(msg.write_to_bytes().unwrap());
+    package
+}
+
+/// 超过 idle_timeout 没有向链路写入任何数据时, Client会主动向Server发送心跳数据包.
 #[derive(Clone)]
 struct WriterIdleTimeoutTask {
-    connection: Connection,
+    link: Link,
     timer: WheelTimer,
+    idle_timeout: Duration,
+    write_timeout: Duration,
 }
 
 impl WriterIdleTimeoutTask {
-    fn new(connection: Connection, timer: WheelTimer) -> WriterIdleTimeoutTask {
-        WriterIdleTimeoutTask { connection, timer }
+    fn new(
+        link: Link,
+        timer: WheelTimer,
+        idle_timeout: Duration,
+        write_timeout: Duration,
+    ) -> WriterIdleTimeoutTask {
+        WriterIdleTimeoutTask {
+            link,
+            timer,
+            idle_timeout,
+            write_timeout,
+        }
+    }
+
+    fn schedule(&mut self, delay: Duration) {
+        let task = Box::new(self.deref().clone());
+        if let Err(e) = self.timer.new_timeout(task, delay) {
+            warn!("无法继续写空闲检测: {}", e);
+        }
     }
 }
 
 impl TimerTask for WriterIdleTimeoutTask {
     fn run(&mut self) {
-        if self.connection.is_closed() {
+        if self.link.is_closed() {
             return;
         }
-        let next_delay = (WRITER_IDLE_TIME_SECONDS * 1000) as i64
-            - (system_time_unix() - self.connection.get_last_write_time()) as i64;
+        let idle = self
+            .timer
+            .get_clock()
+            .now()
+            .saturating_duration_since(self.link.get_last_write_time());
+        let next_delay = self.idle_timeout.as_millis() as i64 - idle.as_millis() as i64;
         if next_delay <= 0 {
             debug!("trigger write idle timeout check.");
             // set a new timeout.
-            self.timer.new_timeout(
-                Box::new(self.deref().clone()),
-                Duration::from_secs(WRITER_IDLE_TIME_SECONDS),
-            );
+            self.schedule(self.idle_timeout);
 
             let mut package = Package::new();
             package.set_action(HEARTBEAT);
             package.set_content("PING".as_bytes().to_vec());
-            self.connection
-                .write_package(package, Duration::from_secs(10))
-                .unwrap();
+            // 重连过程中写入失败, 重连之后继续检测
+            if let Err(e) = self.link.write_package(package, self.write_timeout) {
+                debug!("心跳发送失败: {}", e);
+            }
         } else {
             // set a new timeout with shorter delay.
-            self.timer.new_timeout(
-                Box::new(self.deref().clone()),
-                Duration::from_millis(next_delay as u64),
-            )
+            self.schedule(Duration::from_millis(next_delay as u64));
+        }
+    }
+}
+
+// 消息发出后未收到服务端 MSG_ACK 时按策略重传, 重连过程中不计入重传次数
+#[derive(Clone)]
+struct ResendTask {
+    seq: u64,
+    attempt: u32, // 第几次重传
+    link: Link,
+    outbox: Arc<Mutex<Outbox>>,
+    timer: WheelTimer,
+    policy: RetransmitPolicy,
+    write_timeout: Duration,
+}
+
+impl TimerTask for ResendTask {
+    fn run(&mut self) {
+        if self.link.is_closed() {
+            return;
+        }
+        let connected = self.link.is_connected();
+        let msg = {
+            let mut outbox = self.outbox.lock().unwrap();
+            if outbox.state(self.seq) != Some(SendState::Sending) {
+                return;
+            }
+            if connected && self.attempt > self.policy.max_attempts {
+                warn!(
+                    "消息 seq = {} 重传 {} 次未收到服务端确认",
+                    self.seq, self.policy.max_attempts
+                );
+                outbox.failed(self.seq);
+                return;
+            }
+            outbox.get(self.seq).map(|v| v.msg.clone())
+        };
+        let msg = match msg {
+            Some(v) => v,
+            None => return,
+        };
+        if connected {
+            debug!("重传消息 seq = {}, attempt = {}", self.seq, self.attempt);
+            let _ = self
+                .link
+                .write_package(package(MSG_TO_USER, &msg), self.write_timeout);
+            // set a new timeout with longer delay.
+            self.attempt += 1;
+        }
+        let ret = self.timer.new_timeout(
+            Box::new(self.deref().clone()),
+            self.policy.delay(self.attempt),
+        );
+        if let Err(e) = ret {
+            warn!("消息 seq = {} 无法安排重传: {}", self.seq, e);
         }
     }
 }
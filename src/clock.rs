@@ -0,0 +1,103 @@
+use crate::wheel_timer::system_time_unix;
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// MockClock::sleep 最多阻塞的真实时间, 时钟没有推进时调用方可以借此检查是否已停止.
+const MOCK_SLEEP_MILLIS: u64 = 10;
+
+/// 时间来源. WheelTimer、Connection 以及读写空闲检测都通过它取当前时间,
+/// 测试时换成 MockClock 可以不真的等待就触发超时.
+pub trait Clock: Send + Sync {
+    /// 单调时间, 不受系统时间调整的影响, 用于计算超时.
+    fn now(&self) -> Instant;
+
+    /// 当前的 unix 时间戳, 单位毫秒, 只用于展示, 系统时间调整时可能回退.
+    fn now_millis(&self) -> u64;
+
+    /// 等待 duration, 允许提前返回, 调用方需要重新读取时间.
+    fn sleep(&self, duration: Duration);
+}
+
+/// 系统时钟.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn now_millis(&self) -> u64 {
+        system_time_unix()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// 手动推进的时钟, 只有调用 advance 时时间才会前进.
+pub struct MockClock {
+    start: Instant,
+    now: Mutex<MockTime>,
+    condvar: Condvar,
+}
+
+struct MockTime {
+    elapsed: Duration, // 单调时间相对 start 前进的时长
+    millis: u64,       // unix 时间戳
+}
+
+impl MockClock {
+    /// 从当前的系统时间开始.
+    pub fn new() -> MockClock {
+        MockClock {
+            start: Instant::now(),
+            now: Mutex::new(MockTime {
+                elapsed: Duration::from_millis(0),
+                millis: system_time_unix(),
+            }),
+            condvar: Condvar::new(),
+        }
+    }
+
+    /// 时间前进 duration, 唤醒所有在 sleep 中等待的线程.
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.now.lock().unwrap();
+        now.elapsed += duration;
+        now.millis += duration.as_millis() as u64;
+        self.condvar.notify_all();
+    }
+
+    /// 只修改 unix 时间戳, 单调时间不变, 模拟 NTP 向前或向后调整系统时间.
+    pub fn set_millis(&self, millis: u64) {
+        self.now.lock().unwrap().millis = millis;
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        MockClock::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.start + self.now.lock().unwrap().elapsed
+    }
+
+    fn now_millis(&self) -> u64 {
+        self.now.lock().unwrap().millis
+    }
+
+    // 等到时钟被推进了 duration, 最多阻塞 MOCK_SLEEP_MILLIS 毫秒的真实时间
+    fn sleep(&self, duration: Duration) {
+        let now = self.now.lock().unwrap();
+        let deadline = now.elapsed + duration;
+        let timeout = Duration::from_millis(MOCK_SLEEP_MILLIS);
+        let _ = self
+            .condvar
+            .wait_timeout_while(now, timeout, |now| now.elapsed < deadline);
+    }
+}
@@ -3,53 +3,253 @@ use crate::Buffer;
 use crate::{IMError, Result};
 use protobuf::ProtobufEnum;
 
-// 消息类型字节数据长度
-const TYPE_LEN: u8 = 2;
+/// 握手数据的魔数, 旧客户端不发送握手, 第一个数据包直接以 Type(2字节) 开头
+pub const MAGIC: [u8; 4] = *b"CATH";
+/// 当前的协议版本, 帧头部中的 Version 字段
+pub const PROTOCOL_VERSION: u8 = 2;
+/// 仍然支持的最低协议版本. 版本1为最初的 Type(2字节) | len(2字节) 格式, 没有握手与分帧
+pub const MIN_PROTOCOL_VERSION: u8 = 1;
+/// 单个帧的消息体默认最大长度, 超过时分成多个帧发送
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 16 * 1024;
+/// 消息体(分帧前)默认最大长度
+pub const DEFAULT_CONTENT_MAX_LEN: usize = 1024 * 1024;
+
+// 版本1的消息头部字节数组长度与消息体最大长度, 旧客户端的 Buffer 只有4096字节
+const V1_HEAD_LEN: usize = 4;
+const V1_CONTENT_MAX_LEN: usize = 4092;
+// 客户端握手: MAGIC(4字节) | min_version(1字节) | max_version(1字节)
+const HELLO_LEN: usize = MAGIC.len() + 2;
+// 服务端回复: MAGIC(4字节) | version(1字节), 没有共同支持的版本时 version 为0
+const HELLO_REPLY_LEN: usize = MAGIC.len() + 1;
+
+// 版本字节数组长度
+const VERSION_LEN: usize = 1;
+// 标志位字节数组长度
+const FLAGS_LEN: usize = 1;
+// 消息类型字节数组长度
+const TYPE_LEN: usize = 2;
 // 消息体字节数组长度
-const BODY_LEN: u8 = 2;
+const BODY_LEN: usize = 4;
 // 消息头部字节数组长度
-const HEAD_LEN: u8 = TYPE_LEN + BODY_LEN;
-// 消息体最大长度
-const CONTENT_MAX_LEN: usize = 4092;
+const HEAD_LEN: usize = VERSION_LEN + FLAGS_LEN + TYPE_LEN + BODY_LEN;
+// 标志位: 后面还有属于同一个数据包的帧
+const FLAG_MORE: u8 = 0x01;
 
-/// 通信协议
-/// --------------------------------------
-/// | Type(2字节) | len(2字节) | body(len) |
-/// --------------------------------------
-pub struct Codec;
+/// 通信协议, 连接建立后客户端先发送 MAGIC 与支持的版本范围, 服务端回复选择的版本,
+/// 之后的数据包都按该版本编解码. 当前版本2的帧格式:
+/// ------------------------------------------------------------------
+/// | Version(1字节) | Flags(1字节) | Type(2字节) | len(4字节) | body(len) |
+/// ------------------------------------------------------------------
+/// 消息体超过 max_frame_size 时拆分为多个帧, 除最后一帧外都带有 MORE 标志,
+/// 接收方收齐所有帧后拼接为一个数据包.
+#[derive(Debug, Clone, Copy)]
+pub struct Codec {
+    version: u8,
+    max_frame_size: usize,
+    content_max_len: usize,
+}
+
+impl Default for Codec {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_FRAME_SIZE, DEFAULT_CONTENT_MAX_LEN)
+    }
+}
 
 impl Codec {
-    pub fn encode(p: Package) -> Result<Vec<u8>> {
+    /// max_frame_size 为单个帧的消息体最大长度, content_max_len 为拼接后的消息体最大长度.
+    pub fn new(max_frame_size: usize, content_max_len: usize) -> Codec {
+        Codec {
+            version: PROTOCOL_VERSION,
+            max_frame_size: max_frame_size.clamp(1, u32::MAX as usize),
+            content_max_len,
+        }
+    }
+
+    /// 按握手协商的版本编解码, version 需在 MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION 之间.
+    pub fn with_version(mut self, version: u8) -> Codec {
+        self.version = version;
+        self
+    }
+
+    pub fn get_version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn get_max_frame_size(&self) -> usize {
+        self.max_frame_size
+    }
+
+    pub fn get_content_max_len(&self) -> usize {
+        self.content_max_len
+    }
+
+    /// 一个数据包最多的帧数, 超过时即使总长度没有超过限制也拒绝.
+    pub fn max_frames(&self) -> usize {
+        self.content_max_len / self.max_frame_size + 1
+    }
+
+    /// 一个数据包编码后(包括所有帧头部)的最大字节数, 用作接收缓冲区的上限.
+    pub fn max_packet_len(&self) -> usize {
+        let v2 = self.max_frames() * HEAD_LEN + self.content_max_len;
+        v2.max(V1_HEAD_LEN + V1_CONTENT_MAX_LEN).max(HELLO_LEN)
+    }
+
+    pub fn encode(&self, p: Package) -> Result<Vec<u8>> {
+        if self.version == 1 {
+            return self.encode_v1(p);
+        }
         let body_len = p.content.len();
-        if body_len as usize > CONTENT_MAX_LEN {
+        if body_len > self.content_max_len {
             return Err(IMError::ContentMaxLen);
         }
-        let mut buffer = vec![0; HEAD_LEN as usize + body_len as usize];
-
-        // 写大端序
+        let chunks = body_len.div_ceil(self.max_frame_size).max(1);
+        let mut buffer = Vec::with_capacity(chunks * HEAD_LEN + body_len);
         let action = p.action as u16;
-        buffer[0] = (action >> 8) as u8;
-        buffer[1] = action as u8;
-        buffer[2] = (body_len >> 8) as u8;
-        buffer[3] = body_len as u8;
-        buffer[4..].copy_from_slice(p.get_content());
-        return Ok(buffer);
-    }
-
-    pub fn decode(buffer: &mut Buffer) -> Result<Package> {
-        let action_buf = buffer.seek(0, TYPE_LEN as usize)?;
-        let len_buf = buffer.seek(TYPE_LEN as usize, BODY_LEN as usize)?;
-        // 读大端序
-        let action = action_buf[1] as u16 | (action_buf[0] as u16) << 8;
-        let body_len = len_buf[1] as u16 | (len_buf[0] as u16) << 8;
-        if body_len as usize > CONTENT_MAX_LEN {
+        for i in 0..chunks {
+            let chunk = &p.get_content()
+                [i * self.max_frame_size..body_len.min((i + 1) * self.max_frame_size)];
+            let flags = if i + 1 < chunks { FLAG_MORE } else { 0 };
+            // 写大端序
+            buffer.push(self.version);
+            buffer.push(flags);
+            buffer.extend_from_slice(&action.to_be_bytes());
+            buffer.extend_from_slice(&(chunk.len() as u32).to_be_bytes());
+            buffer.extend_from_slice(chunk);
+        }
+        Ok(buffer)
+    }
+
+    /// 缓冲区中的帧不足一个完整的数据包时返回 NotEnoughData, 不消费任何数据.
+    /// 未知的 Action 返回 UnknownAction, 该数据包已被消费, 可以继续解码后面的数据包.
+    pub fn decode(&self, buffer: &mut Buffer) -> Result<Package> {
+        if self.version == 1 {
+            return self.decode_v1(buffer);
+        }
+        // 先确认所有分帧都已到达, 再一次性取出
+        let mut frames = Vec::new();
+        let mut offset = 0;
+        let mut content_len = 0;
+        loop {
+            let head = buffer.seek(offset, HEAD_LEN)?;
+            if head[0] != self.version {
+                return Err(IMError::UnsupportedVersion(head[0]));
+            }
+            let flags = head[1];
+            // 读大端序
+            let action = u16::from_be_bytes([head[2], head[3]]);
+            let body_len = u32::from_be_bytes([head[4], head[5], head[6], head[7]]) as usize;
+            content_len += body_len;
+            if body_len > self.max_frame_size || content_len > self.content_max_len {
+                return Err(IMError::ContentMaxLen);
+            }
+            let more = flags & FLAG_MORE != 0;
+            // 空的中间帧不增加长度, 会让对端无限地发送下去
+            if more && body_len == 0 {
+                return Err(IMError::MalformedFrame);
+            }
+            frames.push((action, body_len));
+            if frames.len() > self.max_frames() {
+                return Err(IMError::ContentMaxLen);
+            }
+            offset += HEAD_LEN + body_len;
+            if !more {
+                break;
+            }
+        }
+        if buffer.len() < offset {
+            return Err(IMError::NotEnoughData);
+        }
+        let mut content = Vec::with_capacity(content_len);
+        for &(_, body_len) in &frames {
+            content.extend(buffer.read(HEAD_LEN, body_len)?);
+        }
+
+        new_package(frames[0].0, content)
+    }
+}
+
+impl Codec {
+    /// 客户端发送的握手数据.
+    pub fn encode_hello(min_version: u8, max_version: u8) -> Vec<u8> {
+        let mut buffer = MAGIC.to_vec();
+        buffer.push(min_version);
+        buffer.push(max_version);
+        buffer
+    }
+
+    /// 解析客户端的握手数据, 返回客户端支持的版本范围.
+    /// 第一个数据包不以 MAGIC 开头时是没有握手的旧客户端, 返回 None 且不消费任何数据.
+    pub fn decode_hello(buffer: &mut Buffer) -> Result<Option<(u8, u8)>> {
+        for (i, &b) in MAGIC.iter().enumerate() {
+            if buffer.seek(i, 1)?[0] != b {
+                return Ok(None);
+            }
+        }
+        let hello = buffer.read(0, HELLO_LEN)?;
+        Ok(Some((hello[4], hello[5])))
+    }
+
+    pub fn encode_hello_reply(version: u8) -> Vec<u8> {
+        let mut buffer = MAGIC.to_vec();
+        buffer.push(version);
+        buffer
+    }
+
+    /// 解析服务端的握手回复, 返回选择的版本.
+    pub fn decode_hello_reply(buffer: &mut Buffer) -> Result<u8> {
+        let reply = buffer.seek(0, HELLO_REPLY_LEN)?;
+        if reply[..MAGIC.len()] != MAGIC {
+            return Err(IMError::BadMagic);
+        }
+        buffer.read(0, HELLO_REPLY_LEN)?;
+        match reply[4] {
+            0 => Err(IMError::UnsupportedVersion(0)),
+            version => Ok(version),
+        }
+    }
+
+    /// 在双方都支持的版本中选择最高的一个.
+    pub fn negotiate(min_version: u8, max_version: u8) -> Option<u8> {
+        let version = max_version.min(PROTOCOL_VERSION);
+        if version < min_version.max(MIN_PROTOCOL_VERSION) {
+            return None;
+        }
+        Some(version)
+    }
+
+    // 版本1: | Type(2字节) | len(2字节) | body(len) |
+    fn encode_v1(&self, p: Package) -> Result<Vec<u8>> {
+        let body_len = p.content.len();
+        if body_len > self.content_max_len.min(V1_CONTENT_MAX_LEN) {
             return Err(IMError::ContentMaxLen);
         }
-        let content = buffer.read(HEAD_LEN as usize, body_len as usize)?;
+        let mut buffer = Vec::with_capacity(V1_HEAD_LEN + body_len);
+        buffer.extend_from_slice(&(p.action as u16).to_be_bytes());
+        buffer.extend_from_slice(&(body_len as u16).to_be_bytes());
+        buffer.extend_from_slice(p.get_content());
+        Ok(buffer)
+    }
 
-        let mut package = Package::new();
-        package.action = Action::from_i32(action as i32).unwrap();
-        package.content = content;
-        Ok(package)
+    fn decode_v1(&self, buffer: &mut Buffer) -> Result<Package> {
+        let head = buffer.seek(0, V1_HEAD_LEN)?;
+        let action = u16::from_be_bytes([head[0], head[1]]);
+        let body_len = u16::from_be_bytes([head[2], head[3]]) as usize;
+        if body_len > self.content_max_len.min(V1_CONTENT_MAX_LEN) {
+            return Err(IMError::ContentMaxLen);
+        }
+        let content = buffer.read(V1_HEAD_LEN, body_len)?;
+        new_package(action, content)
     }
 }
+
+// 较新的对端可能发送本端还不认识的 Action
+fn new_package(action: u16, content: Vec<u8>) -> Result<Package> {
+    let action = match Action::from_i32(action as i32) {
+        Some(v) => v,
+        None => return Err(IMError::UnknownAction(action)),
+    };
+    let mut package = Package::new();
+    package.action = action;
+    package.content = content;
+    Ok(package)
+}
@@ -0,0 +1,523 @@
+use crate::{
+    IMError, LoginPolicy, OverflowPolicy, Result, DEFAULT_CONTENT_MAX_LEN, DEFAULT_MAX_FRAME_SIZE,
+    DEFAULT_MAX_PROTOCOL_VIOLATIONS, DEFAULT_WHEEL_LEVELS, DEFAULT_WRITE_QUEUE_CAPACITY,
+};
+use log::LevelFilter;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// 服务端配置.
+///
+/// 按以下顺序加载, 后面的覆盖前面的:
+/// 默认值 < TOML 配置文件 < 环境变量 `CATHY_SERVER_<KEY>` < 命令行参数 `--<key>`.
+/// 环境变量与命令行参数的 key 与 TOML 相同, 例如 `reader_idle_seconds` 对应
+/// `CATHY_SERVER_READER_IDLE_SECONDS` 和 `--reader-idle-seconds`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// 监听地址
+    pub listen_address: String,
+    /// 浏览器接入的 WebSocket 监听地址, 不设置时不监听, 需要 websocket feature
+    pub websocket_address: Option<String>,
+    /// 链路read空闲检测, 超过该时间没有读取到任何数据强制关闭连接
+    pub reader_idle_seconds: u64,
+    /// 新连接必须在该时间内完成登录认证
+    pub login_timeout_seconds: u64,
+    /// 单个数据包的写超时
+    pub write_timeout_seconds: u64,
+    /// 关闭时等待客户端断开的最长时间
+    pub drain_timeout_seconds: u64,
+    /// 连接断开后保留会话的时长, 客户端在此期间重连可以恢复会话, 0 表示不保留
+    pub session_resume_seconds: u64,
+    /// 同一用户再次登录时的处理策略: multi_device, kick_older
+    pub login_policy: LoginPolicy,
+    /// 每个会话发送队列的容量
+    pub write_queue_capacity: usize,
+    /// 发送队列已满时的处理策略: drop, disconnect, spill_offline
+    pub overflow_policy: OverflowPolicy,
+    /// 每个连接最多容忍的协议违规次数, 超过后断开连接
+    pub max_protocol_violations: u32,
+    /// 时间轮每格的时长
+    pub tick_duration_millis: u64,
+    /// 时间轮的格数
+    pub ticks_per_wheel: u32,
+    /// 时间轮的层数, 每高一层每格的时长乘以 ticks_per_wheel
+    pub timer_levels: u32,
+    /// 时间轮最多等待执行的任务数, 超过后不再安排新的任务, 0 表示不限制
+    pub max_pending_timeouts: u64,
+    /// 执行到期定时任务的线程数, 0 表示在时间轮线程上执行
+    pub timer_threads: usize,
+    /// 单个帧的消息体最大长度, 更长的消息拆分为多个帧. 收到更长的帧时断开连接, 客户端不能大于服务端
+    pub max_frame_size: usize,
+    /// 消息体最大长度
+    pub content_max_len: usize,
+    /// 日志级别: off, error, warn, info, debug, trace
+    pub log_level: String,
+    /// 用户文件
+    pub users_file: String,
+    /// 消息持久化目录
+    pub message_dir: String,
+    /// 离线消息目录
+    pub offline_dir: String,
+    /// PEM 格式的证书链, 与 tls_key_file 同时设置时启用 TLS, 需要 tls feature
+    pub tls_cert_file: Option<String>,
+    /// PEM 格式的私钥
+    pub tls_key_file: Option<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            listen_address: "127.0.0.1:8099".to_string(),
+            websocket_address: None,
+            reader_idle_seconds: 60,
+            login_timeout_seconds: 10,
+            write_timeout_seconds: 10,
+            drain_timeout_seconds: 5,
+            session_resume_seconds: 30,
+            login_policy: LoginPolicy::default(),
+            write_queue_capacity: DEFAULT_WRITE_QUEUE_CAPACITY,
+            overflow_policy: OverflowPolicy::default(),
+            max_protocol_violations: DEFAULT_MAX_PROTOCOL_VIOLATIONS,
+            tick_duration_millis: 100,
+            ticks_per_wheel: 12,
+            timer_levels: DEFAULT_WHEEL_LEVELS,
+            max_pending_timeouts: 0,
+            timer_threads: 0,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            content_max_len: DEFAULT_CONTENT_MAX_LEN,
+            log_level: "debug".to_string(),
+            users_file: "conf/users.txt".to_string(),
+            message_dir: "data/messages".to_string(),
+            offline_dir: "data/offline".to_string(),
+            tls_cert_file: None,
+            tls_key_file: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// 依次读取配置文件(`--config <path>` 或环境变量 `CATHY_SERVER_CONFIG`)、环境变量
+    /// 和命令行参数, 返回配置与剩余的位置参数. args 不包含程序名.
+    pub fn load<I: IntoIterator<Item = String>>(args: I) -> Result<(ServerConfig, Vec<String>)> {
+        load(args)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<ServerConfig> {
+        from_file(path)
+    }
+
+    pub fn from_toml(s: &str) -> Result<ServerConfig> {
+        from_toml(s)
+    }
+
+    pub fn reader_idle_timeout(&self) -> Duration {
+        Duration::from_secs(self.reader_idle_seconds)
+    }
+
+    pub fn login_timeout(&self) -> Duration {
+        Duration::from_secs(self.login_timeout_seconds)
+    }
+
+    pub fn write_timeout(&self) -> Duration {
+        Duration::from_secs(self.write_timeout_seconds)
+    }
+
+    pub fn drain_timeout(&self) -> Duration {
+        Duration::from_secs(self.drain_timeout_seconds)
+    }
+
+    pub fn session_resume_grace(&self) -> Duration {
+        Duration::from_secs(self.session_resume_seconds)
+    }
+
+    pub fn level_filter(&self) -> Result<LevelFilter> {
+        parse_level(&self.log_level)
+    }
+}
+
+impl Settings for ServerConfig {
+    const ENV_PREFIX: &'static str = "CATHY_SERVER_";
+    const KEYS: &'static [&'static str] = &[
+        "listen_address",
+        "websocket_address",
+        "reader_idle_seconds",
+        "login_timeout_seconds",
+        "write_timeout_seconds",
+        "drain_timeout_seconds",
+        "session_resume_seconds",
+        "login_policy",
+        "write_queue_capacity",
+        "overflow_policy",
+        "max_protocol_violations",
+        "tick_duration_millis",
+        "ticks_per_wheel",
+        "timer_levels",
+        "max_pending_timeouts",
+        "timer_threads",
+        "max_frame_size",
+        "content_max_len",
+        "log_level",
+        "users_file",
+        "message_dir",
+        "offline_dir",
+        "tls_cert_file",
+        "tls_key_file",
+    ];
+
+    fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "listen_address" => self.listen_address = value.to_string(),
+            "websocket_address" => self.websocket_address = Some(value.to_string()),
+            "reader_idle_seconds" => self.reader_idle_seconds = parse(key, value)?,
+            "login_timeout_seconds" => self.login_timeout_seconds = parse(key, value)?,
+            "write_timeout_seconds" => self.write_timeout_seconds = parse(key, value)?,
+            "drain_timeout_seconds" => self.drain_timeout_seconds = parse(key, value)?,
+            "session_resume_seconds" => self.session_resume_seconds = parse(key, value)?,
+            "login_policy" => self.login_policy = parse_login_policy(value)?,
+            "write_queue_capacity" => self.write_queue_capacity = parse(key, value)?,
+            "overflow_policy" => self.overflow_policy = parse_overflow_policy(value)?,
+            "max_protocol_violations" => self.max_protocol_violations = parse(key, value)?,
+            "tick_duration_millis" => self.tick_duration_millis = parse(key, value)?,
+            "ticks_per_wheel" => self.ticks_per_wheel = parse(key, value)?,
+            "timer_levels" => self.timer_levels = parse(key, value)?,
+            "max_pending_timeouts" => self.max_pending_timeouts = parse(key, value)?,
+            "timer_threads" => self.timer_threads = parse(key, value)?,
+            "max_frame_size" => self.max_frame_size = parse(key, value)?,
+            "content_max_len" => self.content_max_len = parse(key, value)?,
+            "log_level" => self.log_level = value.to_string(),
+            "users_file" => self.users_file = value.to_string(),
+            "message_dir" => self.message_dir = value.to_string(),
+            "offline_dir" => self.offline_dir = value.to_string(),
+            "tls_cert_file" => self.tls_cert_file = Some(value.to_string()),
+            "tls_key_file" => self.tls_key_file = Some(value.to_string()),
+            _ => return Err(invalid(format!("unknown option: {}", key))),
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<()> {
+        validate_common(
+            self.max_frame_size,
+            self.content_max_len,
+            self.tick_duration_millis,
+            self.ticks_per_wheel,
+            &self.log_level,
+        )?;
+        if self.write_queue_capacity == 0 {
+            return Err(invalid("write_queue_capacity must be greater than 0"));
+        }
+        if self.timer_levels == 0 {
+            return Err(invalid("timer_levels must be greater than 0"));
+        }
+        if self.tls_cert_file.is_some() != self.tls_key_file.is_some() {
+            return Err(invalid(
+                "tls_cert_file and tls_key_file must be set together",
+            ));
+        }
+        validate_feature(
+            self.websocket_address.is_some(),
+            cfg!(feature = "websocket"),
+            "websocket",
+        )?;
+        validate_feature(self.tls_cert_file.is_some(), cfg!(feature = "tls"), "tls")
+    }
+}
+
+/// 客户端配置, 加载顺序与 ServerConfig 相同, 环境变量前缀为 `CATHY_CLIENT_`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientConfig {
+    /// 服务端地址
+    pub server_address: String,
+    /// 设备ID, 同一用户的不同设备可以同时在线, 同一设备再次登录时踢掉旧的会话
+    pub device_id: Option<String>,
+    /// 链路write空闲检测, 超过该时间没有写入任何数据时发送心跳
+    pub writer_idle_seconds: u64,
+    /// 单个数据包的写超时
+    pub write_timeout_seconds: u64,
+    /// 时间轮每格的时长
+    pub tick_duration_millis: u64,
+    /// 时间轮的格数
+    pub ticks_per_wheel: u32,
+    /// 执行到期定时任务的线程数, 0 表示在时间轮线程上执行. 心跳和重传会阻塞写入, 可以交给单独的线程
+    pub timer_threads: usize,
+    /// 单个帧的消息体最大长度, 更长的消息拆分为多个帧. 收到更长的帧时断开连接, 客户端不能大于服务端
+    pub max_frame_size: usize,
+    /// 消息体最大长度
+    pub content_max_len: usize,
+    /// 日志级别: off, error, warn, info, debug, trace
+    pub log_level: String,
+    /// PEM 格式的 CA 证书, 设置时通过 TLS 连接服务端, 需要 tls feature
+    pub tls_ca_file: Option<String>,
+    /// 校验服务端证书使用的域名, 默认为 server_address 中的主机名
+    pub tls_server_name: Option<String>,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        ClientConfig {
+            server_address: "127.0.0.1:8099".to_string(),
+            device_id: None,
+            writer_idle_seconds: 30,
+            write_timeout_seconds: 10,
+            tick_duration_millis: 100,
+            ticks_per_wheel: 12,
+            timer_threads: 0,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            content_max_len: DEFAULT_CONTENT_MAX_LEN,
+            log_level: "debug".to_string(),
+            tls_ca_file: None,
+            tls_server_name: None,
+        }
+    }
+}
+
+impl ClientConfig {
+    /// 依次读取配置文件(`--config <path>` 或环境变量 `CATHY_CLIENT_CONFIG`)、环境变量
+    /// 和命令行参数, 返回配置与剩余的位置参数. args 不包含程序名.
+    pub fn load<I: IntoIterator<Item = String>>(args: I) -> Result<(ClientConfig, Vec<String>)> {
+        load(args)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<ClientConfig> {
+        from_file(path)
+    }
+
+    pub fn from_toml(s: &str) -> Result<ClientConfig> {
+        from_toml(s)
+    }
+
+    pub fn writer_idle_timeout(&self) -> Duration {
+        Duration::from_secs(self.writer_idle_seconds)
+    }
+
+    pub fn write_timeout(&self) -> Duration {
+        Duration::from_secs(self.write_timeout_seconds)
+    }
+
+    pub fn level_filter(&self) -> Result<LevelFilter> {
+        parse_level(&self.log_level)
+    }
+
+    /// 校验服务端证书使用的域名, 没有设置 tls_server_name 时取 server_address 中的主机名.
+    pub fn server_name(&self) -> String {
+        if let Some(name) = &self.tls_server_name {
+            return name.clone();
+        }
+        let host = match self.server_address.rsplit_once(':') {
+            Some((host, _)) => host,
+            None => &self.server_address,
+        };
+        host.trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string()
+    }
+}
+
+impl Settings for ClientConfig {
+    const ENV_PREFIX: &'static str = "CATHY_CLIENT_";
+    const KEYS: &'static [&'static str] = &[
+        "server_address",
+        "device_id",
+        "writer_idle_seconds",
+        "write_timeout_seconds",
+        "tick_duration_millis",
+        "ticks_per_wheel",
+        "timer_threads",
+        "max_frame_size",
+        "content_max_len",
+        "log_level",
+        "tls_ca_file",
+        "tls_server_name",
+    ];
+
+    fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "server_address" => self.server_address = value.to_string(),
+            "device_id" => self.device_id = Some(value.to_string()),
+            "writer_idle_seconds" => self.writer_idle_seconds = parse(key, value)?,
+            "write_timeout_seconds" => self.write_timeout_seconds = parse(key, value)?,
+            "tick_duration_millis" => self.tick_duration_millis = parse(key, value)?,
+            "ticks_per_wheel" => self.ticks_per_wheel = parse(key, value)?,
+            "timer_threads" => self.timer_threads = parse(key, value)?,
+            "max_frame_size" => self.max_frame_size = parse(key, value)?,
+            "content_max_len" => self.content_max_len = parse(key, value)?,
+            "log_level" => self.log_level = value.to_string(),
+            "tls_ca_file" => self.tls_ca_file = Some(value.to_string()),
+            "tls_server_name" => self.tls_server_name = Some(value.to_string()),
+            _ => return Err(invalid(format!("unknown option: {}", key))),
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<()> {
+        validate_common(
+            self.max_frame_size,
+            self.content_max_len,
+            self.tick_duration_millis,
+            self.ticks_per_wheel,
+            &self.log_level,
+        )?;
+        validate_feature(self.tls_ca_file.is_some(), cfg!(feature = "tls"), "tls")
+    }
+}
+
+// 按 key 设置单个配置项, 环境变量和命令行参数共用
+trait Settings: Default + DeserializeOwned {
+    const ENV_PREFIX: &'static str;
+    const KEYS: &'static [&'static str];
+
+    fn set(&mut self, key: &str, value: &str) -> Result<()>;
+
+    fn validate(&self) -> Result<()>;
+}
+
+fn load<T: Settings, I: IntoIterator<Item = String>>(args: I) -> Result<(T, Vec<String>)> {
+    let (path, options, positional) = parse_args(args)?;
+    let path = path.or_else(|| env::var(format!("{}CONFIG", T::ENV_PREFIX)).ok());
+    let mut config = match path {
+        Some(path) => from_file::<T, _>(path)?,
+        None => T::default(),
+    };
+    for key in T::KEYS {
+        if let Ok(value) = env::var(format!("{}{}", T::ENV_PREFIX, key.to_uppercase())) {
+            config.set(key, &value)?;
+        }
+    }
+    for (key, value) in options {
+        config.set(&key, &value)?;
+    }
+    config.validate()?;
+    Ok((config, positional))
+}
+
+fn from_file<T: Settings, P: AsRef<Path>>(path: P) -> Result<T> {
+    let path = path.as_ref();
+    let s =
+        fs::read_to_string(path).map_err(|e| invalid(format!("read {}: {}", path.display(), e)))?;
+    from_toml(&s)
+}
+
+fn from_toml<T: Settings>(s: &str) -> Result<T> {
+    let config: T = toml::from_str(s).map_err(|e| invalid(e.to_string()))?;
+    config.validate()?;
+    Ok(config)
+}
+
+// 拆分命令行参数: --config 的值, --key value 或 --key=value 形式的配置项, 以及位置参数
+type Args = (Option<String>, Vec<(String, String)>, Vec<String>);
+
+fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Args> {
+    let mut path = None;
+    let mut options = Vec::new();
+    let mut positional = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let flag = match arg.strip_prefix("--") {
+            Some(v) => v,
+            None => {
+                positional.push(arg);
+                continue;
+            }
+        };
+        let (key, value) = match flag.split_once('=') {
+            Some((key, value)) => (key.to_string(), value.to_string()),
+            None => match args.next() {
+                Some(value) => (flag.to_string(), value),
+                None => return Err(invalid(format!("missing value for --{}", flag))),
+            },
+        };
+        let key = key.replace('-', "_");
+        if key == "config" {
+            path = Some(value);
+        } else {
+            options.push((key, value));
+        }
+    }
+    Ok((path, options, positional))
+}
+
+fn validate_common(
+    max_frame_size: usize,
+    content_max_len: usize,
+    tick_duration_millis: u64,
+    ticks_per_wheel: u32,
+    log_level: &str,
+) -> Result<()> {
+    // 帧头部的长度字段为4字节
+    if max_frame_size == 0 || max_frame_size > u32::MAX as usize {
+        return Err(invalid(format!(
+            "max_frame_size must be in 1..={}",
+            u32::MAX
+        )));
+    }
+    if content_max_len == 0 {
+        return Err(invalid("content_max_len must be greater than 0"));
+    }
+    if tick_duration_millis == 0 || ticks_per_wheel == 0 {
+        return Err(invalid(
+            "tick_duration_millis and ticks_per_wheel must be greater than 0",
+        ));
+    }
+    // 与 WheelTimer 的限制一致, 通过校验的配置创建时间轮时不会失败
+    if ticks_per_wheel > 1 << 30 {
+        return Err(invalid("ticks_per_wheel may not be greater than 2^30"));
+    }
+    let max_tick_duration = u64::MAX / ticks_per_wheel.next_power_of_two() as u64;
+    if tick_duration_millis >= max_tick_duration {
+        return Err(invalid(format!(
+            "tick_duration_millis must be less than {}",
+            max_tick_duration
+        )));
+    }
+    parse_level(log_level)?;
+    Ok(())
+}
+
+// 没有启用对应的 feature 时不能配置, 避免以为连接已加密或网关已启动
+fn validate_feature(enabled: bool, available: bool, feature: &str) -> Result<()> {
+    if enabled && !available {
+        return Err(invalid(format!(
+            "requires building with the {} feature",
+            feature
+        )));
+    }
+    Ok(())
+}
+
+fn parse<T: FromStr>(key: &str, value: &str) -> Result<T> {
+    value
+        .parse()
+        .map_err(|_| invalid(format!("invalid value for {}: {}", key, value)))
+}
+
+fn parse_level(value: &str) -> Result<LevelFilter> {
+    LevelFilter::from_str(value).map_err(|_| invalid(format!("invalid log_level: {}", value)))
+}
+
+fn parse_overflow_policy(value: &str) -> Result<OverflowPolicy> {
+    match value {
+        "drop" => Ok(OverflowPolicy::Drop),
+        "disconnect" => Ok(OverflowPolicy::Disconnect),
+        "spill_offline" => Ok(OverflowPolicy::SpillOffline),
+        _ => Err(invalid(format!("invalid overflow_policy: {}", value))),
+    }
+}
+
+fn parse_login_policy(value: &str) -> Result<LoginPolicy> {
+    match value {
+        "multi_device" => Ok(LoginPolicy::MultiDevice),
+        "kick_older" => Ok(LoginPolicy::KickOlder),
+        _ => Err(invalid(format!("invalid login_policy: {}", value))),
+    }
+}
+
+fn invalid<S: Into<String>>(reason: S) -> IMError {
+    IMError::InvalidConfig(reason.into())
+}
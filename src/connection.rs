@@ -1,79 +1,319 @@
+use crate::clock::{Clock, SystemClock};
 use crate::proto::Package;
-use crate::wheel_timer;
 use crate::IMError;
 use crate::Result;
-use crate::{Buffer, Codec};
-use std::borrow::BorrowMut;
+use crate::Transport;
+use crate::{Buffer, Codec, MIN_PROTOCOL_VERSION};
+use log::debug;
+use std::io;
 use std::io::Write;
-use std::net::{Shutdown, TcpStream};
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+/// 默认最多容忍16次协议违规(未知的 Action, 无法解析的消息体等), 超过后断开连接.
+pub const DEFAULT_MAX_PROTOCOL_VIOLATIONS: u32 = 16;
 
 pub struct Connection {
-    stream: TcpStream,
+    stream: Box<dyn Transport>,
     buffer: Buffer,
+    codec: Codec,
     closed: Arc<AtomicBool>,
-    last_read_time: Arc<AtomicU64>,
-    last_write_time: Arc<AtomicU64>,
+    origin: Instant,                 // 最后读写时间的起点
+    last_read_time: Arc<AtomicU64>,  // 相对 origin 的毫秒数
+    last_write_time: Arc<AtomicU64>, // 相对 origin 的毫秒数
+    violations: Arc<AtomicU32>,
+    max_violations: u32,
+    clock: Arc<dyn Clock>,
 }
 
 impl Clone for Connection {
     fn clone(&self) -> Self {
         Connection {
             stream: self.stream.try_clone().unwrap(),
-            buffer: Buffer::new(),
+            buffer: Buffer::with_limit(self.codec.max_packet_len()),
+            codec: self.codec,
             closed: self.closed.clone(),
+            origin: self.origin,
             last_read_time: self.last_read_time.clone(),
             last_write_time: self.last_write_time.clone(),
+            violations: self.violations.clone(),
+            max_violations: self.max_violations,
+            clock: self.clock.clone(),
         }
     }
 }
 
 impl Connection {
     pub fn new(stream: TcpStream) -> Connection {
+        Self::with_codec(stream, Codec::default())
+    }
+
+    /// 使用指定的 Codec 编解码数据包, 例如调整分帧大小或限制消息体长度.
+    pub fn with_codec(stream: TcpStream, codec: Codec) -> Connection {
+        Self::with_transport(Box::new(stream), codec)
+    }
+
+    /// 在 TcpStream 以外的传输层上通信, 例如 TLS 或内存管道.
+    pub fn with_transport(stream: Box<dyn Transport>, codec: Codec) -> Connection {
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
         Connection {
             stream,
-            buffer: Buffer::new(),
+            buffer: Buffer::with_limit(codec.max_packet_len()),
+            codec,
             closed: Arc::new(AtomicBool::new(false)),
+            origin: clock.now(),
             last_read_time: Arc::new(AtomicU64::new(0)),
             last_write_time: Arc::new(AtomicU64::new(0)),
+            violations: Arc::new(AtomicU32::new(0)),
+            max_violations: DEFAULT_MAX_PROTOCOL_VIOLATIONS,
+            clock,
         }
     }
 
+    /// 记录最后读写时间使用的时钟, 应与检测读写空闲的时间轮一致. 需要在 clone 之前调用,
+    /// 最后读写时间重置为当前时间.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.origin = clock.now();
+        self.last_read_time.store(0, Ordering::SeqCst);
+        self.last_write_time.store(0, Ordering::SeqCst);
+        self.clock = clock;
+    }
+
+    // 相对 origin 的单调时间, 单位毫秒
+    fn elapsed_millis(&self) -> u64 {
+        let elapsed = self.clock.now().saturating_duration_since(self.origin);
+        elapsed.as_millis() as u64
+    }
+
+    /// 最多容忍的协议违规次数, 之后 clone 出来的 Connection 使用同样的上限.
+    pub fn set_max_protocol_violations(&mut self, max_violations: u32) {
+        self.max_violations = max_violations;
+    }
+
+    /// 记录一次协议违规, 累计次数超过上限时返回 TooManyViolations, 需要关闭连接.
+    pub fn protocol_violation(&self) -> Result<()> {
+        let n = self.violations.fetch_add(1, Ordering::SeqCst) + 1;
+        if n > self.max_violations {
+            return Err(IMError::TooManyViolations(n));
+        }
+        Ok(())
+    }
+
+    pub fn get_protocol_violations(&self) -> u32 {
+        self.violations.load(Ordering::SeqCst)
+    }
+
     pub fn write_package(&mut self, p: Package, write_timeout: Duration) -> Result<()> {
-        let buffer = Codec::encode(p)?;
+        let buffer = self.codec.encode(p)?;
         self.stream.set_write_timeout(Option::Some(write_timeout))?;
-        self.stream.write(&buffer)?;
-        self.stream.flush()?;
+        self.write_all(&buffer, write_timeout)?;
 
         self.last_write_time
-            .store(wheel_timer::system_time_unix(), Ordering::SeqCst);
+            .store(self.elapsed_millis(), Ordering::SeqCst);
         Ok(())
     }
 
-    pub fn read_package(&mut self) -> Result<Package> {
+    // 与 Write::write_all 相同并在最后 flush, 但非阻塞模式下发送缓冲区已满时会在超时之前重试,
+    // 避免只写出半个数据包.
+    fn write_all(&mut self, buf: &[u8], write_timeout: Duration) -> Result<()> {
+        let deadline = self.clock.now() + write_timeout;
+        let mut written = 0;
+        while written < buf.len() {
+            match self.stream.write(&buf[written..]) {
+                Ok(0) => return Err(io::Error::from(io::ErrorKind::WriteZero).into()),
+                Ok(n) => written += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock && self.clock.now() < deadline => {
+                    self.clock.sleep(Duration::from_millis(1));
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        // TLS 等传输层在 flush 时才把缓存的数据写入 socket
         loop {
-            match Codec::decode(self.buffer.borrow_mut()) {
-                Ok(p) => {
-                    return Ok(p);
+            match self.stream.flush() {
+                Ok(()) => return Ok(()),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock && self.clock.now() < deadline => {
+                    self.clock.sleep(Duration::from_millis(1));
                 }
-                Err(e) => match e {
-                    IMError::ContentMaxLen => {
-                        return Err(e);
-                    }
-                    _ => {
-                        self.buffer.read_from_reader(self.stream.borrow_mut())?;
-                        self.last_read_time
-                            .store(wheel_timer::system_time_unix(), Ordering::SeqCst);
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// 非阻塞模式下写出 buf 开头的数据, 返回写出的字节数, 发送缓冲区已满时返回0.
+    /// 传输层可能缓存已写出的数据, 全部写完后还要调用 try_flush.
+    pub fn try_write(&mut self, buf: &[u8]) -> Result<usize> {
+        let mut written = 0;
+        while written < buf.len() {
+            match self.stream.write(&buf[written..]) {
+                Ok(0) => return Err(io::Error::from(io::ErrorKind::WriteZero).into()),
+                Ok(n) => written += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        if written > 0 {
+            self.last_write_time
+                .store(self.elapsed_millis(), Ordering::SeqCst);
+        }
+        Ok(written)
+    }
+
+    /// 非阻塞模式下把传输层缓存的数据写入 socket, 发送缓冲区已满时返回 false.
+    pub fn try_flush(&mut self) -> Result<bool> {
+        loop {
+            match self.stream.flush() {
+                Ok(()) => return Ok(true),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(false),
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// 切换非阻塞模式, 所有 clone 出来的 Connection 共享同一个模式.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> Result<()> {
+        self.stream.set_nonblocking(nonblocking)?;
+        Ok(())
+    }
+
+    pub fn set_read_timeout(&self, read_timeout: Option<Duration>) -> Result<()> {
+        self.stream.set_read_timeout(read_timeout)?;
+        Ok(())
+    }
+
+    pub fn read_package(&mut self) -> Result<Package> {
+        let codec = self.codec;
+        self.read_with(|buffer| codec.decode(buffer))
+    }
+
+    /// 非阻塞模式下读取数据包, 已读到的数据不足一个数据包时返回 None.
+    /// 返回 None 时 socket 中的数据已读完, 可以等待下一次可读事件.
+    pub fn try_read_package(&mut self) -> Result<Option<Package>> {
+        let codec = self.codec;
+        self.try_read_with(|buffer| codec.decode(buffer))
+    }
+
+    /// 客户端握手: 发送 MAGIC 与支持的版本范围(最高为当前 Codec 的版本),
+    /// 之后按服务端选择的版本编解码, 返回该版本.
+    pub fn handshake(&mut self, timeout: Duration) -> Result<u8> {
+        let hello = Codec::encode_hello(MIN_PROTOCOL_VERSION, self.codec.get_version());
+        self.stream.set_write_timeout(Some(timeout))?;
+        self.write_all(&hello, timeout)?;
+        let version = self.read_with(Codec::decode_hello_reply)?;
+        self.codec = self.codec.with_version(version);
+        Ok(version)
+    }
+
+    /// 服务端握手: 读取客户端的握手数据并回复选择的版本, 没有握手的旧客户端使用版本1.
+    /// 没有共同支持的版本时返回 UnsupportedVersion, 需要关闭连接.
+    pub fn accept_handshake(&mut self, write_timeout: Duration) -> Result<u8> {
+        let hello = self.read_with(Codec::decode_hello)?;
+        self.reply_handshake(hello, write_timeout)
+    }
+
+    /// 非阻塞模式下的 accept_handshake, 握手数据还未到齐时返回 None.
+    pub fn try_accept_handshake(&mut self, write_timeout: Duration) -> Result<Option<u8>> {
+        match self.try_read_with(Codec::decode_hello)? {
+            Some(hello) => self.reply_handshake(hello, write_timeout).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// 非阻塞模式下读取客户端的握手数据, 返回协商的结果与需要回复的数据, 回复由调用方写出.
+    /// 握手数据还未到齐时返回 None, 没有握手的旧客户端使用版本1, 不需要回复.
+    /// 没有共同支持的版本时结果为 UnsupportedVersion, 写出回复后需要关闭连接.
+    pub fn try_negotiate(&mut self) -> Result<Option<(Result<u8>, Vec<u8>)>> {
+        let hello = self.try_read_with(Codec::decode_hello)?;
+        Ok(hello.map(|hello| self.negotiate(hello)))
+    }
+
+    pub fn get_codec(&self) -> Codec {
+        self.codec
+    }
+
+    fn reply_handshake(&mut self, hello: Option<(u8, u8)>, write_timeout: Duration) -> Result<u8> {
+        let (version, reply) = self.negotiate(hello);
+        if !reply.is_empty() {
+            self.stream.set_write_timeout(Some(write_timeout))?;
+            self.write_all(&reply, write_timeout)?;
+        }
+        version
+    }
+
+    // 按客户端的握手数据选择版本, 返回选择的结果与回复的数据
+    fn negotiate(&mut self, hello: Option<(u8, u8)>) -> (Result<u8>, Vec<u8>) {
+        let (min_version, max_version) = match hello {
+            Some(v) => v,
+            None => {
+                self.codec = self.codec.with_version(MIN_PROTOCOL_VERSION);
+                return (Ok(MIN_PROTOCOL_VERSION), Vec::new());
+            }
+        };
+        let version = Codec::negotiate(min_version, max_version);
+        let reply = Codec::encode_hello_reply(version.unwrap_or(0));
+        let version = match version {
+            Some(v) => v,
+            None => return (Err(IMError::UnsupportedVersion(max_version)), reply),
+        };
+        self.codec = self.codec.with_version(version);
+        (Ok(version), reply)
+    }
+
+    // 从缓冲区解码, 数据不足时阻塞读取 socket. 未知 Action 的数据包记为一次违规后跳过
+    fn read_with<T, F>(&mut self, decode: F) -> Result<T>
+    where
+        F: Fn(&mut Buffer) -> Result<T>,
+    {
+        loop {
+            match decode(&mut self.buffer) {
+                Err(IMError::NotEnoughData) => self.fill()?,
+                Err(e @ IMError::UnknownAction(_)) => self.skip(e)?,
+                ret => return ret,
+            }
+        }
+    }
+
+    // 从缓冲区解码, 数据不足且 socket 中的数据已读完时返回 None
+    fn try_read_with<T, F>(&mut self, decode: F) -> Result<Option<T>>
+    where
+        F: Fn(&mut Buffer) -> Result<T>,
+    {
+        loop {
+            match decode(&mut self.buffer) {
+                Ok(v) => return Ok(Some(v)),
+                Err(IMError::NotEnoughData) => match self.fill() {
+                    Ok(()) => {}
+                    Err(IMError::Io(e)) if e.kind() == io::ErrorKind::WouldBlock => {
+                        return Ok(None);
                     }
+                    Err(e) => return Err(e),
                 },
+                Err(e @ IMError::UnknownAction(_)) => self.skip(e)?,
+                Err(e) => return Err(e),
             }
         }
     }
 
+    fn skip(&self, e: IMError) -> Result<()> {
+        debug!("跳过数据包: {}", e);
+        self.protocol_violation()
+    }
+
+    fn fill(&mut self) -> Result<()> {
+        self.buffer.read_from_reader(&mut self.stream)?;
+        self.last_read_time
+            .store(self.elapsed_millis(), Ordering::SeqCst);
+        Ok(())
+    }
+
     pub fn remote_address(&self) -> String {
-        self.stream.peer_addr().unwrap().to_string()
+        self.stream.remote_address()
     }
 
     pub fn shutdown(&mut self) {
@@ -81,9 +321,8 @@ impl Connection {
             return;
         }
         self.closed.store(true, Ordering::SeqCst);
-        self.stream
-            .shutdown(Shutdown::Both)
-            .expect("shutdown call failed");
+        // the peer may have already reset the connection
+        let _ = self.stream.shutdown();
     }
 
     pub fn set_closed(&mut self) {
@@ -94,11 +333,13 @@ impl Connection {
         self.closed.load(Ordering::SeqCst)
     }
 
-    pub fn get_last_read_time(&self) -> u64 {
-        self.last_read_time.load(Ordering::SeqCst)
+    /// 最后一次读到数据的单调时间, 还没有读过时为创建连接的时间.
+    pub fn get_last_read_time(&self) -> Instant {
+        self.origin + Duration::from_millis(self.last_read_time.load(Ordering::SeqCst))
     }
 
-    pub fn get_last_write_time(&self) -> u64 {
-        self.last_write_time.load(Ordering::SeqCst)
+    /// 最后一次写出数据包的单调时间, 还没有写过时为创建连接的时间.
+    pub fn get_last_write_time(&self) -> Instant {
+        self.origin + Duration::from_millis(self.last_write_time.load(Ordering::SeqCst))
     }
 }
@@ -0,0 +1,334 @@
+use crate::proto::{Action::MSG_TO_USER, MsgToUser, Package};
+use crate::{
+    IMError, OfflineStore, OverflowPolicy, Session, SessionManager, Timeout, TimerTask, WheelTimer,
+};
+use log::{debug, warn};
+use protobuf::Message;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// 记录最近投递的消息的收发双方, 用于校验回执
+const RECENT_MESSAGE_CAPACITY: usize = 100_000;
+
+/// 重传策略, 第n次重传的延迟为 initial_delay * 2^(n-1), 不超过 max_delay.
+#[derive(Debug, Clone, Copy)]
+pub struct RetransmitPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub max_attempts: u32, // 最多重传次数, 超过后放弃
+}
+
+impl Default for RetransmitPolicy {
+    fn default() -> Self {
+        RetransmitPolicy {
+            initial_delay: Duration::from_secs(2),
+            max_delay: Duration::from_secs(30),
+            max_attempts: 5,
+        }
+    }
+}
+
+impl RetransmitPolicy {
+    /// 第 attempt 次重传之前等待的时间, attempt 从1开始.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u32 << attempt.saturating_sub(1).min(16);
+        self.initial_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+}
+
+// 等待送达确认的消息与它的重传任务
+struct Unacked {
+    msg: MsgToUser,
+    timeout: Option<Timeout>,
+}
+
+impl Unacked {
+    // 取消重传任务, 返回消息
+    fn cancel(self) -> MsgToUser {
+        if let Some(timeout) = self.timeout {
+            timeout.cancel();
+        }
+        self.msg
+    }
+}
+
+// 最近投递的消息, 超过容量时淘汰最早的记录
+#[derive(Default)]
+struct RecentMessages {
+    parties: HashMap<u64, (u64, u64)>, // key => message_id, value => (sender_uid, receiver_uid)
+    order: VecDeque<u64>,
+}
+
+impl RecentMessages {
+    fn insert(&mut self, msg: &MsgToUser) {
+        let message_id = msg.get_message_id();
+        let parties = (msg.get_sender_uid(), msg.get_receiver_uid());
+        if self.parties.insert(message_id, parties).is_some() {
+            return;
+        }
+        self.order.push_back(message_id);
+        if self.order.len() > RECENT_MESSAGE_CAPACITY {
+            if let Some(message_id) = self.order.pop_front() {
+                self.parties.remove(&message_id);
+            }
+        }
+    }
+}
+
+/// 服务端消息投递: 推送给在线的接收方并等待送达确认, 超时未确认时按策略重传,
+/// 接收方不在线或重传次数用尽时转入离线队列.
+#[derive(Clone)]
+pub(crate) struct Delivery {
+    session_manager: Arc<SessionManager>,
+    offline_store: Arc<Mutex<Box<dyn OfflineStore>>>,
+    unacked: Arc<Mutex<HashMap<u64, Unacked>>>, // 等待送达确认的消息, key => message_id
+    recent: Arc<Mutex<RecentMessages>>,
+    timer: WheelTimer,
+    policy: RetransmitPolicy,
+}
+
+impl Delivery {
+    pub(crate) fn new(
+        session_manager: Arc<SessionManager>,
+        offline_store: Arc<Mutex<Box<dyn OfflineStore>>>,
+        timer: WheelTimer,
+        policy: RetransmitPolicy,
+    ) -> Delivery {
+        Delivery {
+            session_manager,
+            offline_store,
+            unacked: Arc::new(Mutex::new(HashMap::new())),
+            recent: Arc::default(),
+            timer,
+            policy,
+        }
+    }
+
+    /// 投递消息, 推送给接收方所有在线的设备, 接收方不在线时消息进入离线队列.
+    pub(crate) fn push(&mut self, msg: MsgToUser) {
+        let receiver_uid = msg.get_receiver_uid();
+        let sessions = {
+            // 持有接收方所在分片的锁时入队, 与上线时取出离线消息互斥
+            let user = self.session_manager.lock_user(receiver_uid);
+            let sessions = user.load_all();
+            if sessions.is_empty() {
+                debug!("用户 uid = {} 不在线, 消息进入离线队列", receiver_uid);
+                self.push_offline(msg.clone());
+            }
+            sessions
+        };
+        if sessions.is_empty() {
+            return;
+        }
+        self.recent.lock().unwrap().insert(&msg);
+        let message_id = msg.get_message_id();
+        let unacked = Unacked {
+            msg: msg.clone(),
+            timeout: None,
+        };
+        self.unacked.lock().unwrap().insert(message_id, unacked);
+        if !self.send(&sessions, &msg) {
+            return;
+        }
+        self.schedule(message_id, 1);
+    }
+
+    // 消息放入接收方每个设备的发送队列, 返回是否需要继续等待送达确认, 任一设备确认即为送达.
+    // 所有设备的发送队列都溢出且策略为 SpillOffline 时消息转入离线队列; 其他失败交给重传任务,
+    // 重传时接收方已重新登录则发往新的会话, 已离线则在持有分片锁时转入离线队列.
+    fn send(&self, sessions: &[Arc<Session>], msg: &MsgToUser) -> bool {
+        let mut waiting = false;
+        for session in sessions {
+            let e = match session.send(msg_to_user_package(msg)) {
+                Ok(()) => {
+                    waiting = true;
+                    continue;
+                }
+                Err(e) => e,
+            };
+            debug!("用户 uid = {} 发送失败: {}", msg.get_receiver_uid(), e);
+            match e {
+                IMError::WriteQueueFull
+                    if session.get_overflow_policy() == OverflowPolicy::SpillOffline => {}
+                _ => waiting = true,
+            }
+        }
+        if !waiting && self.forget(msg.get_message_id()) {
+            self.push_offline(msg.clone());
+        }
+        waiting
+    }
+
+    /// 接收方 receiver_uid 确认送达, 只有消息确实发给 receiver_uid 时才停止重传.
+    /// 返回消息的发送方, 不是最近发给 receiver_uid 的消息时返回 None.
+    pub(crate) fn acked(&self, message_id: u64, receiver_uid: u64) -> Option<u64> {
+        let sender_uid = self.sender_of(message_id, receiver_uid)?;
+        self.forget(message_id);
+        Some(sender_uid)
+    }
+
+    /// 最近投递给 receiver_uid 的消息的发送方.
+    pub(crate) fn sender_of(&self, message_id: u64, receiver_uid: u64) -> Option<u64> {
+        match self.recent.lock().unwrap().parties.get(&message_id) {
+            Some(&(sender_uid, uid)) if uid == receiver_uid => Some(sender_uid),
+            _ => None,
+        }
+    }
+
+    /// 将所有等待送达确认的消息转入离线队列, 服务端关闭时调用, 重启后重新投递.
+    pub(crate) fn flush_unacked(&self) {
+        let mut messages: Vec<MsgToUser> = self
+            .unacked
+            .lock()
+            .unwrap()
+            .drain()
+            .map(|(_, v)| v.cancel())
+            .collect();
+        messages.sort_by_key(|v| v.get_message_id());
+        if !messages.is_empty() {
+            debug!("{} 条消息未确认送达, 转入离线队列", messages.len());
+        }
+        for msg in messages {
+            self.push_offline(msg);
+        }
+    }
+
+    /// 用户的会话全部关闭后, 发给该用户等待确认的消息立即转入离线队列并取消重传任务.
+    pub(crate) fn disconnected(&self, uid: u64) {
+        // 持有分片锁, 与重新登录时取出离线消息互斥
+        let user = self.session_manager.lock_user(uid);
+        if user.exist() {
+            return;
+        }
+        let mut messages: Vec<MsgToUser> = {
+            let mut unacked = self.unacked.lock().unwrap();
+            let message_ids: Vec<u64> = unacked
+                .iter()
+                .filter(|(_, v)| v.msg.get_receiver_uid() == uid)
+                .map(|(k, _)| *k)
+                .collect();
+            message_ids
+                .iter()
+                .filter_map(|v| unacked.remove(v))
+                .map(|v| v.cancel())
+                .collect()
+        };
+        messages.sort_by_key(|v| v.get_message_id());
+        if !messages.is_empty() {
+            debug!(
+                "用户 uid = {} 离线, {} 条消息未确认送达, 转入离线队列",
+                uid,
+                messages.len()
+            );
+        }
+        for msg in messages {
+            self.push_offline(msg);
+        }
+    }
+
+    // 停止跟踪消息并取消重传任务, 返回消息之前是否在等待确认. 重传任务与送达确认
+    // 可能并发执行, 只有成功移除的一方才能把消息转入离线队列.
+    fn forget(&self, message_id: u64) -> bool {
+        match self.unacked.lock().unwrap().remove(&message_id) {
+            Some(v) => {
+                v.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn push_offline(&self, msg: MsgToUser) {
+        let uid = msg.get_receiver_uid();
+        if let Err(e) = self.offline_store.lock().unwrap().push(uid, msg) {
+            warn!("离线消息入队失败: {}", e);
+        }
+    }
+
+    fn schedule(&mut self, message_id: u64, attempt: u32) {
+        let task = RetransmitTask {
+            message_id,
+            attempt,
+            delivery: self.clone(),
+        };
+        let timeout = match self
+            .timer
+            .new_timeout(Box::new(task), self.policy.delay(attempt))
+        {
+            Ok(v) => v,
+            Err(e) => {
+                // 不再重传, 接收方离线时消息转入离线队列
+                warn!("消息 message_id = {} 无法安排重传: {}", message_id, e);
+                return;
+            }
+        };
+        // 已经确认送达的消息不再重传
+        match self.unacked.lock().unwrap().get_mut(&message_id) {
+            Some(v) => v.timeout = Some(timeout),
+            None => {
+                timeout.cancel();
+            }
+        }
+    }
+}
+
+fn msg_to_user_package(msg: &MsgToUser) -> Package {
+    let mut package = Package::new();
+    package.set_action(MSG_TO_USER);
+    package.set_content(msg.write_to_bytes().unwrap());
+    package
+}
+
+struct RetransmitTask {
+    message_id: u64,
+    attempt: u32, // 第几次重传
+    delivery: Delivery,
+}
+
+impl TimerTask for RetransmitTask {
+    fn run(&mut self) {
+        // 已确认送达的消息不在 unacked 中
+        let msg = match self.delivery.unacked.lock().unwrap().get(&self.message_id) {
+            Some(v) => v.msg.clone(),
+            None => return,
+        };
+        if self.attempt > self.delivery.policy.max_attempts {
+            debug!(
+                "消息 message_id = {} 重传 {} 次未确认, 转入离线队列",
+                self.message_id, self.delivery.policy.max_attempts
+            );
+            if self.delivery.forget(self.message_id) {
+                self.delivery.push_offline(msg);
+            }
+            return;
+        }
+
+        let sessions = {
+            let user = self
+                .delivery
+                .session_manager
+                .lock_user(msg.get_receiver_uid());
+            let sessions = user.load_all();
+            if sessions.is_empty() && self.delivery.forget(self.message_id) {
+                self.delivery.push_offline(msg.clone());
+            }
+            sessions
+        };
+        if sessions.is_empty() {
+            return;
+        }
+        debug!(
+            "重传消息 message_id = {}, attempt = {}",
+            self.message_id, self.attempt
+        );
+        if !self.delivery.send(&sessions, &msg) {
+            return;
+        }
+        let attempt = self.attempt + 1;
+        self.delivery.schedule(self.message_id, attempt);
+    }
+}
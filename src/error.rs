@@ -1,3 +1,4 @@
+use crate::proto::Action;
 use std::fmt::{Debug, Display, Formatter};
 use std::io;
 use std::io::Error;
@@ -6,7 +7,22 @@ use std::io::Error;
 pub enum IMError {
     NotEnoughData,
     ContentMaxLen,
+    MalformedFrame,
+    UnsupportedVersion(u8),
+    BadMagic,
+    UnknownAction(u16),
+    MalformedPayload(Action),
+    TooManyViolations(u32),
     TcpStreamEOF,
+    Unauthorized(String),
+    RoomNotFound(u64),
+    NotRoomMember(u64),
+    MessageNotFound(u64),
+    WriteQueueFull,
+    TooManyTimeouts(u64),
+    TimerStopped,
+    ConnectionClosed,
+    InvalidConfig(String),
     Io(io::Error),
 }
 
@@ -15,7 +31,28 @@ impl Display for IMError {
         match self {
             IMError::NotEnoughData => write!(f, "Not enough data"),
             IMError::ContentMaxLen => write!(f, "The message exceeds the maximum length limit"),
+            IMError::MalformedFrame => write!(f, "Malformed frame"),
+            IMError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported frame version {}", version)
+            }
+            IMError::BadMagic => write!(f, "Bad handshake magic"),
+            IMError::UnknownAction(action) => write!(f, "Unknown action {}", action),
+            IMError::MalformedPayload(action) => write!(f, "Malformed {:?} payload", action),
+            IMError::TooManyViolations(n) => write!(f, "Too many protocol violations ({})", n),
             IMError::TcpStreamEOF => write!(f, "EOF reached"),
+            IMError::Unauthorized(reason) => write!(f, "Unauthorized: {}", reason),
+            IMError::RoomNotFound(room_id) => write!(f, "Room {} not found", room_id),
+            IMError::NotRoomMember(room_id) => write!(f, "Not a member of room {}", room_id),
+            IMError::MessageNotFound(message_id) => {
+                write!(f, "Message {} not found", message_id)
+            }
+            IMError::WriteQueueFull => write!(f, "The write queue is full"),
+            IMError::TooManyTimeouts(max) => {
+                write!(f, "Too many pending timeouts (max {})", max)
+            }
+            IMError::TimerStopped => write!(f, "The timer is stopped"),
+            IMError::ConnectionClosed => write!(f, "The connection is closed"),
+            IMError::InvalidConfig(reason) => write!(f, "Invalid config: {}", reason),
             IMError::Io(e) => write!(f, "IO error: {}", e),
         }
     }
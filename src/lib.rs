@@ -1,20 +1,56 @@
+mod auth;
 mod buffer;
 mod client;
+mod clock;
 mod codec;
+mod config;
 mod connection;
+mod delivery;
 mod error;
+mod message_store;
 mod message_system;
+mod offline;
+mod outbox;
+mod presence;
 pub mod proto;
+mod room;
 mod server;
 mod session;
+#[cfg(feature = "tls")]
+mod tls;
+mod transport;
+#[cfg(feature = "websocket")]
+mod websocket;
 mod wheel_timer;
+mod write_queue;
 
+pub use auth::{Authenticator, FileUserStore};
 pub use buffer::Buffer;
-pub use client::IMClient;
-pub use codec::Codec;
-pub use connection::Connection;
+pub use client::{Event, IMClient, MessageHandle, ReconnectPolicy};
+pub use clock::{Clock, MockClock, SystemClock};
+pub use codec::{
+    Codec, DEFAULT_CONTENT_MAX_LEN, DEFAULT_MAX_FRAME_SIZE, MAGIC, MIN_PROTOCOL_VERSION,
+    PROTOCOL_VERSION,
+};
+pub use config::{ClientConfig, ServerConfig};
+pub use connection::{Connection, DEFAULT_MAX_PROTOCOL_VIOLATIONS};
+pub use delivery::RetransmitPolicy;
 pub use error::{IMError, Result};
+pub use message_store::{LogMessageStore, MessageStore};
 pub use message_system::MessageSystem;
-pub use server::IMServer;
-pub use session::{Session, SessionManager};
-pub use wheel_timer::{TimerTask, WheelTimer};
+pub use offline::{FileOfflineStore, MemoryOfflineStore, OfflineStore};
+pub use outbox::{Outbox, PendingMessage, SendState};
+pub use presence::PresenceManager;
+pub use room::{Room, RoomManager};
+pub use server::{IMServer, ShutdownHandle};
+pub use session::{
+    DetachedSession, LoginPolicy, RecentSeqs, Session, SessionManager, UserSessions,
+    DEFAULT_SESSION_SHARDS,
+};
+#[cfg(feature = "tls")]
+pub use tls::{TlsAcceptor, TlsConnector, TlsTransport};
+pub use transport::{MemoryTransport, Transport};
+#[cfg(feature = "websocket")]
+pub use websocket::WebSocketTransport;
+pub use wheel_timer::{PanicHook, Timeout, TimerTask, WheelTimer, DEFAULT_WHEEL_LEVELS};
+pub use write_queue::{OverflowPolicy, WriteQueue, DEFAULT_WRITE_QUEUE_CAPACITY};
@@ -0,0 +1,338 @@
+use crate::proto::MsgToUser;
+use crate::{IMError, Result};
+use log::{debug, warn};
+use protobuf::Message;
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// 消息持久化存储, 消息按照 message_id 递增的顺序追加写入.
+pub trait MessageStore: Send {
+    /// 追加一条已分配 message_id 的消息.
+    fn append(&mut self, msg: &MsgToUser) -> Result<()>;
+
+    /// 根据 message_id 读取消息.
+    fn load(&mut self, message_id: u64) -> Result<Option<MsgToUser>>;
+
+    /// 最后一条持久化消息的ID, 没有任何消息时返回 None.
+    fn last_message_id(&self) -> Option<u64>;
+
+    /// 将缓冲的数据刷到磁盘.
+    fn flush(&mut self) -> Result<()>;
+}
+
+const LOG_SUFFIX: &str = "log";
+const INDEX_SUFFIX: &str = "index";
+// 日志记录头部: len(4字节) | crc32(4字节)
+const RECORD_HEAD_LEN: u64 = 8;
+// 索引项: message_id(8字节) | position(8字节)
+const INDEX_ENTRY_LEN: u64 = 16;
+// 单个日志段默认最大64MB
+const DEFAULT_SEGMENT_MAX_BYTES: u64 = 64 * 1024 * 1024;
+
+/// 基于追加日志的消息存储
+///
+/// 目录下由若干日志段组成, 每个段以段内第一条消息的ID命名:
+/// -----------------------------------------------------------
+/// | 00000000000000010000.log   | len(4) | crc32(4) | body(len) | ...
+/// | 00000000000000010000.index | message_id(8) | position(8) | ...
+/// -----------------------------------------------------------
+/// 只有最后一个段可写, 段大小超过上限后滚动生成新段. 重启时会校验
+/// 最后一个段, 截断写了一半的记录并重建其索引.
+pub struct LogMessageStore {
+    dir: PathBuf,
+    segment_max_bytes: u64,
+    segments: Vec<u64>, // 所有段的起始消息ID, 升序
+    active: Option<Segment>,
+    last_message_id: Option<u64>,
+}
+
+struct Segment {
+    log: File,
+    index: File,
+    size: u64,
+}
+
+impl Segment {
+    fn write(&mut self, record: &[u8], entry: &[u8]) -> io::Result<()> {
+        self.log.write_all(record)?;
+        self.index.write_all(entry)
+    }
+
+    // 日志截断到 position, 索引截断到 index_len, 并把写入位置移到末尾
+    fn truncate(&mut self, position: u64, index_len: u64) -> io::Result<()> {
+        self.log.set_len(position)?;
+        self.log.seek(SeekFrom::Start(position))?;
+        self.index.set_len(index_len)?;
+        self.index.seek(SeekFrom::Start(index_len))?;
+        Ok(())
+    }
+}
+
+impl LogMessageStore {
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<LogMessageStore> {
+        LogMessageStore::with_segment_max_bytes(dir, DEFAULT_SEGMENT_MAX_BYTES)
+    }
+
+    pub fn with_segment_max_bytes<P: AsRef<Path>>(
+        dir: P,
+        segment_max_bytes: u64,
+    ) -> Result<LogMessageStore> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        let mut segments = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|v| v.to_str()) != Some(LOG_SUFFIX) {
+                continue;
+            }
+            let base_id = path
+                .file_stem()
+                .and_then(|v| v.to_str())
+                .and_then(|v| v.parse::<u64>().ok());
+            if let Some(base_id) = base_id {
+                segments.push(base_id);
+            }
+        }
+        segments.sort_unstable();
+
+        let mut store = LogMessageStore {
+            dir,
+            segment_max_bytes,
+            segments,
+            active: None,
+            last_message_id: None,
+        };
+        store.recover()?;
+        Ok(store)
+    }
+
+    // 校验最后一个段并恢复 last_message_id, 空段会被删除并回退到前一个段.
+    fn recover(&mut self) -> Result<()> {
+        while let Some(&base_id) = self.segments.last() {
+            let (segment, last_id) = self.recover_segment(base_id)?;
+            if let Some(last_id) = last_id {
+                self.active = Some(segment);
+                self.last_message_id = Some(last_id);
+                return Ok(());
+            }
+            drop(segment);
+            fs::remove_file(self.segment_path(base_id, LOG_SUFFIX))?;
+            fs::remove_file(self.segment_path(base_id, INDEX_SUFFIX))?;
+            self.segments.pop();
+        }
+        Ok(())
+    }
+
+    fn recover_segment(&self, base_id: u64) -> Result<(Segment, Option<u64>)> {
+        let mut log = open_file(&self.segment_path(base_id, LOG_SUFFIX))?;
+        let mut index = open_file(&self.segment_path(base_id, INDEX_SUFFIX))?;
+        let len = log.metadata()?.len();
+
+        let mut entries = Vec::new();
+        let mut position = 0;
+        log.seek(SeekFrom::Start(0))?;
+        while position < len {
+            match read_record(&mut log, position, len) {
+                Some((msg, record_len)) => {
+                    entries.push((msg.get_message_id(), position));
+                    position += record_len;
+                }
+                None => {
+                    warn!(
+                        "truncate segment {} at position {}, {} bytes dropped",
+                        base_id,
+                        position,
+                        len - position
+                    );
+                    break;
+                }
+            }
+        }
+        log.set_len(position)?;
+        log.seek(SeekFrom::End(0))?;
+
+        let mut buf = Vec::with_capacity(entries.len() * INDEX_ENTRY_LEN as usize);
+        for (message_id, position) in entries.iter() {
+            buf.extend_from_slice(&message_id.to_be_bytes());
+            buf.extend_from_slice(&position.to_be_bytes());
+        }
+        index.set_len(0)?;
+        index.seek(SeekFrom::Start(0))?;
+        index.write_all(&buf)?;
+
+        debug!(
+            "recover segment {} with {} messages",
+            base_id,
+            entries.len()
+        );
+        let segment = Segment {
+            log,
+            index,
+            size: position,
+        };
+        Ok((segment, entries.last().map(|v| v.0)))
+    }
+
+    fn segment_path(&self, base_id: u64, suffix: &str) -> PathBuf {
+        self.dir.join(format!("{:020}.{}", base_id, suffix))
+    }
+
+    fn roll(&mut self, base_id: u64) -> Result<()> {
+        if let Some(segment) = self.active.take() {
+            segment.log.sync_all()?;
+            segment.index.sync_all()?;
+        }
+        let log = open_file(&self.segment_path(base_id, LOG_SUFFIX))?;
+        let index = open_file(&self.segment_path(base_id, INDEX_SUFFIX))?;
+        self.segments.push(base_id);
+        self.active = Some(Segment {
+            log,
+            index,
+            size: 0,
+        });
+        Ok(())
+    }
+
+    // 在段索引中二分查找 message_id 对应的日志位置
+    fn find_position(&self, base_id: u64, message_id: u64) -> Result<Option<u64>> {
+        let mut index = File::open(self.segment_path(base_id, INDEX_SUFFIX))?;
+        let entries = index.metadata()?.len() / INDEX_ENTRY_LEN;
+        let (mut low, mut high) = (0, entries);
+        let mut entry = [0u8; INDEX_ENTRY_LEN as usize];
+        while low < high {
+            let mid = low + (high - low) / 2;
+            index.seek(SeekFrom::Start(mid * INDEX_ENTRY_LEN))?;
+            index.read_exact(&mut entry)?;
+            let id = be_u64(&entry[..8]);
+            if id == message_id {
+                return Ok(Some(be_u64(&entry[8..])));
+            } else if id < message_id {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl MessageStore for LogMessageStore {
+    fn append(&mut self, msg: &MsgToUser) -> Result<()> {
+        // 上一次写入失败且无法截断时, 重新校验最后一个段
+        if self.active.is_none() {
+            self.recover()?;
+        }
+        let message_id = msg.get_message_id();
+        if let Some(last_id) = self.last_message_id {
+            if message_id <= last_id {
+                return Err(IMError::Io(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("message_id {} <= last message_id {}", message_id, last_id),
+                )));
+            }
+        }
+        let need_roll = match self.active.as_ref() {
+            None => true,
+            Some(segment) => segment.size >= self.segment_max_bytes,
+        };
+        if need_roll {
+            self.roll(message_id)?;
+        }
+
+        let body = msg
+            .write_to_bytes()
+            .map_err(|e| IMError::Io(io::Error::new(io::ErrorKind::InvalidData, e)))?;
+        let mut record = Vec::with_capacity(RECORD_HEAD_LEN as usize + body.len());
+        record.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        record.extend_from_slice(&crc32fast::hash(&body).to_be_bytes());
+        record.extend_from_slice(&body);
+
+        let segment = self.active.as_mut().unwrap();
+        let position = segment.size;
+        let index_len = segment.index.stream_position()?;
+        let mut entry = [0u8; INDEX_ENTRY_LEN as usize];
+        entry[..8].copy_from_slice(&message_id.to_be_bytes());
+        entry[8..].copy_from_slice(&position.to_be_bytes());
+        if let Err(e) = segment.write(&record, &entry) {
+            // 截掉写了一半的记录和索引项, 下一条消息仍从 position 开始写
+            if let Err(truncate_err) = segment.truncate(position, index_len) {
+                warn!("failed to truncate the active segment: {}", truncate_err);
+                self.active = None;
+            }
+            return Err(e.into());
+        }
+
+        segment.size += record.len() as u64;
+        self.last_message_id = Some(message_id);
+        Ok(())
+    }
+
+    fn load(&mut self, message_id: u64) -> Result<Option<MsgToUser>> {
+        let base_id = match self.segments.iter().rev().find(|&&v| v <= message_id) {
+            Some(&v) => v,
+            None => return Ok(None),
+        };
+        let position = match self.find_position(base_id, message_id)? {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        // 独立的读句柄, 避免移动活跃段的写入位置
+        let mut log = File::open(self.segment_path(base_id, LOG_SUFFIX))?;
+        let len = log.metadata()?.len();
+        Ok(read_record(&mut log, position, len).map(|v| v.0))
+    }
+
+    fn last_message_id(&self) -> Option<u64> {
+        self.last_message_id
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if let Some(segment) = self.active.as_mut() {
+            segment.log.sync_data()?;
+            segment.index.sync_data()?;
+        }
+        Ok(())
+    }
+}
+
+fn open_file(path: &Path) -> Result<File> {
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)?;
+    Ok(file)
+}
+
+// 读取并校验 position 处的一条记录及其长度, 记录不完整或校验失败时返回 None
+fn read_record(log: &mut File, position: u64, len: u64) -> Option<(MsgToUser, u64)> {
+    if position + RECORD_HEAD_LEN > len {
+        return None;
+    }
+    let mut head = [0u8; RECORD_HEAD_LEN as usize];
+    log.seek(SeekFrom::Start(position)).ok()?;
+    log.read_exact(&mut head).ok()?;
+    let body_len = u32::from_be_bytes([head[0], head[1], head[2], head[3]]) as u64;
+    let crc = u32::from_be_bytes([head[4], head[5], head[6], head[7]]);
+    if position + RECORD_HEAD_LEN + body_len > len {
+        return None;
+    }
+    let mut body = vec![0u8; body_len as usize];
+    log.read_exact(&mut body).ok()?;
+    if crc32fast::hash(&body) != crc {
+        return None;
+    }
+    let msg = MsgToUser::parse_from_bytes(&body).ok()?;
+    Some((msg, RECORD_HEAD_LEN + body_len))
+}
+
+fn be_u64(buf: &[u8]) -> u64 {
+    let mut v = [0u8; 8];
+    v.copy_from_slice(buf);
+    u64::from_be_bytes(v)
+}
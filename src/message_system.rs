@@ -1,17 +1,67 @@
+use crate::proto::MsgToUser;
+use crate::{MessageStore, Result};
 use std::sync::atomic::{AtomicU64, Ordering};
 
+// 没有任何持久化消息时, 消息ID的起始值
+const FIRST_MESSAGE_ID: u64 = 10000;
+
 pub struct MessageSystem {
     last_message_id: AtomicU64,
+    store: Option<Box<dyn MessageStore>>,
+}
+
+impl Default for MessageSystem {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MessageSystem {
     pub fn new() -> MessageSystem {
         MessageSystem {
-            last_message_id: AtomicU64::new(10000),
+            last_message_id: AtomicU64::new(FIRST_MESSAGE_ID),
+            store: None,
+        }
+    }
+
+    /// 消息持久化到 store, 重启后从最后一条持久化的消息ID继续分配.
+    pub fn with_store(store: Box<dyn MessageStore>) -> MessageSystem {
+        let next_message_id = match store.last_message_id() {
+            Some(v) => v + 1,
+            None => FIRST_MESSAGE_ID,
+        };
+        MessageSystem {
+            last_message_id: AtomicU64::new(next_message_id),
+            store: Some(store),
         }
     }
 
     pub fn next_seq(&self) -> u64 {
         self.last_message_id.fetch_add(1, Ordering::SeqCst)
     }
+
+    /// 为消息分配ID并持久化, 返回分配的消息ID. 持久化失败时ID不被占用, 下一条消息继续使用.
+    pub fn save(&mut self, msg: &mut MsgToUser) -> Result<u64> {
+        let message_id = self.last_message_id.load(Ordering::SeqCst);
+        msg.set_message_id(message_id);
+        if let Some(store) = self.store.as_mut() {
+            store.append(msg)?;
+        }
+        self.last_message_id.store(message_id + 1, Ordering::SeqCst);
+        Ok(message_id)
+    }
+
+    pub fn load(&mut self, message_id: u64) -> Result<Option<MsgToUser>> {
+        match self.store.as_mut() {
+            Some(store) => store.load(message_id),
+            None => Ok(None),
+        }
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        match self.store.as_mut() {
+            Some(store) => store.flush(),
+            None => Ok(()),
+        }
+    }
 }
@@ -0,0 +1,132 @@
+use crate::proto::MsgToUser;
+use crate::{IMError, Result};
+use log::warn;
+use protobuf::Message;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// 离线消息队列, 保存接收方不在线时的消息, 等待用户上线后按顺序投递.
+pub trait OfflineStore: Send {
+    /// 追加一条发给 uid 的离线消息.
+    fn push(&mut self, uid: u64, msg: MsgToUser) -> Result<()>;
+
+    /// 取出 uid 的全部离线消息, 按入队顺序返回.
+    fn take(&mut self, uid: u64) -> Result<Vec<MsgToUser>>;
+
+    /// 将缓冲的数据刷到磁盘.
+    fn flush(&mut self) -> Result<()>;
+}
+
+/// 内存离线队列, 重启后丢失.
+#[derive(Default)]
+pub struct MemoryOfflineStore {
+    queues: HashMap<u64, VecDeque<MsgToUser>>,
+}
+
+impl MemoryOfflineStore {
+    pub fn new() -> MemoryOfflineStore {
+        MemoryOfflineStore {
+            queues: HashMap::new(),
+        }
+    }
+}
+
+impl OfflineStore for MemoryOfflineStore {
+    fn push(&mut self, uid: u64, msg: MsgToUser) -> Result<()> {
+        self.queues.entry(uid).or_default().push_back(msg);
+        Ok(())
+    }
+
+    fn take(&mut self, uid: u64) -> Result<Vec<MsgToUser>> {
+        match self.queues.remove(&uid) {
+            Some(queue) => Ok(queue.into_iter().collect()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+const OFFLINE_SUFFIX: &str = "offline";
+// 离线记录头部: len(4字节)
+const RECORD_HEAD_LEN: usize = 4;
+
+/// 基于文件的离线队列, 每个用户一个文件, 消息以 len(4) | body(len) 的格式追加写入.
+pub struct FileOfflineStore {
+    dir: PathBuf,
+}
+
+impl FileOfflineStore {
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<FileOfflineStore> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(FileOfflineStore { dir })
+    }
+
+    fn queue_path(&self, uid: u64) -> PathBuf {
+        self.dir.join(format!("{}.{}", uid, OFFLINE_SUFFIX))
+    }
+}
+
+impl OfflineStore for FileOfflineStore {
+    fn push(&mut self, uid: u64, msg: MsgToUser) -> Result<()> {
+        let body = msg
+            .write_to_bytes()
+            .map_err(|e| IMError::Io(io::Error::new(io::ErrorKind::InvalidData, e)))?;
+        let mut record = Vec::with_capacity(RECORD_HEAD_LEN + body.len());
+        record.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        record.extend_from_slice(&body);
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.queue_path(uid))?;
+        file.write_all(&record)?;
+        Ok(())
+    }
+
+    fn take(&mut self, uid: u64) -> Result<Vec<MsgToUser>> {
+        let path = self.queue_path(uid);
+        let buf = match fs::read(&path) {
+            Ok(v) => v,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut messages = Vec::new();
+        let mut position = 0;
+        while position + RECORD_HEAD_LEN <= buf.len() {
+            let mut len = [0u8; RECORD_HEAD_LEN];
+            len.copy_from_slice(&buf[position..position + RECORD_HEAD_LEN]);
+            let start = position + RECORD_HEAD_LEN;
+            let end = start + u32::from_be_bytes(len) as usize;
+            if end > buf.len() {
+                break;
+            }
+            match MsgToUser::parse_from_bytes(&buf[start..end]) {
+                Ok(msg) => messages.push(msg),
+                Err(e) => warn!("skip malformed offline message of uid = {}: {}", uid, e),
+            }
+            position = end;
+        }
+        if position != buf.len() {
+            warn!(
+                "offline queue of uid = {} has {} trailing bytes",
+                uid,
+                buf.len() - position
+            );
+        }
+        fs::remove_file(&path)?;
+        Ok(messages)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        // 每次 push 都直接写入文件, 没有需要刷新的缓冲
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use crate::proto::MsgToUser;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+// 最多保留的已完成(送达/已读/失败)消息数量, 超出后淘汰最早的记录
+const MAX_FINISHED_MESSAGES: usize = 1024;
+// 最多保留的已被服务端接收但未送达的消息数量, 例如接收方一直不上线, 超出后淘汰最早的记录
+const MAX_ACCEPTED_MESSAGES: usize = 4096;
+
+/// 客户端发出消息的投递状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendState {
+    /// 已发出, 等待服务端确认
+    Sending,
+    /// 服务端已接收并分配消息ID
+    Accepted,
+    /// 接收方已收到
+    Delivered,
+    /// 接收方已读
+    Read,
+    /// 重传次数用尽仍未收到服务端确认
+    Failed,
+}
+
+#[derive(Debug, Clone)]
+pub struct PendingMessage {
+    pub seq: u64,
+    pub message_id: u64,
+    pub state: SendState,
+    pub msg: MsgToUser,
+}
+
+/// 发件箱, 按客户端 seq 跟踪每条发出消息的投递状态.
+#[derive(Default)]
+pub struct Outbox {
+    messages: BTreeMap<u64, PendingMessage>, // key => seq
+    seq_index: HashMap<u64, u64>,            // key => message_id, value => seq
+    accepted: VecDeque<u64>,                 // 按进入 Accepted 的顺序记录 seq
+    finished: VecDeque<u64>,                 // 按完成的顺序记录 seq
+}
+
+impl Outbox {
+    pub fn new() -> Outbox {
+        Outbox {
+            messages: BTreeMap::new(),
+            seq_index: HashMap::new(),
+            accepted: VecDeque::new(),
+            finished: VecDeque::new(),
+        }
+    }
+
+    pub fn add(&mut self, msg: MsgToUser) {
+        let seq = msg.get_seq();
+        let pending = PendingMessage {
+            seq,
+            message_id: 0,
+            state: SendState::Sending,
+            msg,
+        };
+        self.messages.insert(seq, pending);
+    }
+
+    /// 服务端确认, 记录分配的消息ID. 返回对应的消息, 重复确认时返回 None.
+    pub fn accepted(&mut self, seq: u64, message_id: u64) -> Option<&PendingMessage> {
+        let pending = self.messages.get_mut(&seq)?;
+        // 放弃重传之后仍可能收到迟到的确认
+        if pending.state != SendState::Sending && pending.state != SendState::Failed {
+            return None;
+        }
+        pending.message_id = message_id;
+        pending.msg.set_message_id(message_id);
+        pending.state = SendState::Accepted;
+        self.seq_index.insert(message_id, seq);
+        self.accepted.push_back(seq);
+        self.evict();
+        self.messages.get(&seq)
+    }
+
+    /// 放弃等待服务端确认. 返回对应的消息, 已确认的消息返回 None.
+    pub fn failed(&mut self, seq: u64) -> Option<&PendingMessage> {
+        let pending = self.messages.get_mut(&seq)?;
+        if pending.state != SendState::Sending {
+            return None;
+        }
+        pending.state = SendState::Failed;
+        self.finished.push_back(seq);
+        self.evict();
+        self.messages.get(&seq)
+    }
+
+    pub fn delivered(&mut self, message_id: u64) -> Option<&PendingMessage> {
+        self.transfer(message_id, SendState::Delivered)
+    }
+
+    pub fn read(&mut self, message_id: u64) -> Option<&PendingMessage> {
+        self.transfer(message_id, SendState::Read)
+    }
+
+    // 状态只会前进, 已读之后收到迟到的送达确认不会回退
+    fn transfer(&mut self, message_id: u64, state: SendState) -> Option<&PendingMessage> {
+        let seq = *self.seq_index.get(&message_id)?;
+        let pending = self.messages.get_mut(&seq)?;
+        if pending.state == SendState::Read || pending.state == state {
+            return None;
+        }
+        if !is_finished(pending.state) {
+            self.finished.push_back(seq);
+        }
+        pending.state = state;
+        self.evict();
+        self.messages.get(&seq)
+    }
+
+    pub fn get(&self, seq: u64) -> Option<&PendingMessage> {
+        self.messages.get(&seq)
+    }
+
+    pub fn state(&self, seq: u64) -> Option<SendState> {
+        self.messages.get(&seq).map(|v| v.state)
+    }
+
+    /// 尚未送达接收方的消息, 按 seq 升序.
+    pub fn pending(&self) -> Vec<PendingMessage> {
+        self.messages
+            .values()
+            .filter(|v| v.state == SendState::Sending || v.state == SendState::Accepted)
+            .cloned()
+            .collect()
+    }
+
+    // 两个队列超过上限时淘汰最早的记录, 之后状态已经改变的记录只出队不删除
+    fn evict(&mut self) {
+        while self.accepted.len() > MAX_ACCEPTED_MESSAGES {
+            if let Some(seq) = self.accepted.pop_front() {
+                self.remove_if(seq, |state| state == SendState::Accepted);
+            }
+        }
+        while self.finished.len() > MAX_FINISHED_MESSAGES {
+            if let Some(seq) = self.finished.pop_front() {
+                self.remove_if(seq, is_finished);
+            }
+        }
+    }
+
+    fn remove_if<F: Fn(SendState) -> bool>(&mut self, seq: u64, f: F) {
+        if !self.messages.get(&seq).is_some_and(|v| f(v.state)) {
+            return;
+        }
+        if let Some(pending) = self.messages.remove(&seq) {
+            self.seq_index.remove(&pending.message_id);
+        }
+    }
+}
+
+fn is_finished(state: SendState) -> bool {
+    state != SendState::Sending && state != SendState::Accepted
+}
@@ -0,0 +1,142 @@
+use crate::proto::{Action::PRESENCE, Package, Presence, PresenceStatus};
+use crate::wheel_timer::system_time_unix;
+use crate::SessionManager;
+use log::debug;
+use protobuf::Message;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// 用户的在线状态与订阅关系. 用户有任一会话时在线, 在线用户可以设置为离开;
+/// 订阅属于用户, 用户所有设备都会收到订阅用户的状态变化, 用户离线后订阅随之清除.
+///
+/// 持有 PresenceManager 锁时会访问 SessionManager, 反之不可以.
+pub struct PresenceManager {
+    session_manager: Arc<SessionManager>,
+    subscribers: HashMap<u64, HashSet<u64>>, // key => 被订阅的uid, value => 订阅者
+    subscriptions: HashMap<u64, HashSet<u64>>, // key => 订阅者, value => 订阅的uid
+    away: HashSet<u64>,                      // 设置为离开的在线用户
+    published: HashMap<u64, PresenceStatus>, // 最近一次通知订阅者的状态, 离线的用户不记录
+}
+
+impl PresenceManager {
+    pub fn new(session_manager: Arc<SessionManager>) -> PresenceManager {
+        PresenceManager {
+            session_manager,
+            subscribers: HashMap::new(),
+            subscriptions: HashMap::new(),
+            away: HashSet::new(),
+            published: HashMap::new(),
+        }
+    }
+
+    /// 用户当前的在线状态.
+    pub fn status(&self, uid: u64) -> PresenceStatus {
+        if !self.session_manager.exist(uid) {
+            PresenceStatus::OFFLINE
+        } else if self.away.contains(&uid) {
+            PresenceStatus::AWAY
+        } else {
+            PresenceStatus::ONLINE
+        }
+    }
+
+    /// 查询用户的在线状态, uids 为空时返回所有在线用户.
+    pub fn query(&self, uids: &[u64]) -> Vec<Presence> {
+        let uids = match uids {
+            [] => self.session_manager.online_users(),
+            uids => uids.to_vec(),
+        };
+        uids.into_iter()
+            .map(|uid| presence(uid, self.status(uid)))
+            .collect()
+    }
+
+    /// subscriber 订阅 uids 的状态变化, 返回它们当前的状态.
+    pub fn subscribe(&mut self, subscriber: u64, uids: &[u64]) -> Vec<Presence> {
+        for &uid in uids {
+            self.subscribers.entry(uid).or_default().insert(subscriber);
+            self.subscriptions
+                .entry(subscriber)
+                .or_default()
+                .insert(uid);
+        }
+        self.query(uids)
+    }
+
+    pub fn unsubscribe(&mut self, subscriber: u64, uids: &[u64]) {
+        for uid in uids {
+            self.remove_subscriber(*uid, subscriber);
+            if let Some(subscriptions) = self.subscriptions.get_mut(&subscriber) {
+                subscriptions.remove(uid);
+            }
+        }
+    }
+
+    /// 在线用户设置为离开或恢复在线, 之后需要调用 changed 通知订阅者.
+    pub fn set_away(&mut self, uid: u64, away: bool) {
+        match away {
+            true => self.away.insert(uid),
+            false => self.away.remove(&uid),
+        };
+    }
+
+    /// 用户上线、下线或设置状态之后调用, 当前状态与上次通知的不同时通知订阅者.
+    /// 状态总是在持有锁时重新读取, 并发登录和断开时订阅者最后收到的仍是实际状态.
+    pub fn changed(&mut self, uid: u64) {
+        let status = self.status(uid);
+        let last = self
+            .published
+            .get(&uid)
+            .copied()
+            .unwrap_or(PresenceStatus::OFFLINE);
+        if status == last {
+            return;
+        }
+        debug!("用户 uid = {} 状态变为 {:?}", uid, status);
+        if status == PresenceStatus::OFFLINE {
+            self.published.remove(&uid);
+            self.away.remove(&uid);
+            // 离线用户的订阅不再保留, 重新上线后由客户端再次订阅
+            for target in self.subscriptions.remove(&uid).unwrap_or_default() {
+                self.remove_subscriber(target, uid);
+            }
+        } else {
+            self.published.insert(uid, status);
+        }
+        self.notify(presence(uid, status));
+    }
+
+    fn remove_subscriber(&mut self, uid: u64, subscriber: u64) {
+        if let Some(subscribers) = self.subscribers.get_mut(&uid) {
+            subscribers.remove(&subscriber);
+            if subscribers.is_empty() {
+                self.subscribers.remove(&uid);
+            }
+        }
+    }
+
+    // 推送给所有订阅者的所有设备
+    fn notify(&self, presence: Presence) {
+        let subscribers = match self.subscribers.get(&presence.get_uid()) {
+            Some(v) => v,
+            None => return,
+        };
+        let content = presence.write_to_bytes().unwrap();
+        for &subscriber in subscribers {
+            for session in self.session_manager.load_all(subscriber) {
+                let mut package = Package::new();
+                package.set_action(PRESENCE);
+                package.set_content(content.clone());
+                let _ = session.send(package);
+            }
+        }
+    }
+}
+
+fn presence(uid: u64, status: PresenceStatus) -> Presence {
+    let mut presence = Presence::new();
+    presence.set_uid(uid);
+    presence.set_status(status);
+    presence.set_timestamp(system_time_unix());
+    presence
+}
@@ -218,6 +218,10 @@ pub struct ConnectedReply {
     // message fields
     pub uid: u64,
     pub session_id: ::std::string::String,
+    pub version: u32,
+    pub supported_versions: ::std::vec::Vec<u32>,
+    pub resumed: bool,
+    pub resume_token: ::std::string::String,
     // special fields
     pub unknown_fields: ::protobuf::UnknownFields,
     pub cached_size: ::protobuf::CachedSize,
@@ -274,6 +278,87 @@ impl ConnectedReply {
     pub fn take_session_id(&mut self) -> ::std::string::String {
         ::std::mem::replace(&mut self.session_id, ::std::string::String::new())
     }
+
+    // uint32 version = 3;
+
+
+    pub fn get_version(&self) -> u32 {
+        self.version
+    }
+    pub fn clear_version(&mut self) {
+        self.version = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_version(&mut self, v: u32) {
+        self.version = v;
+    }
+
+    // repeated uint32 supported_versions = 4;
+
+
+    pub fn get_supported_versions(&self) -> &[u32] {
+        &self.supported_versions
+    }
+    pub fn clear_supported_versions(&mut self) {
+        self.supported_versions.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_supported_versions(&mut self, v: ::std::vec::Vec<u32>) {
+        self.supported_versions = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_supported_versions(&mut self) -> &mut ::std::vec::Vec<u32> {
+        &mut self.supported_versions
+    }
+
+    // Take field
+    pub fn take_supported_versions(&mut self) -> ::std::vec::Vec<u32> {
+        ::std::mem::replace(&mut self.supported_versions, ::std::vec::Vec::new())
+    }
+
+    // bool resumed = 5;
+
+
+    pub fn get_resumed(&self) -> bool {
+        self.resumed
+    }
+    pub fn clear_resumed(&mut self) {
+        self.resumed = false;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_resumed(&mut self, v: bool) {
+        self.resumed = v;
+    }
+
+    // string resume_token = 6;
+
+
+    pub fn get_resume_token(&self) -> &str {
+        &self.resume_token
+    }
+    pub fn clear_resume_token(&mut self) {
+        self.resume_token.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_resume_token(&mut self, v: ::std::string::String) {
+        self.resume_token = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_resume_token(&mut self) -> &mut ::std::string::String {
+        &mut self.resume_token
+    }
+
+    // Take field
+    pub fn take_resume_token(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.resume_token, ::std::string::String::new())
+    }
 }
 
 impl ::protobuf::Message for ConnectedReply {
@@ -295,6 +380,26 @@ impl ::protobuf::Message for ConnectedReply {
                 2 => {
                     ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.session_id)?;
                 },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.version = tmp;
+                },
+                4 => {
+                    ::protobuf::rt::read_repeated_uint32_into(wire_type, is, &mut self.supported_versions)?;
+                },
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.resumed = tmp;
+                },
+                6 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.resume_token)?;
+                },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
                 },
@@ -313,6 +418,18 @@ impl ::protobuf::Message for ConnectedReply {
         if !self.session_id.is_empty() {
             my_size += ::protobuf::rt::string_size(2, &self.session_id);
         }
+        if self.version != 0 {
+            my_size += ::protobuf::rt::value_size(3, self.version, ::protobuf::wire_format::WireTypeVarint);
+        }
+        for value in &self.supported_versions {
+            my_size += ::protobuf::rt::value_size(4, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        if self.resumed != false {
+            my_size += 2;
+        }
+        if !self.resume_token.is_empty() {
+            my_size += ::protobuf::rt::string_size(6, &self.resume_token);
+        }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
@@ -325,6 +442,18 @@ impl ::protobuf::Message for ConnectedReply {
         if !self.session_id.is_empty() {
             os.write_string(2, &self.session_id)?;
         }
+        if self.version != 0 {
+            os.write_uint32(3, self.version)?;
+        }
+        for v in &self.supported_versions {
+            os.write_uint32(4, *v)?;
+        };
+        if self.resumed != false {
+            os.write_bool(5, self.resumed)?;
+        }
+        if !self.resume_token.is_empty() {
+            os.write_string(6, &self.resume_token)?;
+        }
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -373,6 +502,26 @@ impl ::protobuf::Message for ConnectedReply {
                 |m: &ConnectedReply| { &m.session_id },
                 |m: &mut ConnectedReply| { &mut m.session_id },
             ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "version",
+                |m: &ConnectedReply| { &m.version },
+                |m: &mut ConnectedReply| { &mut m.version },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_vec_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "supported_versions",
+                |m: &ConnectedReply| { &m.supported_versions },
+                |m: &mut ConnectedReply| { &mut m.supported_versions },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "resumed",
+                |m: &ConnectedReply| { &m.resumed },
+                |m: &mut ConnectedReply| { &mut m.resumed },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "resume_token",
+                |m: &ConnectedReply| { &m.resume_token },
+                |m: &mut ConnectedReply| { &mut m.resume_token },
+            ));
             ::protobuf::reflect::MessageDescriptor::new_pb_name::<ConnectedReply>(
                 "ConnectedReply",
                 fields,
@@ -391,6 +540,10 @@ impl ::protobuf::Clear for ConnectedReply {
     fn clear(&mut self) {
         self.uid = 0;
         self.session_id.clear();
+        self.version = 0;
+        self.supported_versions.clear();
+        self.resumed = false;
+        self.resume_token.clear();
         self.unknown_fields.clear();
     }
 }
@@ -741,54 +894,2686 @@ impl ::protobuf::reflect::ProtobufValue for MsgToUser {
     }
 }
 
-#[derive(Clone,PartialEq,Eq,Debug,Hash)]
-pub enum Action {
-    CONNECTED = 0,
-    HEARTBEAT = 1,
-    MSG_TO_USER = 2,
+#[derive(PartialEq,Clone,Default)]
+pub struct Login {
+    // message fields
+    pub username: ::std::string::String,
+    pub password: ::std::string::String,
+    pub token: ::std::string::String,
+    pub session_id: ::std::string::String,
+    pub device_id: ::std::string::String,
+    pub resume_token: ::std::string::String,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
 }
 
-impl ::protobuf::ProtobufEnum for Action {
-    fn value(&self) -> i32 {
-        *self as i32
+impl<'a> ::std::default::Default for &'a Login {
+    fn default() -> &'a Login {
+        <Login as ::protobuf::Message>::default_instance()
     }
+}
 
-    fn from_i32(value: i32) -> ::std::option::Option<Action> {
-        match value {
-            0 => ::std::option::Option::Some(Action::CONNECTED),
-            1 => ::std::option::Option::Some(Action::HEARTBEAT),
-            2 => ::std::option::Option::Some(Action::MSG_TO_USER),
-            _ => ::std::option::Option::None
-        }
+impl Login {
+    pub fn new() -> Login {
+        ::std::default::Default::default()
     }
 
-    fn values() -> &'static [Self] {
-        static values: &'static [Action] = &[
-            Action::CONNECTED,
-            Action::HEARTBEAT,
-            Action::MSG_TO_USER,
-        ];
-        values
+    // string username = 1;
+
+
+    pub fn get_username(&self) -> &str {
+        &self.username
+    }
+    pub fn clear_username(&mut self) {
+        self.username.clear();
     }
 
-    fn enum_descriptor_static() -> &'static ::protobuf::reflect::EnumDescriptor {
-        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::EnumDescriptor> = ::protobuf::rt::LazyV2::INIT;
-        descriptor.get(|| {
-            ::protobuf::reflect::EnumDescriptor::new_pb_name::<Action>("Action", file_descriptor_proto())
-        })
+    // Param is passed by value, moved
+    pub fn set_username(&mut self, v: ::std::string::String) {
+        self.username = v;
     }
-}
 
-impl ::std::marker::Copy for Action {
-}
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_username(&mut self) -> &mut ::std::string::String {
+        &mut self.username
+    }
 
-impl ::std::default::Default for Action {
-    fn default() -> Self {
-        Action::CONNECTED
+    // Take field
+    pub fn take_username(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.username, ::std::string::String::new())
+    }
+
+    // string password = 2;
+
+
+    pub fn get_password(&self) -> &str {
+        &self.password
+    }
+    pub fn clear_password(&mut self) {
+        self.password.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_password(&mut self, v: ::std::string::String) {
+        self.password = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_password(&mut self) -> &mut ::std::string::String {
+        &mut self.password
+    }
+
+    // Take field
+    pub fn take_password(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.password, ::std::string::String::new())
+    }
+
+    // string token = 3;
+
+
+    pub fn get_token(&self) -> &str {
+        &self.token
+    }
+    pub fn clear_token(&mut self) {
+        self.token.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_token(&mut self, v: ::std::string::String) {
+        self.token = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_token(&mut self) -> &mut ::std::string::String {
+        &mut self.token
+    }
+
+    // Take field
+    pub fn take_token(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.token, ::std::string::String::new())
+    }
+
+    // string session_id = 4;
+
+
+    pub fn get_session_id(&self) -> &str {
+        &self.session_id
+    }
+    pub fn clear_session_id(&mut self) {
+        self.session_id.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_session_id(&mut self, v: ::std::string::String) {
+        self.session_id = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_session_id(&mut self) -> &mut ::std::string::String {
+        &mut self.session_id
+    }
+
+    // Take field
+    pub fn take_session_id(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.session_id, ::std::string::String::new())
+    }
+
+    // string device_id = 5;
+
+
+    pub fn get_device_id(&self) -> &str {
+        &self.device_id
+    }
+    pub fn clear_device_id(&mut self) {
+        self.device_id.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_device_id(&mut self, v: ::std::string::String) {
+        self.device_id = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_device_id(&mut self) -> &mut ::std::string::String {
+        &mut self.device_id
+    }
+
+    // Take field
+    pub fn take_device_id(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.device_id, ::std::string::String::new())
+    }
+
+    // string resume_token = 6;
+
+
+    pub fn get_resume_token(&self) -> &str {
+        &self.resume_token
+    }
+    pub fn clear_resume_token(&mut self) {
+        self.resume_token.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_resume_token(&mut self, v: ::std::string::String) {
+        self.resume_token = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_resume_token(&mut self) -> &mut ::std::string::String {
+        &mut self.resume_token
+    }
+
+    // Take field
+    pub fn take_resume_token(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.resume_token, ::std::string::String::new())
     }
 }
 
-impl ::protobuf::reflect::ProtobufValue for Action {
+impl ::protobuf::Message for Login {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.username)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.password)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.token)?;
+                },
+                4 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.session_id)?;
+                },
+                5 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.device_id)?;
+                },
+                6 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.resume_token)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.username.is_empty() {
+            my_size += ::protobuf::rt::string_size(1, &self.username);
+        }
+        if !self.password.is_empty() {
+            my_size += ::protobuf::rt::string_size(2, &self.password);
+        }
+        if !self.token.is_empty() {
+            my_size += ::protobuf::rt::string_size(3, &self.token);
+        }
+        if !self.session_id.is_empty() {
+            my_size += ::protobuf::rt::string_size(4, &self.session_id);
+        }
+        if !self.device_id.is_empty() {
+            my_size += ::protobuf::rt::string_size(5, &self.device_id);
+        }
+        if !self.resume_token.is_empty() {
+            my_size += ::protobuf::rt::string_size(6, &self.resume_token);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if !self.username.is_empty() {
+            os.write_string(1, &self.username)?;
+        }
+        if !self.password.is_empty() {
+            os.write_string(2, &self.password)?;
+        }
+        if !self.token.is_empty() {
+            os.write_string(3, &self.token)?;
+        }
+        if !self.session_id.is_empty() {
+            os.write_string(4, &self.session_id)?;
+        }
+        if !self.device_id.is_empty() {
+            os.write_string(5, &self.device_id)?;
+        }
+        if !self.resume_token.is_empty() {
+            os.write_string(6, &self.resume_token)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Login {
+        Login::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "username",
+                |m: &Login| { &m.username },
+                |m: &mut Login| { &mut m.username },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "password",
+                |m: &Login| { &m.password },
+                |m: &mut Login| { &mut m.password },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "token",
+                |m: &Login| { &m.token },
+                |m: &mut Login| { &mut m.token },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "session_id",
+                |m: &Login| { &m.session_id },
+                |m: &mut Login| { &mut m.session_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "device_id",
+                |m: &Login| { &m.device_id },
+                |m: &mut Login| { &mut m.device_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "resume_token",
+                |m: &Login| { &m.resume_token },
+                |m: &mut Login| { &mut m.resume_token },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Login>(
+                "Login",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Login {
+        static instance: ::protobuf::rt::LazyV2<Login> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Login::new)
+    }
+}
+
+impl ::protobuf::Clear for Login {
+    fn clear(&mut self) {
+        self.username.clear();
+        self.password.clear();
+        self.token.clear();
+        self.session_id.clear();
+        self.device_id.clear();
+        self.resume_token.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Login {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Login {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct LoginFailedReply {
+    // message fields
+    pub reason: ::std::string::String,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a LoginFailedReply {
+    fn default() -> &'a LoginFailedReply {
+        <LoginFailedReply as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl LoginFailedReply {
+    pub fn new() -> LoginFailedReply {
+        ::std::default::Default::default()
+    }
+
+    // string reason = 1;
+
+
+    pub fn get_reason(&self) -> &str {
+        &self.reason
+    }
+    pub fn clear_reason(&mut self) {
+        self.reason.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_reason(&mut self, v: ::std::string::String) {
+        self.reason = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_reason(&mut self) -> &mut ::std::string::String {
+        &mut self.reason
+    }
+
+    // Take field
+    pub fn take_reason(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.reason, ::std::string::String::new())
+    }
+}
+
+impl ::protobuf::Message for LoginFailedReply {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.reason)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.reason.is_empty() {
+            my_size += ::protobuf::rt::string_size(1, &self.reason);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if !self.reason.is_empty() {
+            os.write_string(1, &self.reason)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> LoginFailedReply {
+        LoginFailedReply::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "reason",
+                |m: &LoginFailedReply| { &m.reason },
+                |m: &mut LoginFailedReply| { &mut m.reason },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<LoginFailedReply>(
+                "LoginFailedReply",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static LoginFailedReply {
+        static instance: ::protobuf::rt::LazyV2<LoginFailedReply> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(LoginFailedReply::new)
+    }
+}
+
+impl ::protobuf::Clear for LoginFailedReply {
+    fn clear(&mut self) {
+        self.reason.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for LoginFailedReply {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for LoginFailedReply {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct MsgAck {
+    // message fields
+    pub seq: u64,
+    pub message_id: u64,
+    pub timestamp: u64,
+    pub room_id: u64,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a MsgAck {
+    fn default() -> &'a MsgAck {
+        <MsgAck as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl MsgAck {
+    pub fn new() -> MsgAck {
+        ::std::default::Default::default()
+    }
+
+    // uint64 seq = 1;
+
+
+    pub fn get_seq(&self) -> u64 {
+        self.seq
+    }
+    pub fn clear_seq(&mut self) {
+        self.seq = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_seq(&mut self, v: u64) {
+        self.seq = v;
+    }
+
+    // uint64 message_id = 2;
+
+
+    pub fn get_message_id(&self) -> u64 {
+        self.message_id
+    }
+    pub fn clear_message_id(&mut self) {
+        self.message_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_message_id(&mut self, v: u64) {
+        self.message_id = v;
+    }
+
+    // uint64 timestamp = 3;
+
+
+    pub fn get_timestamp(&self) -> u64 {
+        self.timestamp
+    }
+    pub fn clear_timestamp(&mut self) {
+        self.timestamp = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_timestamp(&mut self, v: u64) {
+        self.timestamp = v;
+    }
+
+    // uint64 room_id = 4;
+
+
+    pub fn get_room_id(&self) -> u64 {
+        self.room_id
+    }
+    pub fn clear_room_id(&mut self) {
+        self.room_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_room_id(&mut self, v: u64) {
+        self.room_id = v;
+    }
+}
+
+impl ::protobuf::Message for MsgAck {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.seq = tmp;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.message_id = tmp;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.timestamp = tmp;
+                },
+                4 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.room_id = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.seq != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.seq, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.message_id != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.message_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.timestamp != 0 {
+            my_size += ::protobuf::rt::value_size(3, self.timestamp, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.room_id != 0 {
+            my_size += ::protobuf::rt::value_size(4, self.room_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.seq != 0 {
+            os.write_uint64(1, self.seq)?;
+        }
+        if self.message_id != 0 {
+            os.write_uint64(2, self.message_id)?;
+        }
+        if self.timestamp != 0 {
+            os.write_uint64(3, self.timestamp)?;
+        }
+        if self.room_id != 0 {
+            os.write_uint64(4, self.room_id)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> MsgAck {
+        MsgAck::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "seq",
+                |m: &MsgAck| { &m.seq },
+                |m: &mut MsgAck| { &mut m.seq },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "message_id",
+                |m: &MsgAck| { &m.message_id },
+                |m: &mut MsgAck| { &mut m.message_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "timestamp",
+                |m: &MsgAck| { &m.timestamp },
+                |m: &mut MsgAck| { &mut m.timestamp },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "room_id",
+                |m: &MsgAck| { &m.room_id },
+                |m: &mut MsgAck| { &mut m.room_id },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<MsgAck>(
+                "MsgAck",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static MsgAck {
+        static instance: ::protobuf::rt::LazyV2<MsgAck> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(MsgAck::new)
+    }
+}
+
+impl ::protobuf::Clear for MsgAck {
+    fn clear(&mut self) {
+        self.seq = 0;
+        self.message_id = 0;
+        self.timestamp = 0;
+        self.room_id = 0;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for MsgAck {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for MsgAck {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct Receipt {
+    // message fields
+    pub message_id: u64,
+    pub sender_uid: u64,
+    pub receiver_uid: u64,
+    pub timestamp: u64,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a Receipt {
+    fn default() -> &'a Receipt {
+        <Receipt as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl Receipt {
+    pub fn new() -> Receipt {
+        ::std::default::Default::default()
+    }
+
+    // uint64 message_id = 1;
+
+
+    pub fn get_message_id(&self) -> u64 {
+        self.message_id
+    }
+    pub fn clear_message_id(&mut self) {
+        self.message_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_message_id(&mut self, v: u64) {
+        self.message_id = v;
+    }
+
+    // uint64 sender_uid = 2;
+
+
+    pub fn get_sender_uid(&self) -> u64 {
+        self.sender_uid
+    }
+    pub fn clear_sender_uid(&mut self) {
+        self.sender_uid = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_sender_uid(&mut self, v: u64) {
+        self.sender_uid = v;
+    }
+
+    // uint64 receiver_uid = 3;
+
+
+    pub fn get_receiver_uid(&self) -> u64 {
+        self.receiver_uid
+    }
+    pub fn clear_receiver_uid(&mut self) {
+        self.receiver_uid = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_receiver_uid(&mut self, v: u64) {
+        self.receiver_uid = v;
+    }
+
+    // uint64 timestamp = 4;
+
+
+    pub fn get_timestamp(&self) -> u64 {
+        self.timestamp
+    }
+    pub fn clear_timestamp(&mut self) {
+        self.timestamp = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_timestamp(&mut self, v: u64) {
+        self.timestamp = v;
+    }
+}
+
+impl ::protobuf::Message for Receipt {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.message_id = tmp;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.sender_uid = tmp;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.receiver_uid = tmp;
+                },
+                4 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.timestamp = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.message_id != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.message_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.sender_uid != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.sender_uid, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.receiver_uid != 0 {
+            my_size += ::protobuf::rt::value_size(3, self.receiver_uid, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.timestamp != 0 {
+            my_size += ::protobuf::rt::value_size(4, self.timestamp, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.message_id != 0 {
+            os.write_uint64(1, self.message_id)?;
+        }
+        if self.sender_uid != 0 {
+            os.write_uint64(2, self.sender_uid)?;
+        }
+        if self.receiver_uid != 0 {
+            os.write_uint64(3, self.receiver_uid)?;
+        }
+        if self.timestamp != 0 {
+            os.write_uint64(4, self.timestamp)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Receipt {
+        Receipt::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "message_id",
+                |m: &Receipt| { &m.message_id },
+                |m: &mut Receipt| { &mut m.message_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "sender_uid",
+                |m: &Receipt| { &m.sender_uid },
+                |m: &mut Receipt| { &mut m.sender_uid },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "receiver_uid",
+                |m: &Receipt| { &m.receiver_uid },
+                |m: &mut Receipt| { &mut m.receiver_uid },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "timestamp",
+                |m: &Receipt| { &m.timestamp },
+                |m: &mut Receipt| { &mut m.timestamp },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Receipt>(
+                "Receipt",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Receipt {
+        static instance: ::protobuf::rt::LazyV2<Receipt> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Receipt::new)
+    }
+}
+
+impl ::protobuf::Clear for Receipt {
+    fn clear(&mut self) {
+        self.message_id = 0;
+        self.sender_uid = 0;
+        self.receiver_uid = 0;
+        self.timestamp = 0;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Receipt {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Receipt {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct RoomRequest {
+    // message fields
+    pub room_id: u64,
+    pub name: ::std::string::String,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a RoomRequest {
+    fn default() -> &'a RoomRequest {
+        <RoomRequest as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl RoomRequest {
+    pub fn new() -> RoomRequest {
+        ::std::default::Default::default()
+    }
+
+    // uint64 room_id = 1;
+
+
+    pub fn get_room_id(&self) -> u64 {
+        self.room_id
+    }
+    pub fn clear_room_id(&mut self) {
+        self.room_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_room_id(&mut self, v: u64) {
+        self.room_id = v;
+    }
+
+    // string name = 2;
+
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    pub fn clear_name(&mut self) {
+        self.name.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_name(&mut self, v: ::std::string::String) {
+        self.name = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_name(&mut self) -> &mut ::std::string::String {
+        &mut self.name
+    }
+
+    // Take field
+    pub fn take_name(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.name, ::std::string::String::new())
+    }
+}
+
+impl ::protobuf::Message for RoomRequest {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.room_id = tmp;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.name)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.room_id != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.room_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if !self.name.is_empty() {
+            my_size += ::protobuf::rt::string_size(2, &self.name);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.room_id != 0 {
+            os.write_uint64(1, self.room_id)?;
+        }
+        if !self.name.is_empty() {
+            os.write_string(2, &self.name)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> RoomRequest {
+        RoomRequest::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "room_id",
+                |m: &RoomRequest| { &m.room_id },
+                |m: &mut RoomRequest| { &mut m.room_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "name",
+                |m: &RoomRequest| { &m.name },
+                |m: &mut RoomRequest| { &mut m.name },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<RoomRequest>(
+                "RoomRequest",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static RoomRequest {
+        static instance: ::protobuf::rt::LazyV2<RoomRequest> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(RoomRequest::new)
+    }
+}
+
+impl ::protobuf::Clear for RoomRequest {
+    fn clear(&mut self) {
+        self.room_id = 0;
+        self.name.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for RoomRequest {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for RoomRequest {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct RoomReply {
+    // message fields
+    pub action: Action,
+    pub room_id: u64,
+    pub success: bool,
+    pub reason: ::std::string::String,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a RoomReply {
+    fn default() -> &'a RoomReply {
+        <RoomReply as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl RoomReply {
+    pub fn new() -> RoomReply {
+        ::std::default::Default::default()
+    }
+
+    // .Action action = 1;
+
+
+    pub fn get_action(&self) -> Action {
+        self.action
+    }
+    pub fn clear_action(&mut self) {
+        self.action = Action::CONNECTED;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_action(&mut self, v: Action) {
+        self.action = v;
+    }
+
+    // uint64 room_id = 2;
+
+
+    pub fn get_room_id(&self) -> u64 {
+        self.room_id
+    }
+    pub fn clear_room_id(&mut self) {
+        self.room_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_room_id(&mut self, v: u64) {
+        self.room_id = v;
+    }
+
+    // bool success = 3;
+
+
+    pub fn get_success(&self) -> bool {
+        self.success
+    }
+    pub fn clear_success(&mut self) {
+        self.success = false;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_success(&mut self, v: bool) {
+        self.success = v;
+    }
+
+    // string reason = 4;
+
+
+    pub fn get_reason(&self) -> &str {
+        &self.reason
+    }
+    pub fn clear_reason(&mut self) {
+        self.reason.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_reason(&mut self, v: ::std::string::String) {
+        self.reason = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_reason(&mut self) -> &mut ::std::string::String {
+        &mut self.reason
+    }
+
+    // Take field
+    pub fn take_reason(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.reason, ::std::string::String::new())
+    }
+}
+
+impl ::protobuf::Message for RoomReply {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_proto3_enum_with_unknown_fields_into(wire_type, is, &mut self.action, 1, &mut self.unknown_fields)?
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.room_id = tmp;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.success = tmp;
+                },
+                4 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.reason)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.action != Action::CONNECTED {
+            my_size += ::protobuf::rt::enum_size(1, self.action);
+        }
+        if self.room_id != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.room_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.success != false {
+            my_size += 2;
+        }
+        if !self.reason.is_empty() {
+            my_size += ::protobuf::rt::string_size(4, &self.reason);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.action != Action::CONNECTED {
+            os.write_enum(1, ::protobuf::ProtobufEnum::value(&self.action))?;
+        }
+        if self.room_id != 0 {
+            os.write_uint64(2, self.room_id)?;
+        }
+        if self.success != false {
+            os.write_bool(3, self.success)?;
+        }
+        if !self.reason.is_empty() {
+            os.write_string(4, &self.reason)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> RoomReply {
+        RoomReply::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeEnum<Action>>(
+                "action",
+                |m: &RoomReply| { &m.action },
+                |m: &mut RoomReply| { &mut m.action },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "room_id",
+                |m: &RoomReply| { &m.room_id },
+                |m: &mut RoomReply| { &mut m.room_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "success",
+                |m: &RoomReply| { &m.success },
+                |m: &mut RoomReply| { &mut m.success },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "reason",
+                |m: &RoomReply| { &m.reason },
+                |m: &mut RoomReply| { &mut m.reason },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<RoomReply>(
+                "RoomReply",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static RoomReply {
+        static instance: ::protobuf::rt::LazyV2<RoomReply> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(RoomReply::new)
+    }
+}
+
+impl ::protobuf::Clear for RoomReply {
+    fn clear(&mut self) {
+        self.action = Action::CONNECTED;
+        self.room_id = 0;
+        self.success = false;
+        self.reason.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for RoomReply {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for RoomReply {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct MsgToRoom {
+    // message fields
+    pub seq: u64,
+    pub sender_uid: u64,
+    pub room_id: u64,
+    pub message_id: u64,
+    pub content: ::std::string::String,
+    pub timestamp: u64,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a MsgToRoom {
+    fn default() -> &'a MsgToRoom {
+        <MsgToRoom as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl MsgToRoom {
+    pub fn new() -> MsgToRoom {
+        ::std::default::Default::default()
+    }
+
+    // uint64 seq = 1;
+
+
+    pub fn get_seq(&self) -> u64 {
+        self.seq
+    }
+    pub fn clear_seq(&mut self) {
+        self.seq = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_seq(&mut self, v: u64) {
+        self.seq = v;
+    }
+
+    // uint64 sender_uid = 2;
+
+
+    pub fn get_sender_uid(&self) -> u64 {
+        self.sender_uid
+    }
+    pub fn clear_sender_uid(&mut self) {
+        self.sender_uid = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_sender_uid(&mut self, v: u64) {
+        self.sender_uid = v;
+    }
+
+    // uint64 room_id = 3;
+
+
+    pub fn get_room_id(&self) -> u64 {
+        self.room_id
+    }
+    pub fn clear_room_id(&mut self) {
+        self.room_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_room_id(&mut self, v: u64) {
+        self.room_id = v;
+    }
+
+    // uint64 message_id = 4;
+
+
+    pub fn get_message_id(&self) -> u64 {
+        self.message_id
+    }
+    pub fn clear_message_id(&mut self) {
+        self.message_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_message_id(&mut self, v: u64) {
+        self.message_id = v;
+    }
+
+    // string content = 5;
+
+
+    pub fn get_content(&self) -> &str {
+        &self.content
+    }
+    pub fn clear_content(&mut self) {
+        self.content.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_content(&mut self, v: ::std::string::String) {
+        self.content = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_content(&mut self) -> &mut ::std::string::String {
+        &mut self.content
+    }
+
+    // Take field
+    pub fn take_content(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.content, ::std::string::String::new())
+    }
+
+    // uint64 timestamp = 6;
+
+
+    pub fn get_timestamp(&self) -> u64 {
+        self.timestamp
+    }
+    pub fn clear_timestamp(&mut self) {
+        self.timestamp = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_timestamp(&mut self, v: u64) {
+        self.timestamp = v;
+    }
+}
+
+impl ::protobuf::Message for MsgToRoom {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.seq = tmp;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.sender_uid = tmp;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.room_id = tmp;
+                },
+                4 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.message_id = tmp;
+                },
+                5 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.content)?;
+                },
+                6 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.timestamp = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.seq != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.seq, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.sender_uid != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.sender_uid, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.room_id != 0 {
+            my_size += ::protobuf::rt::value_size(3, self.room_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.message_id != 0 {
+            my_size += ::protobuf::rt::value_size(4, self.message_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if !self.content.is_empty() {
+            my_size += ::protobuf::rt::string_size(5, &self.content);
+        }
+        if self.timestamp != 0 {
+            my_size += ::protobuf::rt::value_size(6, self.timestamp, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.seq != 0 {
+            os.write_uint64(1, self.seq)?;
+        }
+        if self.sender_uid != 0 {
+            os.write_uint64(2, self.sender_uid)?;
+        }
+        if self.room_id != 0 {
+            os.write_uint64(3, self.room_id)?;
+        }
+        if self.message_id != 0 {
+            os.write_uint64(4, self.message_id)?;
+        }
+        if !self.content.is_empty() {
+            os.write_string(5, &self.content)?;
+        }
+        if self.timestamp != 0 {
+            os.write_uint64(6, self.timestamp)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> MsgToRoom {
+        MsgToRoom::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "seq",
+                |m: &MsgToRoom| { &m.seq },
+                |m: &mut MsgToRoom| { &mut m.seq },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "sender_uid",
+                |m: &MsgToRoom| { &m.sender_uid },
+                |m: &mut MsgToRoom| { &mut m.sender_uid },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "room_id",
+                |m: &MsgToRoom| { &m.room_id },
+                |m: &mut MsgToRoom| { &mut m.room_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "message_id",
+                |m: &MsgToRoom| { &m.message_id },
+                |m: &mut MsgToRoom| { &mut m.message_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "content",
+                |m: &MsgToRoom| { &m.content },
+                |m: &mut MsgToRoom| { &mut m.content },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "timestamp",
+                |m: &MsgToRoom| { &m.timestamp },
+                |m: &mut MsgToRoom| { &mut m.timestamp },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<MsgToRoom>(
+                "MsgToRoom",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static MsgToRoom {
+        static instance: ::protobuf::rt::LazyV2<MsgToRoom> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(MsgToRoom::new)
+    }
+}
+
+impl ::protobuf::Clear for MsgToRoom {
+    fn clear(&mut self) {
+        self.seq = 0;
+        self.sender_uid = 0;
+        self.room_id = 0;
+        self.message_id = 0;
+        self.content.clear();
+        self.timestamp = 0;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for MsgToRoom {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for MsgToRoom {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct ServerClosing {
+    // message fields
+    pub reason: ::std::string::String,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a ServerClosing {
+    fn default() -> &'a ServerClosing {
+        <ServerClosing as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl ServerClosing {
+    pub fn new() -> ServerClosing {
+        ::std::default::Default::default()
+    }
+
+    // string reason = 1;
+
+
+    pub fn get_reason(&self) -> &str {
+        &self.reason
+    }
+    pub fn clear_reason(&mut self) {
+        self.reason.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_reason(&mut self, v: ::std::string::String) {
+        self.reason = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_reason(&mut self) -> &mut ::std::string::String {
+        &mut self.reason
+    }
+
+    // Take field
+    pub fn take_reason(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.reason, ::std::string::String::new())
+    }
+}
+
+impl ::protobuf::Message for ServerClosing {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.reason)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.reason.is_empty() {
+            my_size += ::protobuf::rt::string_size(1, &self.reason);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if !self.reason.is_empty() {
+            os.write_string(1, &self.reason)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> ServerClosing {
+        ServerClosing::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "reason",
+                |m: &ServerClosing| { &m.reason },
+                |m: &mut ServerClosing| { &mut m.reason },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<ServerClosing>(
+                "ServerClosing",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static ServerClosing {
+        static instance: ::protobuf::rt::LazyV2<ServerClosing> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(ServerClosing::new)
+    }
+}
+
+impl ::protobuf::Clear for ServerClosing {
+    fn clear(&mut self) {
+        self.reason.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for ServerClosing {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ServerClosing {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct Presence {
+    // message fields
+    pub uid: u64,
+    pub status: PresenceStatus,
+    pub timestamp: u64,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a Presence {
+    fn default() -> &'a Presence {
+        <Presence as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl Presence {
+    pub fn new() -> Presence {
+        ::std::default::Default::default()
+    }
+
+    // uint64 uid = 1;
+
+
+    pub fn get_uid(&self) -> u64 {
+        self.uid
+    }
+    pub fn clear_uid(&mut self) {
+        self.uid = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_uid(&mut self, v: u64) {
+        self.uid = v;
+    }
+
+    // .PresenceStatus status = 2;
+
+
+    pub fn get_status(&self) -> PresenceStatus {
+        self.status
+    }
+    pub fn clear_status(&mut self) {
+        self.status = PresenceStatus::OFFLINE;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_status(&mut self, v: PresenceStatus) {
+        self.status = v;
+    }
+
+    // uint64 timestamp = 3;
+
+
+    pub fn get_timestamp(&self) -> u64 {
+        self.timestamp
+    }
+    pub fn clear_timestamp(&mut self) {
+        self.timestamp = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_timestamp(&mut self, v: u64) {
+        self.timestamp = v;
+    }
+}
+
+impl ::protobuf::Message for Presence {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.uid = tmp;
+                },
+                2 => {
+                    ::protobuf::rt::read_proto3_enum_with_unknown_fields_into(wire_type, is, &mut self.status, 2, &mut self.unknown_fields)?
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.timestamp = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.uid != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.uid, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.status != PresenceStatus::OFFLINE {
+            my_size += ::protobuf::rt::enum_size(2, self.status);
+        }
+        if self.timestamp != 0 {
+            my_size += ::protobuf::rt::value_size(3, self.timestamp, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.uid != 0 {
+            os.write_uint64(1, self.uid)?;
+        }
+        if self.status != PresenceStatus::OFFLINE {
+            os.write_enum(2, ::protobuf::ProtobufEnum::value(&self.status))?;
+        }
+        if self.timestamp != 0 {
+            os.write_uint64(3, self.timestamp)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Presence {
+        Presence::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "uid",
+                |m: &Presence| { &m.uid },
+                |m: &mut Presence| { &mut m.uid },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeEnum<PresenceStatus>>(
+                "status",
+                |m: &Presence| { &m.status },
+                |m: &mut Presence| { &mut m.status },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "timestamp",
+                |m: &Presence| { &m.timestamp },
+                |m: &mut Presence| { &mut m.timestamp },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Presence>(
+                "Presence",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Presence {
+        static instance: ::protobuf::rt::LazyV2<Presence> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Presence::new)
+    }
+}
+
+impl ::protobuf::Clear for Presence {
+    fn clear(&mut self) {
+        self.uid = 0;
+        self.status = PresenceStatus::OFFLINE;
+        self.timestamp = 0;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Presence {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Presence {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct PresenceRequest {
+    // message fields
+    pub uids: ::std::vec::Vec<u64>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a PresenceRequest {
+    fn default() -> &'a PresenceRequest {
+        <PresenceRequest as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl PresenceRequest {
+    pub fn new() -> PresenceRequest {
+        ::std::default::Default::default()
+    }
+
+    // repeated uint64 uids = 1;
+
+
+    pub fn get_uids(&self) -> &[u64] {
+        &self.uids
+    }
+    pub fn clear_uids(&mut self) {
+        self.uids.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_uids(&mut self, v: ::std::vec::Vec<u64>) {
+        self.uids = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_uids(&mut self) -> &mut ::std::vec::Vec<u64> {
+        &mut self.uids
+    }
+
+    // Take field
+    pub fn take_uids(&mut self) -> ::std::vec::Vec<u64> {
+        ::std::mem::replace(&mut self.uids, ::std::vec::Vec::new())
+    }
+}
+
+impl ::protobuf::Message for PresenceRequest {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_repeated_uint64_into(wire_type, is, &mut self.uids)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        for value in &self.uids {
+            my_size += ::protobuf::rt::value_size(1, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        for v in &self.uids {
+            os.write_uint64(1, *v)?;
+        };
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> PresenceRequest {
+        PresenceRequest::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_vec_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "uids",
+                |m: &PresenceRequest| { &m.uids },
+                |m: &mut PresenceRequest| { &mut m.uids },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<PresenceRequest>(
+                "PresenceRequest",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static PresenceRequest {
+        static instance: ::protobuf::rt::LazyV2<PresenceRequest> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(PresenceRequest::new)
+    }
+}
+
+impl ::protobuf::Clear for PresenceRequest {
+    fn clear(&mut self) {
+        self.uids.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for PresenceRequest {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for PresenceRequest {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct PresenceList {
+    // message fields
+    pub presences: ::protobuf::RepeatedField<Presence>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a PresenceList {
+    fn default() -> &'a PresenceList {
+        <PresenceList as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl PresenceList {
+    pub fn new() -> PresenceList {
+        ::std::default::Default::default()
+    }
+
+    // repeated .Presence presences = 1;
+
+
+    pub fn get_presences(&self) -> &[Presence] {
+        &self.presences
+    }
+    pub fn clear_presences(&mut self) {
+        self.presences.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_presences(&mut self, v: ::protobuf::RepeatedField<Presence>) {
+        self.presences = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_presences(&mut self) -> &mut ::protobuf::RepeatedField<Presence> {
+        &mut self.presences
+    }
+
+    // Take field
+    pub fn take_presences(&mut self) -> ::protobuf::RepeatedField<Presence> {
+        ::std::mem::replace(&mut self.presences, ::protobuf::RepeatedField::new())
+    }
+}
+
+impl ::protobuf::Message for PresenceList {
+    fn is_initialized(&self) -> bool {
+        for v in &self.presences {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.presences)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        for value in &self.presences {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        for v in &self.presences {
+            os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> PresenceList {
+        PresenceList::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<Presence>>(
+                "presences",
+                |m: &PresenceList| { &m.presences },
+                |m: &mut PresenceList| { &mut m.presences },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<PresenceList>(
+                "PresenceList",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static PresenceList {
+        static instance: ::protobuf::rt::LazyV2<PresenceList> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(PresenceList::new)
+    }
+}
+
+impl ::protobuf::Clear for PresenceList {
+    fn clear(&mut self) {
+        self.presences.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for PresenceList {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for PresenceList {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(Clone,PartialEq,Eq,Debug,Hash)]
+pub enum Action {
+    CONNECTED = 0,
+    HEARTBEAT = 1,
+    MSG_TO_USER = 2,
+    LOGIN = 3,
+    LOGIN_FAILED = 4,
+    MSG_ACK = 5,
+    DELIVERED_ACK = 6,
+    READ_RECEIPT = 7,
+    CREATE_ROOM = 8,
+    JOIN_ROOM = 9,
+    LEAVE_ROOM = 10,
+    MSG_TO_ROOM = 11,
+    ROOM_REPLY = 12,
+    SERVER_CLOSING = 13,
+    PRESENCE_QUERY = 14,
+    PRESENCE_SUBSCRIBE = 15,
+    PRESENCE_UNSUBSCRIBE = 16,
+    PRESENCE_LIST = 17,
+    PRESENCE = 18,
+}
+
+impl ::protobuf::ProtobufEnum for Action {
+    fn value(&self) -> i32 {
+        *self as i32
+    }
+
+    fn from_i32(value: i32) -> ::std::option::Option<Action> {
+        match value {
+            0 => ::std::option::Option::Some(Action::CONNECTED),
+            1 => ::std::option::Option::Some(Action::HEARTBEAT),
+            2 => ::std::option::Option::Some(Action::MSG_TO_USER),
+            3 => ::std::option::Option::Some(Action::LOGIN),
+            4 => ::std::option::Option::Some(Action::LOGIN_FAILED),
+            5 => ::std::option::Option::Some(Action::MSG_ACK),
+            6 => ::std::option::Option::Some(Action::DELIVERED_ACK),
+            7 => ::std::option::Option::Some(Action::READ_RECEIPT),
+            8 => ::std::option::Option::Some(Action::CREATE_ROOM),
+            9 => ::std::option::Option::Some(Action::JOIN_ROOM),
+            10 => ::std::option::Option::Some(Action::LEAVE_ROOM),
+            11 => ::std::option::Option::Some(Action::MSG_TO_ROOM),
+            12 => ::std::option::Option::Some(Action::ROOM_REPLY),
+            13 => ::std::option::Option::Some(Action::SERVER_CLOSING),
+            14 => ::std::option::Option::Some(Action::PRESENCE_QUERY),
+            15 => ::std::option::Option::Some(Action::PRESENCE_SUBSCRIBE),
+            16 => ::std::option::Option::Some(Action::PRESENCE_UNSUBSCRIBE),
+            17 => ::std::option::Option::Some(Action::PRESENCE_LIST),
+            18 => ::std::option::Option::Some(Action::PRESENCE),
+            _ => ::std::option::Option::None
+        }
+    }
+
+    fn values() -> &'static [Self] {
+        static values: &'static [Action] = &[
+            Action::CONNECTED,
+            Action::HEARTBEAT,
+            Action::MSG_TO_USER,
+            Action::LOGIN,
+            Action::LOGIN_FAILED,
+            Action::MSG_ACK,
+            Action::DELIVERED_ACK,
+            Action::READ_RECEIPT,
+            Action::CREATE_ROOM,
+            Action::JOIN_ROOM,
+            Action::LEAVE_ROOM,
+            Action::MSG_TO_ROOM,
+            Action::ROOM_REPLY,
+            Action::SERVER_CLOSING,
+            Action::PRESENCE_QUERY,
+            Action::PRESENCE_SUBSCRIBE,
+            Action::PRESENCE_UNSUBSCRIBE,
+            Action::PRESENCE_LIST,
+            Action::PRESENCE,
+        ];
+        values
+    }
+
+    fn enum_descriptor_static() -> &'static ::protobuf::reflect::EnumDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::EnumDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            ::protobuf::reflect::EnumDescriptor::new_pb_name::<Action>("Action", file_descriptor_proto())
+        })
+    }
+}
+
+impl ::std::marker::Copy for Action {
+}
+
+impl ::std::default::Default for Action {
+    fn default() -> Self {
+        Action::CONNECTED
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Action {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Enum(::protobuf::ProtobufEnum::descriptor(self))
+    }
+}
+
+#[derive(Clone,PartialEq,Eq,Debug,Hash)]
+pub enum PresenceStatus {
+    OFFLINE = 0,
+    ONLINE = 1,
+    AWAY = 2,
+}
+
+impl ::protobuf::ProtobufEnum for PresenceStatus {
+    fn value(&self) -> i32 {
+        *self as i32
+    }
+
+    fn from_i32(value: i32) -> ::std::option::Option<PresenceStatus> {
+        match value {
+            0 => ::std::option::Option::Some(PresenceStatus::OFFLINE),
+            1 => ::std::option::Option::Some(PresenceStatus::ONLINE),
+            2 => ::std::option::Option::Some(PresenceStatus::AWAY),
+            _ => ::std::option::Option::None
+        }
+    }
+
+    fn values() -> &'static [Self] {
+        static values: &'static [PresenceStatus] = &[
+            PresenceStatus::OFFLINE,
+            PresenceStatus::ONLINE,
+            PresenceStatus::AWAY,
+        ];
+        values
+    }
+
+    fn enum_descriptor_static() -> &'static ::protobuf::reflect::EnumDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::EnumDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            ::protobuf::reflect::EnumDescriptor::new_pb_name::<PresenceStatus>("PresenceStatus", file_descriptor_proto())
+        })
+    }
+}
+
+impl ::std::marker::Copy for PresenceStatus {
+}
+
+impl ::std::default::Default for PresenceStatus {
+    fn default() -> Self {
+        PresenceStatus::OFFLINE
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for PresenceStatus {
     fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
         ::protobuf::reflect::ReflectValueRef::Enum(::protobuf::ProtobufEnum::descriptor(self))
     }
@@ -797,62 +3582,339 @@ impl ::protobuf::reflect::ProtobufValue for Action {
 static file_descriptor_proto_data: &'static [u8] = b"\
     \n\x0fchat_room.proto\"D\n\x07Package\x12\x1f\n\x06action\x18\x01\x20\
     \x01(\x0e2\x07.ActionR\x06action\x12\x18\n\x07content\x18\x02\x20\x01(\
-    \x0cR\x07content\"A\n\x0eConnectedReply\x12\x10\n\x03uid\x18\x01\x20\x01\
-    (\x04R\x03uid\x12\x1d\n\nsession_id\x18\x02\x20\x01(\tR\tsessionId\"\xb6\
-    \x01\n\tMsgToUser\x12\x10\n\x03seq\x18\x01\x20\x01(\x04R\x03seq\x12\x1d\
-    \n\nsender_uid\x18\x02\x20\x01(\x04R\tsenderUid\x12!\n\x0creceiver_uid\
-    \x18\x03\x20\x01(\x04R\x0breceiverUid\x12\x1d\n\nmessage_id\x18\x04\x20\
-    \x01(\x04R\tmessageId\x12\x18\n\x07content\x18\x05\x20\x01(\tR\x07conten\
-    t\x12\x1c\n\ttimestamp\x18\x06\x20\x01(\x04R\ttimestamp*7\n\x06Action\
-    \x12\r\n\tCONNECTED\x10\0\x12\r\n\tHEARTBEAT\x10\x01\x12\x0f\n\x0bMSG_TO\
-    _USER\x10\x02J\xb7\x07\n\x06\x12\x04\0\0\x19\x01\n\x08\n\x01\x0c\x12\x03\
-    \0\0\x12\n\n\n\x02\x05\0\x12\x04\x02\0\x06\x01\n\n\n\x03\x05\0\x01\x12\
-    \x03\x02\x05\x0b\n\x1b\n\x04\x05\0\x02\0\x12\x03\x03\x02\x12\"\x0e\x20\
-    \xe8\xbf\x9e\xe6\x8e\xa5\xe6\x88\x90\xe5\x8a\x9f\n\n\x0c\n\x05\x05\0\x02\
-    \0\x01\x12\x03\x03\x02\x0b\n\x0c\n\x05\x05\0\x02\0\x02\x12\x03\x03\x10\
-    \x11\n\x1b\n\x04\x05\0\x02\x01\x12\x03\x04\x02\x12\"\x0e\x20\xe5\xbf\x83\
-    \xe8\xb7\xb3\xe6\xa3\x80\xe6\xb5\x8b\n\n\x0c\n\x05\x05\0\x02\x01\x01\x12\
-    \x03\x04\x02\x0b\n\x0c\n\x05\x05\0\x02\x01\x02\x12\x03\x04\x10\x11\n!\n\
-    \x04\x05\0\x02\x02\x12\x03\x05\x02\x12\"\x14\x20\xe5\x8f\x91\xe6\xb6\x88\
-    \xe6\x81\xaf\xe5\x88\xb0\xe7\x94\xa8\xe6\x88\xb7\n\n\x0c\n\x05\x05\0\x02\
-    \x02\x01\x12\x03\x05\x02\r\n\x0c\n\x05\x05\0\x02\x02\x02\x12\x03\x05\x10\
-    \x11\n\n\n\x02\x04\0\x12\x04\x08\0\x0b\x01\n\n\n\x03\x04\0\x01\x12\x03\
-    \x08\x08\x0f\n\x0b\n\x04\x04\0\x02\0\x12\x03\t\x02\x15\n\x0c\n\x05\x04\0\
-    \x02\0\x06\x12\x03\t\x02\x08\n\x0c\n\x05\x04\0\x02\0\x01\x12\x03\t\t\x0f\
-    \n\x0c\n\x05\x04\0\x02\0\x03\x12\x03\t\x13\x14\n\x0b\n\x04\x04\0\x02\x01\
-    \x12\x03\n\x02\x15\n\x0c\n\x05\x04\0\x02\x01\x05\x12\x03\n\x02\x07\n\x0c\
-    \n\x05\x04\0\x02\x01\x01\x12\x03\n\t\x10\n\x0c\n\x05\x04\0\x02\x01\x03\
-    \x12\x03\n\x13\x14\n\n\n\x02\x04\x01\x12\x04\r\0\x10\x01\n\n\n\x03\x04\
-    \x01\x01\x12\x03\r\x08\x16\n\x17\n\x04\x04\x01\x02\0\x12\x03\x0e\x02\x18\
-    \"\n\x20\xe7\x94\xa8\xe6\x88\xb7ID\n\n\x0c\n\x05\x04\x01\x02\0\x05\x12\
-    \x03\x0e\x02\x08\n\x0c\n\x05\x04\x01\x02\0\x01\x12\x03\x0e\t\x0c\n\x0c\n\
-    \x05\x04\x01\x02\0\x03\x12\x03\x0e\x16\x17\n\x17\n\x04\x04\x01\x02\x01\
-    \x12\x03\x0f\x02\x18\"\n\x20\xe4\xbc\x9a\xe8\xaf\x9dID\n\n\x0c\n\x05\x04\
-    \x01\x02\x01\x05\x12\x03\x0f\x02\x08\n\x0c\n\x05\x04\x01\x02\x01\x01\x12\
-    \x03\x0f\t\x13\n\x0c\n\x05\x04\x01\x02\x01\x03\x12\x03\x0f\x16\x17\n\n\n\
-    \x02\x04\x02\x12\x04\x12\0\x19\x01\n\n\n\x03\x04\x02\x01\x12\x03\x12\x08\
-    \x11\n\x1e\n\x04\x04\x02\x02\0\x12\x03\x13\x02\x1a\"\x11\x20\xe6\xb6\x88\
-    \xe6\x81\xaf\xe5\xba\x8f\xe5\x88\x97\xe5\x8f\xb7\n\n\x0c\n\x05\x04\x02\
-    \x02\0\x05\x12\x03\x13\x02\x08\n\x0c\n\x05\x04\x02\x02\0\x01\x12\x03\x13\
-    \t\x0c\n\x0c\n\x05\x04\x02\x02\0\x03\x12\x03\x13\x18\x19\n\x18\n\x04\x04\
-    \x02\x02\x01\x12\x03\x14\x02\x1a\"\x0b\x20\xe5\x8f\x91\xe9\x80\x81\xe6\
-    \x96\xb9\n\n\x0c\n\x05\x04\x02\x02\x01\x05\x12\x03\x14\x02\x08\n\x0c\n\
-    \x05\x04\x02\x02\x01\x01\x12\x03\x14\t\x13\n\x0c\n\x05\x04\x02\x02\x01\
-    \x03\x12\x03\x14\x18\x19\n\x18\n\x04\x04\x02\x02\x02\x12\x03\x15\x02\x1a\
-    \"\x0b\x20\xe6\x8e\xa5\xe6\x94\xb6\xe6\x96\xb9\n\n\x0c\n\x05\x04\x02\x02\
-    \x02\x05\x12\x03\x15\x02\x08\n\x0c\n\x05\x04\x02\x02\x02\x01\x12\x03\x15\
-    \t\x15\n\x0c\n\x05\x04\x02\x02\x02\x03\x12\x03\x15\x18\x19\n\x17\n\x04\
-    \x04\x02\x02\x03\x12\x03\x16\x02\x1a\"\n\x20\xe6\xb6\x88\xe6\x81\xafID\n\
-    \n\x0c\n\x05\x04\x02\x02\x03\x05\x12\x03\x16\x02\x08\n\x0c\n\x05\x04\x02\
-    \x02\x03\x01\x12\x03\x16\t\x13\n\x0c\n\x05\x04\x02\x02\x03\x03\x12\x03\
-    \x16\x18\x19\n\x1b\n\x04\x04\x02\x02\x04\x12\x03\x17\x02\x1a\"\x0e\x20\
-    \xe6\xb6\x88\xe6\x81\xaf\xe5\x86\x85\xe5\xae\xb9\n\n\x0c\n\x05\x04\x02\
-    \x02\x04\x05\x12\x03\x17\x02\x08\n\x0c\n\x05\x04\x02\x02\x04\x01\x12\x03\
-    \x17\t\x10\n\x0c\n\x05\x04\x02\x02\x04\x03\x12\x03\x17\x18\x19\n\x18\n\
-    \x04\x04\x02\x02\x05\x12\x03\x18\x02\x1a\"\x0b\x20\xe6\x97\xb6\xe9\x97\
-    \xb4\xe6\x88\xb3\n\n\x0c\n\x05\x04\x02\x02\x05\x05\x12\x03\x18\x02\x08\n\
-    \x0c\n\x05\x04\x02\x02\x05\x01\x12\x03\x18\t\x12\n\x0c\n\x05\x04\x02\x02\
-    \x05\x03\x12\x03\x18\x18\x19b\x06proto3\
+    \x0cR\x07content\"\xc7\x01\n\x0eConnectedReply\x12\x10\n\x03uid\x18\x01\
+    \x20\x01(\x04R\x03uid\x12\x1d\n\nsession_id\x18\x02\x20\x01(\tR\tsession\
+    Id\x12\x18\n\x07version\x18\x03\x20\x01(\rR\x07version\x12-\n\x12support\
+    ed_versions\x18\x04\x20\x03(\rR\x11supportedVersions\x12\x18\n\x07resume\
+    d\x18\x05\x20\x01(\x08R\x07resumed\x12!\n\x0cresume_token\x18\x06\x20\
+    \x01(\tR\x0bresumeToken\"\xb6\x01\n\tMsgToUser\x12\x10\n\x03seq\x18\x01\
+    \x20\x01(\x04R\x03seq\x12\x1d\n\nsender_uid\x18\x02\x20\x01(\x04R\tsende\
+    rUid\x12!\n\x0creceiver_uid\x18\x03\x20\x01(\x04R\x0breceiverUid\x12\x1d\
+    \n\nmessage_id\x18\x04\x20\x01(\x04R\tmessageId\x12\x18\n\x07content\x18\
+    \x05\x20\x01(\tR\x07content\x12\x1c\n\ttimestamp\x18\x06\x20\x01(\x04R\t\
+    timestamp\"\xb4\x01\n\x05Login\x12\x1a\n\x08username\x18\x01\x20\x01(\tR\
+    \x08username\x12\x1a\n\x08password\x18\x02\x20\x01(\tR\x08password\x12\
+    \x14\n\x05token\x18\x03\x20\x01(\tR\x05token\x12\x1d\n\nsession_id\x18\
+    \x04\x20\x01(\tR\tsessionId\x12\x1b\n\tdevice_id\x18\x05\x20\x01(\tR\x08\
+    deviceId\x12!\n\x0cresume_token\x18\x06\x20\x01(\tR\x0bresumeToken\"*\n\
+    \x10LoginFailedReply\x12\x16\n\x06reason\x18\x01\x20\x01(\tR\x06reason\"\
+    p\n\x06MsgAck\x12\x10\n\x03seq\x18\x01\x20\x01(\x04R\x03seq\x12\x1d\n\nm\
+    essage_id\x18\x02\x20\x01(\x04R\tmessageId\x12\x1c\n\ttimestamp\x18\x03\
+    \x20\x01(\x04R\ttimestamp\x12\x17\n\x07room_id\x18\x04\x20\x01(\x04R\x06\
+    roomId\"\x88\x01\n\x07Receipt\x12\x1d\n\nmessage_id\x18\x01\x20\x01(\x04\
+    R\tmessageId\x12\x1d\n\nsender_uid\x18\x02\x20\x01(\x04R\tsenderUid\x12!\
+    \n\x0creceiver_uid\x18\x03\x20\x01(\x04R\x0breceiverUid\x12\x1c\n\ttimes\
+    tamp\x18\x04\x20\x01(\x04R\ttimestamp\":\n\x0bRoomRequest\x12\x17\n\x07r\
+    oom_id\x18\x01\x20\x01(\x04R\x06roomId\x12\x12\n\x04name\x18\x02\x20\x01\
+    (\tR\x04name\"w\n\tRoomReply\x12\x1f\n\x06action\x18\x01\x20\x01(\x0e2\
+    \x07.ActionR\x06action\x12\x17\n\x07room_id\x18\x02\x20\x01(\x04R\x06roo\
+    mId\x12\x18\n\x07success\x18\x03\x20\x01(\x08R\x07success\x12\x16\n\x06r\
+    eason\x18\x04\x20\x01(\tR\x06reason\"\xac\x01\n\tMsgToRoom\x12\x10\n\x03\
+    seq\x18\x01\x20\x01(\x04R\x03seq\x12\x1d\n\nsender_uid\x18\x02\x20\x01(\
+    \x04R\tsenderUid\x12\x17\n\x07room_id\x18\x03\x20\x01(\x04R\x06roomId\
+    \x12\x1d\n\nmessage_id\x18\x04\x20\x01(\x04R\tmessageId\x12\x18\n\x07con\
+    tent\x18\x05\x20\x01(\tR\x07content\x12\x1c\n\ttimestamp\x18\x06\x20\x01\
+    (\x04R\ttimestamp\"'\n\rServerClosing\x12\x16\n\x06reason\x18\x01\x20\
+    \x01(\tR\x06reason\"c\n\x08Presence\x12\x10\n\x03uid\x18\x01\x20\x01(\
+    \x04R\x03uid\x12'\n\x06status\x18\x02\x20\x01(\x0e2\x0f.PresenceStatusR\
+    \x06status\x12\x1c\n\ttimestamp\x18\x03\x20\x01(\x04R\ttimestamp\"%\n\
+    \x0fPresenceRequest\x12\x12\n\x04uids\x18\x01\x20\x03(\x04R\x04uids\"7\n\
+    \x0cPresenceList\x12'\n\tpresences\x18\x01\x20\x03(\x0b2\t.PresenceR\tpr\
+    esences*\xd2\x02\n\x06Action\x12\r\n\tCONNECTED\x10\0\x12\r\n\tHEARTBEAT\
+    \x10\x01\x12\x0f\n\x0bMSG_TO_USER\x10\x02\x12\t\n\x05LOGIN\x10\x03\x12\
+    \x10\n\x0cLOGIN_FAILED\x10\x04\x12\x0b\n\x07MSG_ACK\x10\x05\x12\x11\n\rD\
+    ELIVERED_ACK\x10\x06\x12\x10\n\x0cREAD_RECEIPT\x10\x07\x12\x0f\n\x0bCREA\
+    TE_ROOM\x10\x08\x12\r\n\tJOIN_ROOM\x10\t\x12\x0e\n\nLEAVE_ROOM\x10\n\x12\
+    \x0f\n\x0bMSG_TO_ROOM\x10\x0b\x12\x0e\n\nROOM_REPLY\x10\x0c\x12\x12\n\
+    \x0eSERVER_CLOSING\x10\r\x12\x12\n\x0ePRESENCE_QUERY\x10\x0e\x12\x16\n\
+    \x12PRESENCE_SUBSCRIBE\x10\x0f\x12\x18\n\x14PRESENCE_UNSUBSCRIBE\x10\x10\
+    \x12\x11\n\rPRESENCE_LIST\x10\x11\x12\x0c\n\x08PRESENCE\x10\x12*3\n\x0eP\
+    resenceStatus\x12\x0b\n\x07OFFLINE\x10\0\x12\n\n\x06ONLINE\x10\x01\x12\
+    \x08\n\x04AWAY\x10\x02J\xde-\n\x06\x12\x04\0\0x\x01\n\x08\n\x01\x0c\x12\
+    \x03\0\0\x12\n\n\n\x02\x05\0\x12\x04\x02\0\x16\x01\n\n\n\x03\x05\0\x01\
+    \x12\x03\x02\x05\x0b\n\x1b\n\x04\x05\0\x02\0\x12\x03\x03\x02\x14\"\x0e\
+    \x20\xe8\xbf\x9e\xe6\x8e\xa5\xe6\x88\x90\xe5\x8a\x9f\n\n\x0c\n\x05\x05\0\
+    \x02\0\x01\x12\x03\x03\x02\x0b\n\x0c\n\x05\x05\0\x02\0\x02\x12\x03\x03\
+    \x12\x13\n\x1b\n\x04\x05\0\x02\x01\x12\x03\x04\x02\x14\"\x0e\x20\xe5\xbf\
+    \x83\xe8\xb7\xb3\xe6\xa3\x80\xe6\xb5\x8b\n\n\x0c\n\x05\x05\0\x02\x01\x01\
+    \x12\x03\x04\x02\x0b\n\x0c\n\x05\x05\0\x02\x01\x02\x12\x03\x04\x12\x13\n\
+    !\n\x04\x05\0\x02\x02\x12\x03\x05\x02\x14\"\x14\x20\xe5\x8f\x91\xe6\xb6\
+    \x88\xe6\x81\xaf\xe5\x88\xb0\xe7\x94\xa8\xe6\x88\xb7\n\n\x0c\n\x05\x05\0\
+    \x02\x02\x01\x12\x03\x05\x02\r\n\x0c\n\x05\x05\0\x02\x02\x02\x12\x03\x05\
+    \x12\x13\n\x1b\n\x04\x05\0\x02\x03\x12\x03\x06\x02\x14\"\x0e\x20\xe7\x99\
+    \xbb\xe5\xbd\x95\xe8\xae\xa4\xe8\xaf\x81\n\n\x0c\n\x05\x05\0\x02\x03\x01\
+    \x12\x03\x06\x02\x07\n\x0c\n\x05\x05\0\x02\x03\x02\x12\x03\x06\x12\x13\n\
+    \x1b\n\x04\x05\0\x02\x04\x12\x03\x07\x02\x14\"\x0e\x20\xe7\x99\xbb\xe5\
+    \xbd\x95\xe5\xa4\xb1\xe8\xb4\xa5\n\n\x0c\n\x05\x05\0\x02\x04\x01\x12\x03\
+    \x07\x02\x0e\n\x0c\n\x05\x05\0\x02\x04\x02\x12\x03\x07\x12\x13\n'\n\x04\
+    \x05\0\x02\x05\x12\x03\x08\x02\x14\"\x1a\x20\xe6\x9c\x8d\xe5\x8a\xa1\xe7\
+    \xab\xaf\xe5\xb7\xb2\xe6\x8e\xa5\xe6\x94\xb6\xe6\xb6\x88\xe6\x81\xaf\n\n\
+    \x0c\n\x05\x05\0\x02\x05\x01\x12\x03\x08\x02\t\n\x0c\n\x05\x05\0\x02\x05\
+    \x02\x12\x03\x08\x12\x13\n'\n\x04\x05\0\x02\x06\x12\x03\t\x02\x14\"\x1a\
+    \x20\xe6\x8e\xa5\xe6\x94\xb6\xe6\x96\xb9\xe5\xb7\xb2\xe6\x94\xb6\xe5\x88\
+    \xb0\xe6\xb6\x88\xe6\x81\xaf\n\n\x0c\n\x05\x05\0\x02\x06\x01\x12\x03\t\
+    \x02\x0f\n\x0c\n\x05\x05\0\x02\x06\x02\x12\x03\t\x12\x13\n$\n\x04\x05\0\
+    \x02\x07\x12\x03\n\x02\x14\"\x17\x20\xe6\x8e\xa5\xe6\x94\xb6\xe6\x96\xb9\
+    \xe5\xb7\xb2\xe8\xaf\xbb\xe6\xb6\x88\xe6\x81\xaf\n\n\x0c\n\x05\x05\0\x02\
+    \x07\x01\x12\x03\n\x02\x0e\n\x0c\n\x05\x05\0\x02\x07\x02\x12\x03\n\x12\
+    \x13\n\x1e\n\x04\x05\0\x02\x08\x12\x03\x0b\x02\x14\"\x11\x20\xe5\x88\x9b\
+    \xe5\xbb\xba\xe8\x81\x8a\xe5\xa4\xa9\xe5\xae\xa4\n\n\x0c\n\x05\x05\0\x02\
+    \x08\x01\x12\x03\x0b\x02\r\n\x0c\n\x05\x05\0\x02\x08\x02\x12\x03\x0b\x12\
+    \x13\n\x1e\n\x04\x05\0\x02\t\x12\x03\x0c\x02\x14\"\x11\x20\xe5\x8a\xa0\
+    \xe5\x85\xa5\xe8\x81\x8a\xe5\xa4\xa9\xe5\xae\xa4\n\n\x0c\n\x05\x05\0\x02\
+    \t\x01\x12\x03\x0c\x02\x0b\n\x0c\n\x05\x05\0\x02\t\x02\x12\x03\x0c\x12\
+    \x13\n\x1e\n\x04\x05\0\x02\n\x12\x03\r\x02\x15\"\x11\x20\xe7\xa6\xbb\xe5\
+    \xbc\x80\xe8\x81\x8a\xe5\xa4\xa9\xe5\xae\xa4\n\n\x0c\n\x05\x05\0\x02\n\
+    \x01\x12\x03\r\x02\x0c\n\x0c\n\x05\x05\0\x02\n\x02\x12\x03\r\x12\x14\n$\
+    \n\x04\x05\0\x02\x0b\x12\x03\x0e\x02\x15\"\x17\x20\xe5\x8f\x91\xe6\xb6\
+    \x88\xe6\x81\xaf\xe5\x88\xb0\xe8\x81\x8a\xe5\xa4\xa9\xe5\xae\xa4\n\n\x0c\
+    \n\x05\x05\0\x02\x0b\x01\x12\x03\x0e\x02\r\n\x0c\n\x05\x05\0\x02\x0b\x02\
+    \x12\x03\x0e\x12\x14\n$\n\x04\x05\0\x02\x0c\x12\x03\x0f\x02\x15\"\x17\
+    \x20\xe8\x81\x8a\xe5\xa4\xa9\xe5\xae\xa4\xe6\x93\x8d\xe4\xbd\x9c\xe7\xbb\
+    \x93\xe6\x9e\x9c\n\n\x0c\n\x05\x05\0\x02\x0c\x01\x12\x03\x0f\x02\x0c\n\
+    \x0c\n\x05\x05\0\x02\x0c\x02\x12\x03\x0f\x12\x14\n$\n\x04\x05\0\x02\r\
+    \x12\x03\x10\x02\x16\"\x17\x20\xe6\x9c\x8d\xe5\x8a\xa1\xe7\xab\xaf\xe5\
+    \x8d\xb3\xe5\xb0\x86\xe5\x85\xb3\xe9\x97\xad\n\n\x0c\n\x05\x05\0\x02\r\
+    \x01\x12\x03\x10\x02\x10\n\x0c\n\x05\x05\0\x02\r\x02\x12\x03\x10\x13\x15\
+    \n@\n\x04\x05\0\x02\x0e\x12\x03\x11\x02\x1c\"3\x20\xe6\x9f\xa5\xe8\xaf\
+    \xa2\xe7\x94\xa8\xe6\x88\xb7\xe7\x9a\x84\xe5\x9c\xa8\xe7\xba\xbf\xe7\x8a\
+    \xb6\xe6\x80\x81,\x20\xe5\x9b\x9e\xe5\xa4\x8d\x20PRESENCE_LIST\n\n\x0c\n\
+    \x05\x05\0\x02\x0e\x01\x12\x03\x11\x02\x10\n\x0c\n\x05\x05\0\x02\x0e\x02\
+    \x12\x03\x11\x19\x1b\n[\n\x04\x05\0\x02\x0f\x12\x03\x12\x02\x1c\"N\x20\
+    \xe8\xae\xa2\xe9\x98\x85\xe7\x94\xa8\xe6\x88\xb7\xe7\x9a\x84\xe5\x9c\xa8\
+    \xe7\xba\xbf\xe7\x8a\xb6\xe6\x80\x81\xe5\x8f\x98\xe5\x8c\x96,\x20\xe5\
+    \x9b\x9e\xe5\xa4\x8d\xe8\xbf\x99\xe4\xba\x9b\xe7\x94\xa8\xe6\x88\xb7\xe5\
+    \xbd\x93\xe5\x89\x8d\xe7\x9a\x84\x20PRESENCE_LIST\n\n\x0c\n\x05\x05\0\
+    \x02\x0f\x01\x12\x03\x12\x02\x14\n\x0c\n\x05\x05\0\x02\x0f\x02\x12\x03\
+    \x12\x19\x1b\n\x1b\n\x04\x05\0\x02\x10\x12\x03\x13\x02\x1c\"\x0e\x20\xe5\
+    \x8f\x96\xe6\xb6\x88\xe8\xae\xa2\xe9\x98\x85\n\n\x0c\n\x05\x05\0\x02\x10\
+    \x01\x12\x03\x13\x02\x16\n\x0c\n\x05\x05\0\x02\x10\x02\x12\x03\x13\x19\
+    \x1b\n!\n\x04\x05\0\x02\x11\x12\x03\x14\x02\x1c\"\x14\x20\xe5\x9c\xa8\
+    \xe7\xba\xbf\xe7\x8a\xb6\xe6\x80\x81\xe5\x88\x97\xe8\xa1\xa8\n\n\x0c\n\
+    \x05\x05\0\x02\x11\x01\x12\x03\x14\x02\x0f\n\x0c\n\x05\x05\0\x02\x11\x02\
+    \x12\x03\x14\x19\x1b\nb\n\x04\x05\0\x02\x12\x12\x03\x15\x02\x1c\"U\x20\
+    \xe6\x9c\x8d\xe5\x8a\xa1\xe7\xab\xaf\xe6\x8e\xa8\xe9\x80\x81\xe8\xae\xa2\
+    \xe9\x98\x85\xe7\x94\xa8\xe6\x88\xb7\xe7\x9a\x84\xe7\x8a\xb6\xe6\x80\x81\
+    \xe5\x8f\x98\xe5\x8c\x96,\x20\xe5\xae\xa2\xe6\x88\xb7\xe7\xab\xaf\xe5\
+    \x8f\x91\xe9\x80\x81\xe6\x97\xb6\xe8\xae\xbe\xe7\xbd\xae\xe8\x87\xaa\xe5\
+    \xb7\xb1\xe7\x9a\x84\xe7\x8a\xb6\xe6\x80\x81\n\n\x0c\n\x05\x05\0\x02\x12\
+    \x01\x12\x03\x15\x02\n\n\x0c\n\x05\x05\0\x02\x12\x02\x12\x03\x15\x19\x1b\
+    \n\n\n\x02\x05\x01\x12\x04\x18\0\x1c\x01\n\n\n\x03\x05\x01\x01\x12\x03\
+    \x18\x05\x13\n\x15\n\x04\x05\x01\x02\0\x12\x03\x19\x02\x0e\"\x08\x20\xe7\
+    \xa6\xbb\xe7\xba\xbf\n\n\x0c\n\x05\x05\x01\x02\0\x01\x12\x03\x19\x02\t\n\
+    \x0c\n\x05\x05\x01\x02\0\x02\x12\x03\x19\x0c\r\n\x15\n\x04\x05\x01\x02\
+    \x01\x12\x03\x1a\x02\x0e\"\x08\x20\xe5\x9c\xa8\xe7\xba\xbf\n\n\x0c\n\x05\
+    \x05\x01\x02\x01\x01\x12\x03\x1a\x02\x08\n\x0c\n\x05\x05\x01\x02\x01\x02\
+    \x12\x03\x1a\x0c\r\n#\n\x04\x05\x01\x02\x02\x12\x03\x1b\x02\x0e\"\x16\
+    \x20\xe7\xa6\xbb\xe5\xbc\x80,\x20\xe4\xbb\x8d\xe7\x84\xb6\xe5\x9c\xa8\
+    \xe7\xba\xbf\n\n\x0c\n\x05\x05\x01\x02\x02\x01\x12\x03\x1b\x02\x06\n\x0c\
+    \n\x05\x05\x01\x02\x02\x02\x12\x03\x1b\x0c\r\n\n\n\x02\x04\0\x12\x04\x1e\
+    \0!\x01\n\n\n\x03\x04\0\x01\x12\x03\x1e\x08\x0f\n\x0b\n\x04\x04\0\x02\0\
+    \x12\x03\x1f\x02\x15\n\x0c\n\x05\x04\0\x02\0\x06\x12\x03\x1f\x02\x08\n\
+    \x0c\n\x05\x04\0\x02\0\x01\x12\x03\x1f\t\x0f\n\x0c\n\x05\x04\0\x02\0\x03\
+    \x12\x03\x1f\x13\x14\n\x0b\n\x04\x04\0\x02\x01\x12\x03\x20\x02\x15\n\x0c\
+    \n\x05\x04\0\x02\x01\x05\x12\x03\x20\x02\x07\n\x0c\n\x05\x04\0\x02\x01\
+    \x01\x12\x03\x20\t\x10\n\x0c\n\x05\x04\0\x02\x01\x03\x12\x03\x20\x13\x14\
+    \n\n\n\x02\x04\x01\x12\x04#\0*\x01\n\n\n\x03\x04\x01\x01\x12\x03#\x08\
+    \x16\n\x17\n\x04\x04\x01\x02\0\x12\x03$\x02\x18\"\n\x20\xe7\x94\xa8\xe6\
+    \x88\xb7ID\n\n\x0c\n\x05\x04\x01\x02\0\x05\x12\x03$\x02\x08\n\x0c\n\x05\
+    \x04\x01\x02\0\x01\x12\x03$\t\x0c\n\x0c\n\x05\x04\x01\x02\0\x03\x12\x03$\
+    \x16\x17\n\x17\n\x04\x04\x01\x02\x01\x12\x03%\x02\x18\"\n\x20\xe4\xbc\
+    \x9a\xe8\xaf\x9dID\n\n\x0c\n\x05\x04\x01\x02\x01\x05\x12\x03%\x02\x08\n\
+    \x0c\n\x05\x04\x01\x02\x01\x01\x12\x03%\t\x13\n\x0c\n\x05\x04\x01\x02\
+    \x01\x03\x12\x03%\x16\x17\nH\n\x04\x04\x01\x02\x02\x12\x03&\x02\x18\";\
+    \x20\xe6\x8f\xa1\xe6\x89\x8b\xe5\x8d\x8f\xe5\x95\x86\xe7\x9a\x84\xe5\x8d\
+    \x8f\xe8\xae\xae\xe7\x89\x88\xe6\x9c\xac,\x20\xe6\x97\xa7\xe5\xae\xa2\
+    \xe6\x88\xb7\xe7\xab\xaf\xe6\x9c\xaa\xe6\x8f\xa1\xe6\x89\x8b\xe6\x97\xb6\
+    \xe4\xb8\xba1\n\n\x0c\n\x05\x04\x01\x02\x02\x05\x12\x03&\x02\x08\n\x0c\n\
+    \x05\x04\x01\x02\x02\x01\x12\x03&\t\x10\n\x0c\n\x05\x04\x01\x02\x02\x03\
+    \x12\x03&\x16\x17\n3\n\x04\x04\x01\x02\x03\x12\x03'\x02)\"&\x20\xe6\x9c\
+    \x8d\xe5\x8a\xa1\xe7\xab\xaf\xe6\x94\xaf\xe6\x8c\x81\xe7\x9a\x84\xe5\x85\
+    \xa8\xe9\x83\xa8\xe5\x8d\x8f\xe8\xae\xae\xe7\x89\x88\xe6\x9c\xac\n\n\x0c\
+    \n\x05\x04\x01\x02\x03\x04\x12\x03'\x02\n\n\x0c\n\x05\x04\x01\x02\x03\
+    \x05\x12\x03'\x0b\x11\n\x0c\n\x05\x04\x01\x02\x03\x01\x12\x03'\x12$\n\
+    \x0c\n\x05\x04\x01\x02\x03\x03\x12\x03''(\n0\n\x04\x04\x01\x02\x04\x12\
+    \x03(\x02\x18\"#\x20\xe6\x98\xaf\xe5\x90\xa6\xe6\x81\xa2\xe5\xa4\x8d\xe4\
+    \xba\x86\xe6\x96\xad\xe7\xba\xbf\xe5\x89\x8d\xe7\x9a\x84\xe4\xbc\x9a\xe8\
+    \xaf\x9d\n\n\x0c\n\x05\x04\x01\x02\x04\x05\x12\x03(\x02\x06\n\x0c\n\x05\
+    \x04\x01\x02\x04\x01\x12\x03(\t\x10\n\x0c\n\x05\x04\x01\x02\x04\x03\x12\
+    \x03(\x16\x17\nJ\n\x04\x04\x01\x02\x05\x12\x03)\x02\x1a\"=\x20\xe6\x81\
+    \xa2\xe5\xa4\x8d\xe4\xbc\x9a\xe8\xaf\x9d\xe7\x9a\x84\xe5\x87\xad\xe8\xaf\
+    \x81,\x20\xe9\x87\x8d\xe8\xbf\x9e\xe6\x97\xb6\xe4\xb8\x8e\x20session_id\
+    \x20\xe4\xb8\x80\xe8\xb5\xb7\xe6\x8f\x90\xe4\xba\xa4\n\n\x0c\n\x05\x04\
+    \x01\x02\x05\x05\x12\x03)\x02\x08\n\x0c\n\x05\x04\x01\x02\x05\x01\x12\
+    \x03)\t\x15\n\x0c\n\x05\x04\x01\x02\x05\x03\x12\x03)\x18\x19\n\n\n\x02\
+    \x04\x02\x12\x04,\03\x01\n\n\n\x03\x04\x02\x01\x12\x03,\x08\x11\n\x1e\n\
+    \x04\x04\x02\x02\0\x12\x03-\x02\x1a\"\x11\x20\xe6\xb6\x88\xe6\x81\xaf\
+    \xe5\xba\x8f\xe5\x88\x97\xe5\x8f\xb7\n\n\x0c\n\x05\x04\x02\x02\0\x05\x12\
+    \x03-\x02\x08\n\x0c\n\x05\x04\x02\x02\0\x01\x12\x03-\t\x0c\n\x0c\n\x05\
+    \x04\x02\x02\0\x03\x12\x03-\x18\x19\n\x18\n\x04\x04\x02\x02\x01\x12\x03.\
+    \x02\x1a\"\x0b\x20\xe5\x8f\x91\xe9\x80\x81\xe6\x96\xb9\n\n\x0c\n\x05\x04\
+    \x02\x02\x01\x05\x12\x03.\x02\x08\n\x0c\n\x05\x04\x02\x02\x01\x01\x12\
+    \x03.\t\x13\n\x0c\n\x05\x04\x02\x02\x01\x03\x12\x03.\x18\x19\n\x18\n\x04\
+    \x04\x02\x02\x02\x12\x03/\x02\x1a\"\x0b\x20\xe6\x8e\xa5\xe6\x94\xb6\xe6\
+    \x96\xb9\n\n\x0c\n\x05\x04\x02\x02\x02\x05\x12\x03/\x02\x08\n\x0c\n\x05\
+    \x04\x02\x02\x02\x01\x12\x03/\t\x15\n\x0c\n\x05\x04\x02\x02\x02\x03\x12\
+    \x03/\x18\x19\n\x17\n\x04\x04\x02\x02\x03\x12\x030\x02\x1a\"\n\x20\xe6\
+    \xb6\x88\xe6\x81\xafID\n\n\x0c\n\x05\x04\x02\x02\x03\x05\x12\x030\x02\
+    \x08\n\x0c\n\x05\x04\x02\x02\x03\x01\x12\x030\t\x13\n\x0c\n\x05\x04\x02\
+    \x02\x03\x03\x12\x030\x18\x19\n\x1b\n\x04\x04\x02\x02\x04\x12\x031\x02\
+    \x1a\"\x0e\x20\xe6\xb6\x88\xe6\x81\xaf\xe5\x86\x85\xe5\xae\xb9\n\n\x0c\n\
+    \x05\x04\x02\x02\x04\x05\x12\x031\x02\x08\n\x0c\n\x05\x04\x02\x02\x04\
+    \x01\x12\x031\t\x10\n\x0c\n\x05\x04\x02\x02\x04\x03\x12\x031\x18\x19\n\
+    \x18\n\x04\x04\x02\x02\x05\x12\x032\x02\x1a\"\x0b\x20\xe6\x97\xb6\xe9\
+    \x97\xb4\xe6\x88\xb3\n\n\x0c\n\x05\x04\x02\x02\x05\x05\x12\x032\x02\x08\
+    \n\x0c\n\x05\x04\x02\x02\x05\x01\x12\x032\t\x12\n\x0c\n\x05\x04\x02\x02\
+    \x05\x03\x12\x032\x18\x19\n\n\n\x02\x04\x03\x12\x045\0<\x01\n\n\n\x03\
+    \x04\x03\x01\x12\x035\x08\r\n\x18\n\x04\x04\x03\x02\0\x12\x036\x02\x16\"\
+    \x0b\x20\xe7\x94\xa8\xe6\x88\xb7\xe5\x90\x8d\n\n\x0c\n\x05\x04\x03\x02\0\
+    \x05\x12\x036\x02\x08\n\x0c\n\x05\x04\x03\x02\0\x01\x12\x036\t\x11\n\x0c\
+    \n\x05\x04\x03\x02\0\x03\x12\x036\x14\x15\n\x15\n\x04\x04\x03\x02\x01\
+    \x12\x037\x02\x16\"\x08\x20\xe5\xaf\x86\xe7\xa0\x81\n\n\x0c\n\x05\x04\
+    \x03\x02\x01\x05\x12\x037\x02\x08\n\x0c\n\x05\x04\x03\x02\x01\x01\x12\
+    \x037\t\x11\n\x0c\n\x05\x04\x03\x02\x01\x03\x12\x037\x14\x15\n2\n\x04\
+    \x04\x03\x02\x02\x12\x038\x02\x16\"%\x20\xe4\xbb\xa4\xe7\x89\x8c,\x20\
+    \xe4\xb8\x8e\xe7\x94\xa8\xe6\x88\xb7\xe5\x90\x8d\xe5\xaf\x86\xe7\xa0\x81\
+    \xe4\xba\x8c\xe9\x80\x89\xe4\xb8\x80\n\n\x0c\n\x05\x04\x03\x02\x02\x05\
+    \x12\x038\x02\x08\n\x0c\n\x05\x04\x03\x02\x02\x01\x12\x038\t\x0e\n\x0c\n\
+    \x05\x04\x03\x02\x02\x03\x12\x038\x14\x15\n_\n\x04\x04\x03\x02\x03\x12\
+    \x039\x02\x18\"R\x20\xe6\x96\xad\xe7\xba\xbf\xe9\x87\x8d\xe8\xbf\x9e\xe6\
+    \x97\xb6\xe6\x81\xa2\xe5\xa4\x8d\xe7\x9a\x84\xe4\xbc\x9a\xe8\xaf\x9d,\
+    \x20\xe5\xae\xbd\xe9\x99\x90\xe6\x9c\x9f\xe5\x86\x85\xe6\x9c\x89\xe6\x95\
+    \x88\xe6\x97\xb6\xe4\xb8\x8d\xe5\x86\x8d\xe6\xa0\xa1\xe9\xaa\x8c\xe7\x94\
+    \xa8\xe6\x88\xb7\xe5\x90\x8d\xe5\xaf\x86\xe7\xa0\x81\n\n\x0c\n\x05\x04\
+    \x03\x02\x03\x05\x12\x039\x02\x08\n\x0c\n\x05\x04\x03\x02\x03\x01\x12\
+    \x039\t\x13\n\x0c\n\x05\x04\x03\x02\x03\x03\x12\x039\x16\x17\nF\n\x04\
+    \x04\x03\x02\x04\x12\x03:\x02\x18\"9\x20\xe8\xae\xbe\xe5\xa4\x87ID,\x20\
+    \xe5\x90\x8c\xe4\xb8\x80\xe7\x94\xa8\xe6\x88\xb7\xe7\x9a\x84\xe4\xb8\x8d\
+    \xe5\x90\x8c\xe8\xae\xbe\xe5\xa4\x87\xe5\x8f\xaf\xe4\xbb\xa5\xe5\x90\x8c\
+    \xe6\x97\xb6\xe5\x9c\xa8\xe7\xba\xbf\n\n\x0c\n\x05\x04\x03\x02\x04\x05\
+    \x12\x03:\x02\x08\n\x0c\n\x05\x04\x03\x02\x04\x01\x12\x03:\t\x12\n\x0c\n\
+    \x05\x04\x03\x02\x04\x03\x12\x03:\x16\x17\n\\\n\x04\x04\x03\x02\x05\x12\
+    \x03;\x02\x1a\"O\x20\xe7\x99\xbb\xe5\xbd\x95\xe6\x88\x90\xe5\x8a\x9f\xe6\
+    \x97\xb6\xe4\xb8\x8b\xe5\x8f\x91\xe7\x9a\x84\xe6\x81\xa2\xe5\xa4\x8d\xe5\
+    \x87\xad\xe8\xaf\x81,\x20\xe4\xb8\x8e\x20session_id\x20\xe4\xb8\x8d\xe5\
+    \x8c\xb9\xe9\x85\x8d\xe6\x97\xb6\xe4\xb8\x8d\xe8\x83\xbd\xe6\x81\xa2\xe5\
+    \xa4\x8d\n\n\x0c\n\x05\x04\x03\x02\x05\x05\x12\x03;\x02\x08\n\x0c\n\x05\
+    \x04\x03\x02\x05\x01\x12\x03;\t\x15\n\x0c\n\x05\x04\x03\x02\x05\x03\x12\
+    \x03;\x18\x19\n\n\n\x02\x04\x04\x12\x04>\0@\x01\n\n\n\x03\x04\x04\x01\
+    \x12\x03>\x08\x18\n\x1b\n\x04\x04\x04\x02\0\x12\x03?\x02\x14\"\x0e\x20\
+    \xe5\xa4\xb1\xe8\xb4\xa5\xe5\x8e\x9f\xe5\x9b\xa0\n\n\x0c\n\x05\x04\x04\
+    \x02\0\x05\x12\x03?\x02\x08\n\x0c\n\x05\x04\x04\x02\0\x01\x12\x03?\t\x0f\
+    \n\x0c\n\x05\x04\x04\x02\0\x03\x12\x03?\x12\x13\n\n\n\x02\x04\x05\x12\
+    \x04B\0G\x01\n\n\n\x03\x04\x05\x01\x12\x03B\x08\x0e\n'\n\x04\x04\x05\x02\
+    \0\x12\x03C\x02\x18\"\x1a\x20\xe5\xae\xa2\xe6\x88\xb7\xe7\xab\xaf\xe6\
+    \xb6\x88\xe6\x81\xaf\xe5\xba\x8f\xe5\x88\x97\xe5\x8f\xb7\n\n\x0c\n\x05\
+    \x04\x05\x02\0\x05\x12\x03C\x02\x08\n\x0c\n\x05\x04\x05\x02\0\x01\x12\
+    \x03C\t\x0c\n\x0c\n\x05\x04\x05\x02\0\x03\x12\x03C\x16\x17\nT\n\x04\x04\
+    \x05\x02\x01\x12\x03D\x02\x18\"G\x20\xe6\x9c\x8d\xe5\x8a\xa1\xe7\xab\xaf\
+    \xe5\x88\x86\xe9\x85\x8d\xe7\x9a\x84\xe6\xb6\x88\xe6\x81\xafID,\x20\xe8\
+    \x81\x8a\xe5\xa4\xa9\xe5\xae\xa4\xe6\xb6\x88\xe6\x81\xaf\xe4\xb8\xba\xe8\
+    \x81\x8a\xe5\xa4\xa9\xe5\xae\xa4\xe5\x86\x85\xe7\x9a\x84\xe6\xb6\x88\xe6\
+    \x81\xafID\n\n\x0c\n\x05\x04\x05\x02\x01\x05\x12\x03D\x02\x08\n\x0c\n\
+    \x05\x04\x05\x02\x01\x01\x12\x03D\t\x13\n\x0c\n\x05\x04\x05\x02\x01\x03\
+    \x12\x03D\x16\x17\n\x18\n\x04\x04\x05\x02\x02\x12\x03E\x02\x18\"\x0b\x20\
+    \xe6\x97\xb6\xe9\x97\xb4\xe6\x88\xb3\n\n\x0c\n\x05\x04\x05\x02\x02\x05\
+    \x12\x03E\x02\x08\n\x0c\n\x05\x04\x05\x02\x02\x01\x12\x03E\t\x12\n\x0c\n\
+    \x05\x04\x05\x02\x02\x03\x12\x03E\x16\x17\n9\n\x04\x04\x05\x02\x03\x12\
+    \x03F\x02\x18\",\x20\xe8\x81\x8a\xe5\xa4\xa9\xe5\xae\xa4\xe6\xb6\x88\xe6\
+    \x81\xaf\xe7\x9a\x84\xe7\xa1\xae\xe8\xae\xa4,\x20\xe5\x8d\x95\xe8\x81\
+    \x8a\xe6\xb6\x88\xe6\x81\xaf\xe4\xb8\xba0\n\n\x0c\n\x05\x04\x05\x02\x03\
+    \x05\x12\x03F\x02\x08\n\x0c\n\x05\x04\x05\x02\x03\x01\x12\x03F\t\x10\n\
+    \x0c\n\x05\x04\x05\x02\x03\x03\x12\x03F\x16\x17\n]\n\x02\x04\x06\x12\x04\
+    J\0O\x01\x1aQ\x20\xe9\x80\x81\xe8\xbe\xbe\xe7\xa1\xae\xe8\xae\xa4\xe4\
+    \xb8\x8e\xe5\xb7\xb2\xe8\xaf\xbb\xe5\x9b\x9e\xe6\x89\xa7,\x20\xe7\x94\
+    \xb1\xe6\x8e\xa5\xe6\x94\xb6\xe6\x96\xb9\xe5\x8f\x91\xe5\x87\xba,\x20\
+    \xe7\xbb\x8f\xe6\x9c\x8d\xe5\x8a\xa1\xe7\xab\xaf\xe8\xbd\xac\xe5\x8f\x91\
+    \xe7\xbb\x99\xe5\x8f\x91\xe9\x80\x81\xe6\x96\xb9\n\n\n\n\x03\x04\x06\x01\
+    \x12\x03J\x08\x0f\n\x17\n\x04\x04\x06\x02\0\x12\x03K\x02\x1a\"\n\x20\xe6\
+    \xb6\x88\xe6\x81\xafID\n\n\x0c\n\x05\x04\x06\x02\0\x05\x12\x03K\x02\x08\
+    \n\x0c\n\x05\x04\x06\x02\0\x01\x12\x03K\t\x13\n\x0c\n\x05\x04\x06\x02\0\
+    \x03\x12\x03K\x18\x19\n!\n\x04\x04\x06\x02\x01\x12\x03L\x02\x1a\"\x14\
+    \x20\xe5\x8e\x9f\xe6\xb6\x88\xe6\x81\xaf\xe5\x8f\x91\xe9\x80\x81\xe6\x96\
+    \xb9\n\n\x0c\n\x05\x04\x06\x02\x01\x05\x12\x03L\x02\x08\n\x0c\n\x05\x04\
+    \x06\x02\x01\x01\x12\x03L\t\x13\n\x0c\n\x05\x04\x06\x02\x01\x03\x12\x03L\
+    \x18\x19\n!\n\x04\x04\x06\x02\x02\x12\x03M\x02\x1a\"\x14\x20\xe5\x8e\x9f\
+    \xe6\xb6\x88\xe6\x81\xaf\xe6\x8e\xa5\xe6\x94\xb6\xe6\x96\xb9\n\n\x0c\n\
+    \x05\x04\x06\x02\x02\x05\x12\x03M\x02\x08\n\x0c\n\x05\x04\x06\x02\x02\
+    \x01\x12\x03M\t\x15\n\x0c\n\x05\x04\x06\x02\x02\x03\x12\x03M\x18\x19\n\
+    \x18\n\x04\x04\x06\x02\x03\x12\x03N\x02\x1a\"\x0b\x20\xe6\x97\xb6\xe9\
+    \x97\xb4\xe6\x88\xb3\n\n\x0c\n\x05\x04\x06\x02\x03\x05\x12\x03N\x02\x08\
+    \n\x0c\n\x05\x04\x06\x02\x03\x01\x12\x03N\t\x12\n\x0c\n\x05\x04\x06\x02\
+    \x03\x03\x12\x03N\x18\x19\nJ\n\x02\x04\x07\x12\x04R\0U\x01\x1a>\x20CREAT\
+    E_ROOM\x20\xe4\xbd\xbf\xe7\x94\xa8\x20name,\x20JOIN_ROOM/LEAVE_ROOM\x20\
+    \xe4\xbd\xbf\xe7\x94\xa8\x20room_id\n\n\n\n\x03\x04\x07\x01\x12\x03R\x08\
+    \x13\n\x1a\n\x04\x04\x07\x02\0\x12\x03S\x02\x15\"\r\x20\xe8\x81\x8a\xe5\
+    \xa4\xa9\xe5\xae\xa4ID\n\n\x0c\n\x05\x04\x07\x02\0\x05\x12\x03S\x02\x08\
+    \n\x0c\n\x05\x04\x07\x02\0\x01\x12\x03S\t\x10\n\x0c\n\x05\x04\x07\x02\0\
+    \x03\x12\x03S\x13\x14\n\x1e\n\x04\x04\x07\x02\x01\x12\x03T\x02\x15\"\x11\
+    \x20\xe8\x81\x8a\xe5\xa4\xa9\xe5\xae\xa4\xe5\x90\x8d\xe7\xa7\xb0\n\n\x0c\
+    \n\x05\x04\x07\x02\x01\x05\x12\x03T\x02\x08\n\x0c\n\x05\x04\x07\x02\x01\
+    \x01\x12\x03T\t\r\n\x0c\n\x05\x04\x07\x02\x01\x03\x12\x03T\x13\x14\n\n\n\
+    \x02\x04\x08\x12\x04W\0\\\x01\n\n\n\x03\x04\x08\x01\x12\x03W\x08\x11\n\
+    \x1e\n\x04\x04\x08\x02\0\x12\x03X\x02\x15\"\x11\x20\xe5\xaf\xb9\xe5\xba\
+    \x94\xe7\x9a\x84\xe8\xaf\xb7\xe6\xb1\x82\n\n\x0c\n\x05\x04\x08\x02\0\x06\
+    \x12\x03X\x02\x08\n\x0c\n\x05\x04\x08\x02\0\x01\x12\x03X\t\x0f\n\x0c\n\
+    \x05\x04\x08\x02\0\x03\x12\x03X\x13\x14\n\x1a\n\x04\x04\x08\x02\x01\x12\
+    \x03Y\x02\x15\"\r\x20\xe8\x81\x8a\xe5\xa4\xa9\xe5\xae\xa4ID\n\n\x0c\n\
+    \x05\x04\x08\x02\x01\x05\x12\x03Y\x02\x08\n\x0c\n\x05\x04\x08\x02\x01\
+    \x01\x12\x03Y\t\x10\n\x0c\n\x05\x04\x08\x02\x01\x03\x12\x03Y\x13\x14\n\
+    \x1b\n\x04\x04\x08\x02\x02\x12\x03Z\x02\x15\"\x0e\x20\xe6\x98\xaf\xe5\
+    \x90\xa6\xe6\x88\x90\xe5\x8a\x9f\n\n\x0c\n\x05\x04\x08\x02\x02\x05\x12\
+    \x03Z\x02\x06\n\x0c\n\x05\x04\x08\x02\x02\x01\x12\x03Z\t\x10\n\x0c\n\x05\
+    \x04\x08\x02\x02\x03\x12\x03Z\x13\x14\n\x1b\n\x04\x04\x08\x02\x03\x12\
+    \x03[\x02\x15\"\x0e\x20\xe5\xa4\xb1\xe8\xb4\xa5\xe5\x8e\x9f\xe5\x9b\xa0\
+    \n\n\x0c\n\x05\x04\x08\x02\x03\x05\x12\x03[\x02\x08\n\x0c\n\x05\x04\x08\
+    \x02\x03\x01\x12\x03[\t\x0f\n\x0c\n\x05\x04\x08\x02\x03\x03\x12\x03[\x13\
+    \x14\n\n\n\x02\x04\t\x12\x04^\0e\x01\n\n\n\x03\x04\t\x01\x12\x03^\x08\
+    \x11\n\x1e\n\x04\x04\t\x02\0\x12\x03_\x02\x18\"\x11\x20\xe6\xb6\x88\xe6\
+    \x81\xaf\xe5\xba\x8f\xe5\x88\x97\xe5\x8f\xb7\n\n\x0c\n\x05\x04\t\x02\0\
+    \x05\x12\x03_\x02\x08\n\x0c\n\x05\x04\t\x02\0\x01\x12\x03_\t\x0c\n\x0c\n\
+    \x05\x04\t\x02\0\x03\x12\x03_\x16\x17\n\x18\n\x04\x04\t\x02\x01\x12\x03`\
+    \x02\x18\"\x0b\x20\xe5\x8f\x91\xe9\x80\x81\xe6\x96\xb9\n\n\x0c\n\x05\x04\
+    \t\x02\x01\x05\x12\x03`\x02\x08\n\x0c\n\x05\x04\t\x02\x01\x01\x12\x03`\t\
+    \x13\n\x0c\n\x05\x04\t\x02\x01\x03\x12\x03`\x16\x17\n\x1a\n\x04\x04\t\
+    \x02\x02\x12\x03a\x02\x18\"\r\x20\xe8\x81\x8a\xe5\xa4\xa9\xe5\xae\xa4ID\
+    \n\n\x0c\n\x05\x04\t\x02\x02\x05\x12\x03a\x02\x08\n\x0c\n\x05\x04\t\x02\
+    \x02\x01\x12\x03a\t\x10\n\x0c\n\x05\x04\t\x02\x02\x03\x12\x03a\x16\x17\n\
+    \x17\n\x04\x04\t\x02\x03\x12\x03b\x02\x18\"\n\x20\xe6\xb6\x88\xe6\x81\
+    \xafID\n\n\x0c\n\x05\x04\t\x02\x03\x05\x12\x03b\x02\x08\n\x0c\n\x05\x04\
+    \t\x02\x03\x01\x12\x03b\t\x13\n\x0c\n\x05\x04\t\x02\x03\x03\x12\x03b\x16\
+    \x17\n\x1b\n\x04\x04\t\x02\x04\x12\x03c\x02\x18\"\x0e\x20\xe6\xb6\x88\
+    \xe6\x81\xaf\xe5\x86\x85\xe5\xae\xb9\n\n\x0c\n\x05\x04\t\x02\x04\x05\x12\
+    \x03c\x02\x08\n\x0c\n\x05\x04\t\x02\x04\x01\x12\x03c\t\x10\n\x0c\n\x05\
+    \x04\t\x02\x04\x03\x12\x03c\x16\x17\n\x18\n\x04\x04\t\x02\x05\x12\x03d\
+    \x02\x18\"\x0b\x20\xe6\x97\xb6\xe9\x97\xb4\xe6\x88\xb3\n\n\x0c\n\x05\x04\
+    \t\x02\x05\x05\x12\x03d\x02\x08\n\x0c\n\x05\x04\t\x02\x05\x01\x12\x03d\t\
+    \x12\n\x0c\n\x05\x04\t\x02\x05\x03\x12\x03d\x16\x17\n\n\n\x02\x04\n\x12\
+    \x04g\0i\x01\n\n\n\x03\x04\n\x01\x12\x03g\x08\x15\n\x1b\n\x04\x04\n\x02\
+    \0\x12\x03h\x02\x14\"\x0e\x20\xe5\x85\xb3\xe9\x97\xad\xe5\x8e\x9f\xe5\
+    \x9b\xa0\n\n\x0c\n\x05\x04\n\x02\0\x05\x12\x03h\x02\x08\n\x0c\n\x05\x04\
+    \n\x02\0\x01\x12\x03h\t\x0f\n\x0c\n\x05\x04\n\x02\0\x03\x12\x03h\x12\x13\
+    \n\n\n\x02\x04\x0b\x12\x04k\0o\x01\n\n\n\x03\x04\x0b\x01\x12\x03k\x08\
+    \x10\n\x17\n\x04\x04\x0b\x02\0\x12\x03l\x02\x11\"\n\x20\xe7\x94\xa8\xe6\
+    \x88\xb7ID\n\n\x0c\n\x05\x04\x0b\x02\0\x05\x12\x03l\x02\x08\n\x0c\n\x05\
+    \x04\x0b\x02\0\x01\x12\x03l\t\x0c\n\x0c\n\x05\x04\x0b\x02\0\x03\x12\x03l\
+    \x0f\x10\n\x1b\n\x04\x04\x0b\x02\x01\x12\x03m\x02\x1c\"\x0e\x20\xe5\x9c\
+    \xa8\xe7\xba\xbf\xe7\x8a\xb6\xe6\x80\x81\n\n\x0c\n\x05\x04\x0b\x02\x01\
+    \x06\x12\x03m\x02\x10\n\x0c\n\x05\x04\x0b\x02\x01\x01\x12\x03m\x11\x17\n\
+    \x0c\n\x05\x04\x0b\x02\x01\x03\x12\x03m\x1a\x1b\n'\n\x04\x04\x0b\x02\x02\
+    \x12\x03n\x02\x17\"\x1a\x20\xe7\x8a\xb6\xe6\x80\x81\xe5\x8f\x98\xe5\x8c\
+    \x96\xe7\x9a\x84\xe6\x97\xb6\xe9\x97\xb4\xe6\x88\xb3\n\n\x0c\n\x05\x04\
+    \x0b\x02\x02\x05\x12\x03n\x02\x08\n\x0c\n\x05\x04\x0b\x02\x02\x01\x12\
+    \x03n\t\x12\n\x0c\n\x05\x04\x0b\x02\x02\x03\x12\x03n\x15\x16\nG\n\x02\
+    \x04\x0c\x12\x04r\0t\x01\x1a;\x20PRESENCE_QUERY\x20\xe4\xb8\x8d\xe6\x8c\
+    \x87\xe5\xae\x9a\x20uids\x20\xe6\x97\xb6\xe6\x9f\xa5\xe8\xaf\xa2\xe6\x89\
+    \x80\xe6\x9c\x89\xe5\x9c\xa8\xe7\xba\xbf\xe7\x94\xa8\xe6\x88\xb7\n\n\n\n\
+    \x03\x04\x0c\x01\x12\x03r\x08\x17\n\x17\n\x04\x04\x0c\x02\0\x12\x03s\x02\
+    \x1b\"\n\x20\xe7\x94\xa8\xe6\x88\xb7ID\n\n\x0c\n\x05\x04\x0c\x02\0\x04\
+    \x12\x03s\x02\n\n\x0c\n\x05\x04\x0c\x02\0\x05\x12\x03s\x0b\x11\n\x0c\n\
+    \x05\x04\x0c\x02\0\x01\x12\x03s\x12\x16\n\x0c\n\x05\x04\x0c\x02\0\x03\
+    \x12\x03s\x19\x1a\n\n\n\x02\x04\r\x12\x04v\0x\x01\n\n\n\x03\x04\r\x01\
+    \x12\x03v\x08\x14\n\x0b\n\x04\x04\r\x02\0\x12\x03w\x02\"\n\x0c\n\x05\x04\
+    \r\x02\0\x04\x12\x03w\x02\n\n\x0c\n\x05\x04\r\x02\0\x06\x12\x03w\x0b\x13\
+    \n\x0c\n\x05\x04\r\x02\0\x01\x12\x03w\x14\x1d\n\x0c\n\x05\x04\r\x02\0\
+    \x03\x12\x03w\x20!b\x06proto3\
 ";
 
 static file_descriptor_proto_lazy: ::protobuf::rt::LazyV2<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::rt::LazyV2::INIT;
@@ -1,5 +1,8 @@
+#[allow(renamed_and_removed_lints, unused_parens, mismatched_lifetime_syntaxes)]
 mod chat_room;
 
-pub use chat_room::{Action, Package, ConnectedReply, MsgToUser};
-
-
+pub use chat_room::{
+    Action, ConnectedReply, Login, LoginFailedReply, MsgAck, MsgToRoom, MsgToUser, Package,
+    Presence, PresenceList, PresenceRequest, PresenceStatus, Receipt, RoomReply, RoomRequest,
+    ServerClosing,
+};
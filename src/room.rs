@@ -0,0 +1,136 @@
+use crate::{IMError, Result};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[derive(Clone)]
+pub struct Room {
+    room_id: u64,
+    name: String,
+    owner_uid: u64,
+    members: HashSet<u64>,
+    last_message_id: u64, // 聊天室内的消息ID从 1 开始递增, 与单聊消息ID互不相关
+}
+
+impl Room {
+    pub fn get_room_id(&self) -> u64 {
+        self.room_id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_owner_uid(&self) -> u64 {
+        self.owner_uid
+    }
+
+    pub fn is_member(&self, uid: u64) -> bool {
+        self.members.contains(&uid)
+    }
+
+    pub fn members(&self) -> Vec<u64> {
+        self.members.iter().copied().collect()
+    }
+}
+
+/// 管理聊天室及其成员, 成员以 uid 记录, 与用户是否在线无关.
+pub struct RoomManager {
+    last_room_id: AtomicU64,
+    rooms: HashMap<u64, Room>, // key => room_id, value => room
+}
+
+impl Default for RoomManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RoomManager {
+    pub fn new() -> RoomManager {
+        RoomManager {
+            last_room_id: AtomicU64::new(1),
+            rooms: HashMap::new(),
+        }
+    }
+
+    /// 创建聊天室, 创建者自动成为成员.
+    pub fn create(&mut self, name: &str, owner_uid: u64) -> Room {
+        let room_id = self.last_room_id.fetch_add(1, Ordering::SeqCst);
+        let mut members = HashSet::new();
+        members.insert(owner_uid);
+        let room = Room {
+            room_id,
+            name: name.to_string(),
+            owner_uid,
+            members,
+            last_message_id: 0,
+        };
+        self.rooms.insert(room_id, room.clone());
+        room
+    }
+
+    pub fn join(&mut self, room_id: u64, uid: u64) -> Result<()> {
+        match self.rooms.get_mut(&room_id) {
+            Some(room) => {
+                room.members.insert(uid);
+                Ok(())
+            }
+            None => Err(IMError::RoomNotFound(room_id)),
+        }
+    }
+
+    /// 离开聊天室, 最后一个成员离开后聊天室被删除.
+    pub fn leave(&mut self, room_id: u64, uid: u64) -> Result<()> {
+        let room = match self.rooms.get_mut(&room_id) {
+            Some(v) => v,
+            None => return Err(IMError::RoomNotFound(room_id)),
+        };
+        if !room.members.remove(&uid) {
+            return Err(IMError::NotRoomMember(room_id));
+        }
+        if room.members.is_empty() {
+            self.rooms.remove(&room_id);
+        }
+        Ok(())
+    }
+
+    pub fn load(&self, room_id: u64) -> Option<Room> {
+        self.rooms.get(&room_id).cloned()
+    }
+
+    /// 聊天室中除 uid 以外的成员, uid 不是成员时返回错误.
+    pub fn other_members(&self, room_id: u64, uid: u64) -> Result<Vec<u64>> {
+        let room = match self.rooms.get(&room_id) {
+            Some(v) => v,
+            None => return Err(IMError::RoomNotFound(room_id)),
+        };
+        if !room.is_member(uid) {
+            return Err(IMError::NotRoomMember(room_id));
+        }
+        Ok(room
+            .members
+            .iter()
+            .copied()
+            .filter(|&member| member != uid)
+            .collect())
+    }
+
+    /// 分配聊天室内的下一个消息ID, 同时返回除 uid 以外的成员, uid 不是成员时返回错误.
+    pub fn next_message(&mut self, room_id: u64, uid: u64) -> Result<(u64, Vec<u64>)> {
+        let room = match self.rooms.get_mut(&room_id) {
+            Some(v) => v,
+            None => return Err(IMError::RoomNotFound(room_id)),
+        };
+        if !room.is_member(uid) {
+            return Err(IMError::NotRoomMember(room_id));
+        }
+        room.last_message_id += 1;
+        let members = room
+            .members
+            .iter()
+            .copied()
+            .filter(|&member| member != uid)
+            .collect();
+        Ok((room.last_message_id, members))
+    }
+}
@@ -1,107 +1,386 @@
+use crate::delivery::Delivery;
 use crate::proto::{
-    Action::CONNECTED, Action::HEARTBEAT, Action::MSG_TO_USER, ConnectedReply, MsgToUser, Package,
+    Action, Action::CONNECTED, Action::CREATE_ROOM, Action::DELIVERED_ACK, Action::HEARTBEAT,
+    Action::JOIN_ROOM, Action::LEAVE_ROOM, Action::LOGIN, Action::LOGIN_FAILED, Action::MSG_ACK,
+    Action::MSG_TO_ROOM, Action::MSG_TO_USER, Action::PRESENCE, Action::PRESENCE_LIST,
+    Action::PRESENCE_QUERY, Action::PRESENCE_SUBSCRIBE, Action::PRESENCE_UNSUBSCRIBE,
+    Action::READ_RECEIPT, Action::ROOM_REPLY, Action::SERVER_CLOSING, ConnectedReply, Login,
+    LoginFailedReply, MsgAck, MsgToRoom, MsgToUser, Package, Presence, PresenceList,
+    PresenceRequest, PresenceStatus, Receipt, RoomReply, RoomRequest, ServerClosing,
 };
 use crate::wheel_timer::system_time_unix;
-use crate::SessionManager;
-use crate::{Connection, WheelTimer};
+#[cfg(feature = "tls")]
+use crate::TlsAcceptor;
+#[cfg(feature = "websocket")]
+use crate::WebSocketTransport;
+use crate::{
+    Authenticator, LoginPolicy, MemoryOfflineStore, MessageStore, OfflineStore, PresenceManager,
+    RecentSeqs, RetransmitPolicy, RoomManager, ServerConfig, Session, SessionManager, WriteQueue,
+    MIN_PROTOCOL_VERSION, PROTOCOL_VERSION,
+};
+use crate::{Clock, Codec, Connection, IMError, SystemClock, Transport, WheelTimer};
 use crate::{MessageSystem, TimerTask};
-use log::debug;
+use log::{debug, info, warn};
 use protobuf::Message;
-use std::net::TcpListener;
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream};
 use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use std::sync::Mutex;
+use std::sync::{Condvar, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-/// Server 链路read空闲检测, 默认60秒, 60秒没有读取到任何数据强制关闭连接.
-const READER_IDLE_TIME_SECONDS: u64 = 60;
+#[cfg(feature = "event-loop")]
+mod event_loop;
 
 pub struct IMServer {
-    session_manager: Arc<Mutex<SessionManager>>,
+    session_manager: Arc<SessionManager>,
     message_system: Arc<Mutex<MessageSystem>>,
+    offline_store: Arc<Mutex<Box<dyn OfflineStore>>>,
+    room_manager: Arc<Mutex<RoomManager>>,
+    presence: Arc<Mutex<PresenceManager>>,
+    authenticator: Arc<dyn Authenticator>,
     timer: WheelTimer,
+    retransmit_policy: RetransmitPolicy,
+    shutdown: ShutdownHandle,
+    config: ServerConfig,
+    #[cfg(feature = "tls")]
+    tls: Option<TlsAcceptor>,
 }
 
 impl IMServer {
-    pub fn new() -> IMServer {
-        IMServer {
-            session_manager: Arc::new(Mutex::new(SessionManager::new())),
+    /// 按配置创建服务端, 时间轮参数非法时返回 IMError::InvalidConfig.
+    pub fn new(
+        config: ServerConfig,
+        authenticator: Box<dyn Authenticator>,
+    ) -> crate::Result<IMServer> {
+        let session_manager = Arc::new(SessionManager::new());
+        let timer = new_timer(&config, Arc::new(SystemClock))?;
+        Ok(IMServer {
+            session_manager: session_manager.clone(),
             message_system: Arc::new(Mutex::new(MessageSystem::new())),
-            timer: WheelTimer::new(100, 12).unwrap(),
-        }
-    }
-
-    // Run the server listening on the given address
-    pub fn run(&mut self, address: &str) {
-        let listener = TcpListener::bind(address).unwrap();
-        for stream in listener.incoming() {
-            match stream {
-                Ok(stream) => {
-                    let connection = Connection::new(stream);
-                    let session = self
-                        .session_manager
-                        .lock()
-                        .unwrap()
-                        .new_session(connection.clone());
-
-                    debug!(
-                        "new conn uid = {}, remote_address = {}",
-                        session.get_uid(),
-                        connection.remote_address()
-                    );
-                    // read idle detect
-                    self.init_reader_idle_timeout(session.get_uid(), connection.clone());
-
-                    let mut handler = Handler::new(
-                        session.get_uid(),
-                        connection,
-                        self.session_manager.clone(),
-                        self.message_system.clone(),
-                    );
-                    thread::spawn(move || handler.run());
+            offline_store: Arc::new(Mutex::new(Box::new(MemoryOfflineStore::new()))),
+            room_manager: Arc::new(Mutex::new(RoomManager::new())),
+            presence: Arc::new(Mutex::new(PresenceManager::new(session_manager))),
+            authenticator: Arc::from(authenticator),
+            timer,
+            retransmit_policy: RetransmitPolicy::default(),
+            shutdown: ShutdownHandle::default(),
+            config,
+            #[cfg(feature = "tls")]
+            tls: None,
+        })
+    }
+
+    /// 使用持久化存储保存消息, 消息ID从最后一条持久化的消息继续分配.
+    pub fn set_message_store(&mut self, store: Box<dyn MessageStore>) {
+        self.message_system = Arc::new(Mutex::new(MessageSystem::with_store(store)));
+    }
+
+    /// 替换默认的内存离线队列, 例如使用 FileOfflineStore 在重启后保留离线消息.
+    pub fn set_offline_store(&mut self, store: Box<dyn OfflineStore>) {
+        self.offline_store = Arc::new(Mutex::new(store));
+    }
+
+    /// 推送给接收方的消息在未收到送达确认时的重传策略.
+    pub fn set_retransmit_policy(&mut self, policy: RetransmitPolicy) {
+        self.retransmit_policy = policy;
+    }
+
+    /// 读空闲检测、会话恢复的宽限期和连接的读写时间使用 clock, 需要在 run 之前调用.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.session_manager.set_clock(clock.clone());
+        self.timer.stop();
+        // 同样的配置已经在 new 中创建过时间轮, 不会失败
+        self.timer = new_timer(&self.config, clock).unwrap();
+    }
+
+    /// 新连接先完成 TLS 握手再开始协议握手, 不再接受明文连接.
+    #[cfg(feature = "tls")]
+    pub fn set_tls(&mut self, acceptor: TlsAcceptor) {
+        self.tls = Some(acceptor);
+    }
+
+    pub fn get_config(&self) -> &ServerConfig {
+        &self.config
+    }
+
+    /// 用于在其他线程关闭服务端, 调用 shutdown 后 run 会在连接排空后返回.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.shutdown.clone()
+    }
+
+    // Run the server listening on the configured address, 监听失败时返回错误
+    pub fn run(&mut self) -> crate::Result<()> {
+        let listener = TcpListener::bind(&self.config.listen_address)?;
+        self.shutdown.listening(listener.local_addr()?);
+        let websocket = self.bind_websocket()?;
+        let delivery = Delivery::new(
+            self.session_manager.clone(),
+            self.offline_store.clone(),
+            self.timer.clone(),
+            self.retransmit_policy,
+        );
+        let handlers = Handlers::default();
+        thread::scope(|s| {
+            if let Some(websocket) = websocket {
+                s.spawn(|| self.serve(websocket, &delivery, &handlers, true));
+            }
+            self.serve(listener, &delivery, &handlers, false);
+        });
+        self.notify_closing();
+        self.drain(&handlers);
+        self.close(&delivery);
+        Ok(())
+    }
+
+    // 每个连接一个线程, 直到服务端关闭. websocket 为 true 时接入的是 WebSocket 连接
+    fn serve(
+        &self,
+        listener: TcpListener,
+        delivery: &Delivery,
+        handlers: &Handlers,
+        websocket: bool,
+    ) {
+        while !self.shutdown.is_shutdown() {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    if self.shutdown.is_shutdown() {
+                        break;
+                    }
+                    let ret = match websocket {
+                        #[cfg(feature = "websocket")]
+                        true => self.new_websocket_connection(stream),
+                        _ => self.new_connection(stream),
+                    };
+                    let connection = match ret {
+                        Ok(v) => v,
+                        Err(e) => {
+                            warn!("Connection failed: {}", e);
+                            continue;
+                        }
+                    };
+                    debug!("new conn remote_address = {}", connection.remote_address());
+
+                    let id = handlers.add(connection.clone());
+                    let mut handler = Handler::new(connection, self, delivery.clone());
+                    // 浏览器不发送协议握手
+                    handler.handshaken = websocket;
+                    let handlers = handlers.clone();
+                    thread::spawn(move || {
+                        handler.run();
+                        handlers.remove(id);
+                    });
                 }
                 Err(e) => {
-                    panic!("Connection failed: {}", e)
+                    warn!("Connection failed: {}", e)
                 }
             }
         }
+        // 返回时关闭 listener, 停止接收新连接
     }
 
-    fn init_reader_idle_timeout(&mut self, uid: u64, connection: Connection) {
-        let timeout_task = ReaderIdleTimeoutTask::new(
-            uid,
-            connection,
-            self.timer.clone(),
-            self.session_manager.clone(),
-        );
-        self.timer.new_timeout(
-            Box::new(timeout_task),
-            Duration::new(READER_IDLE_TIME_SECONDS, 0),
-        );
+    // 先等发送队列写完, 再等待 Handler 线程退出, 超时后强制关闭剩余的连接.
+    // 强制关闭时仍未写出的推送消息没有确认送达, 会由 flush_unacked 转入离线队列
+    fn drain(&self, handlers: &Handlers) {
+        let deadline = Instant::now() + self.config.drain_timeout();
+        for session in self.session_manager.sessions() {
+            session.flush(deadline.saturating_duration_since(Instant::now()));
+        }
+        if !handlers.wait_empty(deadline.saturating_duration_since(Instant::now())) {
+            handlers.shutdown_all();
+            handlers.wait_empty(Duration::from_secs(1));
+        }
+    }
+
+    fn new_connection(&self, stream: TcpStream) -> crate::Result<Connection> {
+        let transport = self.new_transport(stream)?;
+        Ok(self.with_transport(transport))
+    }
+
+    // 配置了 websocket_address 时监听浏览器的 WebSocket 连接
+    fn bind_websocket(&self) -> crate::Result<Option<TcpListener>> {
+        let address = self.config.websocket_address.as_ref();
+        let address = match address.filter(|_| cfg!(feature = "websocket")) {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        let listener = TcpListener::bind(address)?;
+        self.shutdown.listening(listener.local_addr()?);
+        Ok(Some(listener))
+    }
+
+    // WebSocket 连接与 TCP 连接共用会话与消息系统, 同样可以使用 TLS(wss)
+    #[cfg(feature = "websocket")]
+    fn new_websocket_connection(&self, stream: TcpStream) -> crate::Result<Connection> {
+        let transport = WebSocketTransport::accept(self.new_transport(stream)?, self.codec())?;
+        Ok(self.with_transport(Box::new(transport)))
+    }
+
+    fn codec(&self) -> Codec {
+        Codec::new(self.config.max_frame_size, self.config.content_max_len)
+    }
+
+    // 配置了证书时先完成 TLS 握手
+    fn new_transport(&self, stream: TcpStream) -> crate::Result<Box<dyn Transport>> {
+        #[cfg(feature = "tls")]
+        if let Some(acceptor) = &self.tls {
+            return Ok(Box::new(acceptor.accept(stream)?));
+        }
+        Ok(Box::new(stream))
+    }
+
+    fn with_transport(&self, transport: Box<dyn Transport>) -> Connection {
+        let mut connection = Connection::with_transport(transport, self.codec());
+        connection.set_max_protocol_violations(self.config.max_protocol_violations);
+        connection.set_clock(self.timer.get_clock());
+        connection
+    }
+
+    // 通知所有在线用户服务端即将关闭
+    fn notify_closing(&self) {
+        let sessions = self.session_manager.sessions();
+        info!("Server closing, notify {} sessions", sessions.len());
+        let mut notice = ServerClosing::new();
+        notice.set_reason("server shutdown".to_string());
+        let content = notice.write_to_bytes().unwrap();
+        for session in sessions {
+            let mut package = Package::new();
+            package.set_action(SERVER_CLOSING);
+            package.set_content(content.clone());
+            let _ = session.send(package);
+        }
+    }
+
+    // 未确认送达的消息转入离线队列, 刷新存储并停止定时器
+    fn close(&self, delivery: &Delivery) {
+        delivery.flush_unacked();
+        if let Err(e) = self.offline_store.lock().unwrap().flush() {
+            warn!("离线消息刷新失败: {}", e);
+        }
+        if let Err(e) = self.message_system.lock().unwrap().flush() {
+            warn!("消息刷新失败: {}", e);
+        }
+        self.timer.stop();
+        info!("Server stopped");
+    }
+}
+
+/// 关闭服务端的句柄, 可以在任意线程调用.
+#[derive(Clone, Default)]
+pub struct ShutdownHandle {
+    requested: Arc<AtomicBool>,
+    addresses: Arc<Mutex<Vec<SocketAddr>>>, // 监听地址, 用于唤醒阻塞在 accept 上的 run
+}
+
+impl ShutdownHandle {
+    /// 停止接收新连接, 通知在线用户并在排空连接后让 run 返回.
+    pub fn shutdown(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+        let addresses = self.addresses.lock().unwrap().clone();
+        for address in addresses {
+            wake(address);
+        }
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    /// 已经开始监听的地址, 按绑定的顺序排列, 监听端口为0时可以由此得到实际的端口.
+    pub fn get_addresses(&self) -> Vec<SocketAddr> {
+        self.addresses.lock().unwrap().clone()
+    }
+
+    // 先记录监听地址再检查关闭标记: shutdown 在记录之前读取了地址时, 由这里唤醒 accept
+    fn listening(&self, address: SocketAddr) {
+        self.addresses.lock().unwrap().push(address);
+        if self.is_shutdown() {
+            wake(address);
+        }
+    }
+}
+
+// 连接一次监听地址, 唤醒阻塞在 accept 上的线程
+fn wake(mut address: SocketAddr) {
+    // 监听在 0.0.0.0 时通过回环地址唤醒
+    if address.ip().is_unspecified() {
+        match address {
+            SocketAddr::V4(_) => address.set_ip(Ipv4Addr::LOCALHOST.into()),
+            SocketAddr::V6(_) => address.set_ip(Ipv6Addr::LOCALHOST.into()),
+        }
+    }
+    let _ = TcpStream::connect_timeout(&address, Duration::from_secs(1));
+}
+
+// 正在运行的 Handler 线程, 关闭服务端时用于等待连接退出或强制关闭连接
+#[derive(Clone, Default)]
+struct Handlers {
+    last_id: Arc<AtomicU64>,
+    connections: Arc<(Mutex<HashMap<u64, Connection>>, Condvar)>,
+}
+
+impl Handlers {
+    fn add(&self, connection: Connection) -> u64 {
+        let id = self.last_id.fetch_add(1, Ordering::SeqCst);
+        let (lock, _) = self.connections.deref();
+        lock.lock().unwrap().insert(id, connection);
+        id
+    }
+
+    fn remove(&self, id: u64) {
+        let (lock, condvar) = self.connections.deref();
+        lock.lock().unwrap().remove(&id);
+        condvar.notify_all();
+    }
+
+    // 等待所有 Handler 退出, 超时返回 false
+    fn wait_empty(&self, timeout: Duration) -> bool {
+        let (lock, condvar) = self.connections.deref();
+        let guard = lock.lock().unwrap();
+        let (guard, _) = condvar
+            .wait_timeout_while(guard, timeout, |connections| !connections.is_empty())
+            .unwrap();
+        guard.is_empty()
+    }
+
+    fn shutdown_all(&self) {
+        let (lock, _) = self.connections.deref();
+        let mut connections = lock.lock().unwrap();
+        debug!("强制关闭 {} 个连接", connections.len());
+        for connection in connections.values_mut() {
+            connection.shutdown();
+        }
     }
 }
 
 #[derive(Clone)]
 struct ReaderIdleTimeoutTask {
-    uid: u64,
+    session: Arc<Session>,
+    idle_timeout: Duration,
     connection: Connection,
     timer: WheelTimer,
-    session_manager: Arc<Mutex<SessionManager>>,
+    session_manager: Arc<SessionManager>,
+    presence: Arc<Mutex<PresenceManager>>,
 }
 
 impl ReaderIdleTimeoutTask {
     fn new(
-        uid: u64,
+        session: Arc<Session>,
+        idle_timeout: Duration,
         connection: Connection,
         timer: WheelTimer,
-        session_manager: Arc<Mutex<SessionManager>>,
+        session_manager: Arc<SessionManager>,
+        presence: Arc<Mutex<PresenceManager>>,
     ) -> ReaderIdleTimeoutTask {
         ReaderIdleTimeoutTask {
-            uid,
+            session,
+            idle_timeout,
             connection,
             timer,
             session_manager,
+            presence,
         }
     }
 }
@@ -112,134 +391,620 @@ impl TimerTask for ReaderIdleTimeoutTask {
             return;
         }
         let last_read_time = self.connection.get_last_read_time();
-        let next_delay =
-            (READER_IDLE_TIME_SECONDS * 1000) as i64 - (system_time_unix() - last_read_time) as i64;
+        let idle = self
+            .timer
+            .get_clock()
+            .now()
+            .saturating_duration_since(last_read_time);
+        let next_delay = self.idle_timeout.as_millis() as i64 - idle.as_millis() as i64;
         if next_delay <= 0 {
             debug!("trigger read idle timeout check.");
             // shutdown the connection.
             self.connection.shutdown();
             // remove session
-            self.session_manager.lock().unwrap().remove(self.uid);
+            if let Some(session) = self.session_manager.remove_session(&self.session) {
+                session.close();
+                // 通知订阅者用户已离线
+                let uid = session.get_uid();
+                self.presence.lock().unwrap().changed(uid);
+            }
         } else {
             // set a new timeout with shorter delay.
-            self.timer.new_timeout(
+            let ret = self.timer.new_timeout(
                 Box::new(self.deref().clone()),
                 Duration::from_millis(next_delay as u64),
             );
+            match ret {
+                Ok(timeout) => self.session.set_idle_timeout(timeout),
+                Err(e) => warn!(
+                    "用户 uid = {} 无法继续读空闲检测: {}",
+                    self.session.get_uid(),
+                    e
+                ),
+            }
         }
     }
 }
 
 struct Handler {
     uid: u64,
+    session_id: String,
+    session: Option<Arc<Session>>, // 登录之后的会话, 所有写入都经过会话的发送队列
+    resumed: bool,                 // 恢复了断线前的会话
     connection: Connection,
-    session_manager: Arc<Mutex<SessionManager>>,
+    handshaken: bool, // 已经确定协议版本, 不需要再读取握手数据
+    session_manager: Arc<SessionManager>,
     message_system: Arc<Mutex<MessageSystem>>,
+    offline_store: Arc<Mutex<Box<dyn OfflineStore>>>,
+    room_manager: Arc<Mutex<RoomManager>>,
+    presence: Arc<Mutex<PresenceManager>>,
+    delivery: Delivery,
+    authenticator: Arc<dyn Authenticator>,
+    timer: WheelTimer,
+    config: ServerConfig,
+    recent_seqs: Arc<Mutex<RecentSeqs>>, // 会话最近的消息, 登录后与会话共享
+    write_queue: Option<WriteQueue>, // 事件循环创建的发送队列, 登录时交给会话; 没有时登录后启动写线程
 }
 
 impl Handler {
-    fn new(
-        uid: u64,
-        connection: Connection,
-        session_manager: Arc<Mutex<SessionManager>>,
-        message_system: Arc<Mutex<MessageSystem>>,
-    ) -> Handler {
+    fn new(connection: Connection, server: &IMServer, delivery: Delivery) -> Handler {
         Handler {
-            uid,
+            uid: 0,
+            session_id: String::new(),
+            session: None,
+            resumed: false,
             connection,
-            session_manager,
-            message_system,
+            handshaken: false,
+            session_manager: server.session_manager.clone(),
+            message_system: server.message_system.clone(),
+            offline_store: server.offline_store.clone(),
+            room_manager: server.room_manager.clone(),
+            presence: server.presence.clone(),
+            delivery,
+            authenticator: server.authenticator.clone(),
+            timer: server.timer.clone(),
+            config: server.config.clone(),
+            recent_seqs: Arc::default(),
+            write_queue: None,
         }
     }
 
+    // 每个连接独占一个线程, 阻塞读取数据包
     fn run(&mut self) {
-        self.connected_reply();
+        let timeout = Some(self.config.login_timeout());
+        let write_timeout = self.config.write_timeout();
+        let handshaken = self.handshaken;
+        let ret = self
+            .connection
+            .set_read_timeout(timeout)
+            .and_then(|_| match handshaken {
+                true => Ok(self.connection.get_codec().get_version()),
+                false => self.connection.accept_handshake(write_timeout),
+            })
+            .and_then(|_| self.connection.read_package());
+        let package = match ret {
+            Ok(p) => p,
+            Err(e) => {
+                debug!("登录失败: {}", e);
+                self.connection.shutdown();
+                return;
+            }
+        };
+        if !self.on_login(package) || self.connection.set_read_timeout(None).is_err() {
+            self.close();
+            return;
+        }
         loop {
             match self.connection.read_package() {
-                Ok(p) => match p.action {
-                    HEARTBEAT => {
-                        debug!(
-                            "收到 uid = {} 心跳消息：{}",
-                            self.uid,
-                            String::from_utf8_lossy(p.get_content())
-                        );
-                        let mut package = Package::new();
-                        package.set_action(HEARTBEAT);
-                        package.set_content("PONG".as_bytes().to_vec());
-                        self.connection
-                            .write_package(package, Duration::new(10, 0))
-                            .unwrap();
-                    }
-                    MSG_TO_USER => {
-                        let ret = MsgToUser::parse_from_bytes(p.get_content());
-                        match ret {
-                            Ok(v) => self.msg_to_user(v),
-                            Err(_) => {
-                                self.connection.shutdown();
-                                return;
-                            }
-                        }
+                Ok(p) => {
+                    if !self.on_package(p) {
+                        self.close();
+                        return;
                     }
-                    _ => {
-                        debug!("Unknown package action.")
-                    }
-                },
-                Err(_) => {
-                    debug!("用户 uid = {} 离线.", self.uid);
-                    self.connection.set_closed();
-                    self.session_manager.lock().unwrap().remove(self.uid);
+                }
+                // 连接断开, 或者违规次数过多等无法继续读取的错误
+                Err(e) => {
+                    debug!("连接读取失败: {}", e);
+                    self.close();
                     return;
                 }
             }
         }
     }
 
-    fn connected_reply(&mut self) {
-        let option = self.session_manager.lock().unwrap().load(self.uid);
-        match option {
-            Some(session) => {
-                let mut reply = ConnectedReply::new();
-                reply.set_uid(session.get_uid());
-                reply.set_session_id(session.get_session_id());
-                let content = reply.write_to_bytes().unwrap();
+    /// 处理连接的第一个数据包, 认证成功后回复 CONNECTED 并投递离线消息.
+    /// 返回 false 时需要关闭连接.
+    fn on_login(&mut self, package: Package) -> bool {
+        let offline_messages = match self.login(package) {
+            Some(v) => v,
+            None => return false,
+        };
+        // read idle detect
+        self.init_reader_idle_timeout();
+        self.connected_reply();
+        self.presence.lock().unwrap().changed(self.uid);
+        self.deliver_offline_messages(offline_messages);
+        true
+    }
 
+    /// 处理登录之后收到的数据包, 返回 false 时需要关闭连接.
+    /// 无法解析或不应由客户端发送的数据包记为协议违规, 超过上限时关闭连接.
+    fn on_package(&mut self, p: Package) -> bool {
+        match p.action {
+            HEARTBEAT => {
+                debug!(
+                    "收到 uid = {} 心跳消息：{}",
+                    self.uid,
+                    String::from_utf8_lossy(p.get_content())
+                );
                 let mut package = Package::new();
-                package.set_action(CONNECTED);
-                package.set_content(content);
-                self.connection
-                    .write_package(package, Duration::from_secs(10))
-                    .unwrap();
+                package.set_action(HEARTBEAT);
+                package.set_content("PONG".as_bytes().to_vec());
+                self.send(package);
             }
-            None => {
-                // nothing to do
+            MSG_TO_USER => match parse::<MsgToUser>(&p) {
+                Ok(v) => self.msg_to_user(v),
+                Err(e) => return self.violation(e),
+            },
+            CREATE_ROOM | JOIN_ROOM | LEAVE_ROOM => match parse::<RoomRequest>(&p) {
+                Ok(v) => self.room_request(p.action, v),
+                Err(e) => return self.violation(e),
+            },
+            MSG_TO_ROOM => match parse::<MsgToRoom>(&p) {
+                Ok(v) => self.msg_to_room(v),
+                Err(e) => return self.violation(e),
+            },
+            DELIVERED_ACK | READ_RECEIPT => match parse::<Receipt>(&p) {
+                Ok(v) => self.forward_receipt(p.action, v),
+                Err(e) => return self.violation(e),
+            },
+            PRESENCE_QUERY | PRESENCE_SUBSCRIBE | PRESENCE_UNSUBSCRIBE => {
+                match parse::<PresenceRequest>(&p) {
+                    Ok(v) => self.presence_request(p.action, v),
+                    Err(e) => return self.violation(e),
+                }
+            }
+            PRESENCE => match parse::<Presence>(&p) {
+                Ok(v) => return self.set_presence(v),
+                Err(e) => return self.violation(e),
+            },
+            action => {
+                debug!("Unexpected package action {:?}.", action);
+                return self.violation(IMError::MalformedPayload(action));
             }
         }
+        true
     }
 
-    fn msg_to_user(&self, mut mtu_pb: MsgToUser) {
-        let option = self
-            .session_manager
-            .lock()
-            .unwrap()
-            .load(mtu_pb.get_receiver_uid());
-        match option {
-            Some(mut session) => {
-                // 持久化DB，生成消息ID
-                let message_id = self.message_system.lock().unwrap().next_seq();
-                mtu_pb.set_message_id(message_id);
-                mtu_pb.set_sender_uid(self.uid);
-                let content = mtu_pb.write_to_bytes().unwrap();
+    // 记录一次协议违规, 返回 false 时需要关闭连接
+    fn violation(&mut self, e: IMError) -> bool {
+        debug!("用户 uid = {} 协议违规: {}", self.uid, e);
+        match self.connection.protocol_violation() {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("用户 uid = {} 断开连接: {}", self.uid, e);
+                false
+            }
+        }
+    }
+
+    // 服务端主动关闭连接
+    fn close(&mut self) {
+        self.connection.shutdown();
+        self.disconnected();
+    }
+
+    // 连接已断开, 移除会话并停止写线程. 会话在宽限期内保留, 客户端重连时可以恢复
+    fn disconnected(&mut self) {
+        debug!("用户 uid = {} 离线.", self.uid);
+        self.connection.set_closed();
+        // 被重复登录踢掉或已在新连接上恢复的会话不再保留
+        let grace = self.config.session_resume_grace();
+        if let Some(session) = self.session.take() {
+            if grace > Duration::from_secs(0) {
+                self.session_manager.detach(&session, grace);
+            } else {
+                self.session_manager.remove_session(&session);
+            }
+            session.close();
+            self.delivery.disconnected(self.uid);
+            self.presence.lock().unwrap().changed(self.uid);
+        }
+    }
+
+    // 登录之后的数据包放入会话的发送队列, 由写线程或事件循环写出, 不会与其他线程的写入交错
+    fn send(&mut self, package: Package) {
+        // 事件循环中登录之前的回复同样放入发送队列, 不阻塞事件循环
+        let ret = match (self.session.as_ref(), self.write_queue.as_ref()) {
+            (Some(session), _) => session.send(package),
+            (None, Some(write_queue)) => write_queue.push(package),
+            (None, None) => self
+                .connection
+                .write_package(package, self.config.write_timeout()),
+        };
+        if let Err(e) = ret {
+            debug!("用户 uid = {} 发送失败: {}", self.uid, e);
+        }
+    }
+
+    // 对第一个数据包完成登录认证, 认证成功后才会创建Session, 并取出该用户的离线消息.
+    // 携带宽限期内的 session_id 与匹配的 resume_token 时恢复原来的会话, 不再校验用户名密码.
+    fn login(&mut self, package: Package) -> Option<Vec<MsgToUser>> {
+        if package.get_action() != LOGIN {
+            self.login_failed("login required");
+            return None;
+        }
+        let login = match Login::parse_from_bytes(package.get_content()) {
+            Ok(v) => v,
+            Err(_) => {
+                self.login_failed("malformed login");
+                return None;
+            }
+        };
+        let detached = match login.get_session_id() {
+            "" => None,
+            session_id => self
+                .session_manager
+                .resume(session_id, login.get_resume_token()),
+        };
+        let uid = match &detached {
+            Some(v) => v.get_uid(),
+            None => match self.authenticator.authenticate(&login) {
+                Ok(uid) => uid,
+                Err(e) => {
+                    debug!("用户 {} 登录失败: {}", login.get_username(), e);
+                    self.login_failed(&e.to_string());
+                    return None;
+                }
+            },
+        };
+        let device_id = match &detached {
+            Some(v) => v.get_device_id().to_string(),
+            None => login.get_device_id().to_string(),
+        };
+        let session_manager = self.session_manager.clone();
+        let mut user = session_manager.lock_user(uid);
+        // 同一用户重复登录, 按策略踢掉旧的连接
+        let kicked = match self.config.login_policy {
+            LoginPolicy::MultiDevice => user.remove_device(&device_id).into_iter().collect(),
+            LoginPolicy::KickOlder => user.remove_all(),
+        };
+        for old in kicked {
+            debug!("用户 uid = {} 重复登录, 关闭旧连接.", uid);
+            old.close();
+        }
+        let write_queue = match self.write_queue.clone() {
+            Some(v) => v,
+            None => WriteQueue::new(
+                self.config.write_queue_capacity,
+                self.config.overflow_policy,
+            ),
+        };
+        let session = match detached {
+            Some(detached) => {
+                debug!("用户 uid = {} 恢复会话.", uid);
+                self.resumed = true;
+                user.restore_session(detached, self.connection.clone(), write_queue.clone())
+            }
+            None => user.new_session(&device_id, self.connection.clone(), write_queue.clone()),
+        };
+        self.uid = uid;
+        self.session_id = session.get_session_id();
+        // 恢复会话时沿用之前收到的消息序列号, 客户端重发断线前的消息时不会重复投递
+        self.recent_seqs = session.get_recent_seqs();
+        self.session = Some(session);
+
+        // 持有用户所在分片的锁时取出离线消息, 保证不会有消息在上线过程中遗留在队列里
+        let messages = match self.offline_store.lock().unwrap().take(uid) {
+            Ok(messages) => messages,
+            Err(e) => {
+                warn!("读取用户 uid = {} 的离线消息失败: {}", uid, e);
+                Vec::new()
+            }
+        };
+        drop(user);
+        // 释放分片锁之后再启动写线程, 事件循环的发送队列由事件循环写出
+        if self.write_queue.is_none() {
+            write_queue.spawn_writer(self.connection.clone(), self.config.write_timeout());
+        }
+        Some(messages)
+    }
+
+    fn deliver_offline_messages(&mut self, messages: Vec<MsgToUser>) {
+        if !messages.is_empty() {
+            debug!(
+                "投递用户 uid = {} 的 {} 条离线消息",
+                self.uid,
+                messages.len()
+            );
+        }
+        // 连接断开时未送达的消息会重新回到离线队列
+        for msg in messages {
+            self.delivery.push(msg);
+        }
+    }
+
+    fn login_failed(&mut self, reason: &str) {
+        let mut reply = LoginFailedReply::new();
+        reply.set_reason(reason.to_string());
+        let content = reply.write_to_bytes().unwrap();
+
+        let mut package = Package::new();
+        package.set_action(LOGIN_FAILED);
+        package.set_content(content);
+        self.send(package);
+    }
+
+    fn init_reader_idle_timeout(&mut self) {
+        let session = self.session.clone().unwrap();
+        let timeout_task = ReaderIdleTimeoutTask::new(
+            session.clone(),
+            self.config.reader_idle_timeout(),
+            self.connection.clone(),
+            self.timer.clone(),
+            self.session_manager.clone(),
+            self.presence.clone(),
+        );
+        let ret = self
+            .timer
+            .new_timeout(Box::new(timeout_task), self.config.reader_idle_timeout());
+        match ret {
+            Ok(timeout) => session.set_idle_timeout(timeout),
+            Err(e) => warn!("用户 uid = {} 无法开启读空闲检测: {}", self.uid, e),
+        }
+    }
 
+    fn connected_reply(&mut self) {
+        let mut reply = ConnectedReply::new();
+        reply.set_uid(self.uid);
+        reply.set_session_id(self.session_id.clone());
+        reply.set_version(self.connection.get_codec().get_version() as u32);
+        reply.set_supported_versions(
+            (MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION)
+                .map(u32::from)
+                .collect(),
+        );
+        reply.set_resumed(self.resumed);
+        if let Some(session) = self.session.as_ref() {
+            reply.set_resume_token(session.get_resume_token());
+        }
+        let content = reply.write_to_bytes().unwrap();
+
+        let mut package = Package::new();
+        package.set_action(CONNECTED);
+        package.set_content(content);
+        self.send(package);
+    }
+
+    fn msg_to_user(&mut self, mut mtu_pb: MsgToUser) {
+        // 客户端未收到 MSG_ACK 时会重传, 重复的消息只回复确认
+        let seq = mtu_pb.get_seq();
+        let recent = self.recent_seqs.lock().unwrap().get(seq);
+        if let Some(message_id) = recent {
+            debug!("重复的消息 seq = {}, message_id = {}", seq, message_id);
+            self.msg_ack(seq, message_id, 0);
+            return;
+        }
+        // 持久化DB，生成消息ID
+        mtu_pb.set_sender_uid(self.uid);
+        let ret = self.message_system.lock().unwrap().save(&mut mtu_pb);
+        let message_id = match ret {
+            Ok(v) => v,
+            Err(e) => {
+                warn!("消息持久化失败: {}", e);
+                return;
+            }
+        };
+        self.recent_seqs.lock().unwrap().insert(seq, message_id);
+        self.msg_ack(seq, message_id, 0);
+        // 同步给发送方的其他设备, 发给自己的消息已经由投递推送到所有设备
+        if mtu_pb.get_receiver_uid() != self.uid {
+            let content = mtu_pb.write_to_bytes().unwrap();
+            for session in self.other_devices() {
                 let mut package = Package::new();
                 package.set_action(MSG_TO_USER);
-                package.set_content(content);
+                package.set_content(content.clone());
+                let _ = session.send(package);
+            }
+        }
+        self.delivery.push(mtu_pb);
+    }
 
-                let connection = session.borrow_connection();
-                let _ = connection.write_package(package, Duration::new(10, 0));
+    // 当前用户在其他设备上的会话
+    fn other_devices(&self) -> Vec<Arc<Session>> {
+        let mut sessions = self.session_manager.load_all(self.uid);
+        sessions.retain(|session| session.get_session_id() != self.session_id);
+        sessions
+    }
+
+    // 回复发送方: 消息已被服务端接收, 携带客户端 seq 与分配的消息ID
+    // 聊天室消息的确认带上 room_id, 与单聊消息的ID区分
+    fn msg_ack(&mut self, seq: u64, message_id: u64, room_id: u64) {
+        let mut ack = MsgAck::new();
+        ack.set_seq(seq);
+        ack.set_message_id(message_id);
+        ack.set_room_id(room_id);
+        ack.set_timestamp(system_time_unix());
+
+        let mut package = Package::new();
+        package.set_action(MSG_ACK);
+        package.set_content(ack.write_to_bytes().unwrap());
+        self.send(package);
+    }
+
+    // 查询或订阅用户的在线状态, 查询和订阅都回复这些用户当前的状态
+    fn presence_request(&mut self, action: Action, request: PresenceRequest) {
+        let uids = request.get_uids();
+        let presences = {
+            let mut presence = self.presence.lock().unwrap();
+            match action {
+                PRESENCE_SUBSCRIBE => presence.subscribe(self.uid, uids),
+                PRESENCE_UNSUBSCRIBE => {
+                    presence.unsubscribe(self.uid, uids);
+                    return;
+                }
+                _ => presence.query(uids),
             }
+        };
+        let mut list = PresenceList::new();
+        list.set_presences(presences.into());
+
+        let mut package = Package::new();
+        package.set_action(PRESENCE_LIST);
+        package.set_content(list.write_to_bytes().unwrap());
+        self.send(package);
+    }
+
+    // 客户端设置自己的状态, 只能是在线或离开
+    fn set_presence(&mut self, request: Presence) -> bool {
+        let away = match request.get_status() {
+            PresenceStatus::AWAY => true,
+            PresenceStatus::ONLINE => false,
+            PresenceStatus::OFFLINE => return self.violation(IMError::MalformedPayload(PRESENCE)),
+        };
+        let mut presence = self.presence.lock().unwrap();
+        presence.set_away(self.uid, away);
+        presence.changed(self.uid);
+        true
+    }
+
+    // 将接收方的送达确认/已读回执转发给原消息的发送方
+    fn forward_receipt(&self, action: Action, mut receipt: Receipt) {
+        let message_id = receipt.get_message_id();
+        // 只接受发给自己的消息的回执, 发送方以服务端的记录为准
+        let sender_uid = match action {
+            DELIVERED_ACK => self.delivery.acked(message_id, self.uid),
+            _ => self.delivery.sender_of(message_id, self.uid),
+        };
+        let sender_uid = match sender_uid.or_else(|| self.stored_sender_of(message_id)) {
+            Some(v) => v,
             None => {
-                debug!("No user with uid = {} was found", mtu_pb.get_receiver_uid())
+                debug!(
+                    "用户 uid = {} 的回执不对应发给该用户的消息 message_id = {}",
+                    self.uid, message_id
+                );
+                return;
             }
+        };
+        receipt.set_receiver_uid(self.uid);
+        receipt.set_sender_uid(sender_uid);
+        let sessions = self.session_manager.load_all(sender_uid);
+        if sessions.is_empty() {
+            debug!(
+                "用户 uid = {} 不在线, 丢弃消息 message_id = {} 的回执",
+                sender_uid,
+                receipt.get_message_id()
+            )
+        }
+        // 发送方的每个设备都收到回执
+        let content = receipt.write_to_bytes().unwrap();
+        for session in sessions {
+            let mut package = Package::new();
+            package.set_action(action);
+            package.set_content(content.clone());
+            let _ = session.send(package);
+        }
+    }
+
+    // 已经不在投递记录中的消息, 从持久化存储中查找发给当前用户的消息的发送方
+    fn stored_sender_of(&self, message_id: u64) -> Option<u64> {
+        match self.message_system.lock().unwrap().load(message_id) {
+            Ok(Some(msg)) if msg.get_receiver_uid() == self.uid => Some(msg.get_sender_uid()),
+            Ok(_) => None,
+            Err(e) => {
+                warn!("读取消息 message_id = {} 失败: {}", message_id, e);
+                None
+            }
+        }
+    }
+
+    fn room_request(&mut self, action: Action, request: RoomRequest) {
+        let ret = {
+            let mut room_manager = self.room_manager.lock().unwrap();
+            match action {
+                CREATE_ROOM => Ok(room_manager
+                    .create(request.get_name(), self.uid)
+                    .get_room_id()),
+                JOIN_ROOM => room_manager
+                    .join(request.get_room_id(), self.uid)
+                    .map(|_| request.get_room_id()),
+                _ => room_manager
+                    .leave(request.get_room_id(), self.uid)
+                    .map(|_| request.get_room_id()),
+            }
+        };
+        match ret {
+            Ok(room_id) => {
+                debug!("用户 uid = {} {:?} room_id = {}", self.uid, action, room_id);
+                self.room_reply(action, room_id, None);
+            }
+            Err(e) => self.room_reply(action, request.get_room_id(), Some(e.to_string())),
+        }
+    }
+
+    fn room_reply(&mut self, action: Action, room_id: u64, reason: Option<String>) {
+        let mut reply = RoomReply::new();
+        reply.set_action(action);
+        reply.set_room_id(room_id);
+        match reason {
+            Some(reason) => reply.set_reason(reason),
+            None => reply.set_success(true),
+        }
+        let mut package = Package::new();
+        package.set_action(ROOM_REPLY);
+        package.set_content(reply.write_to_bytes().unwrap());
+        self.send(package);
+    }
+
+    // 聊天室消息推送给除发送方以外的在线成员
+    fn msg_to_room(&mut self, mut mtr_pb: MsgToRoom) {
+        let room_id = mtr_pb.get_room_id();
+        let ret = self
+            .room_manager
+            .lock()
+            .unwrap()
+            .next_message(room_id, self.uid);
+        // 聊天室消息不保存, 消息ID由聊天室分配, 不占用单聊消息的ID
+        let (message_id, members) = match ret {
+            Ok(v) => v,
+            Err(e) => {
+                self.room_reply(MSG_TO_ROOM, room_id, Some(e.to_string()));
+                return;
+            }
+        };
+        mtr_pb.set_message_id(message_id);
+        mtr_pb.set_sender_uid(self.uid);
+        self.msg_ack(mtr_pb.get_seq(), message_id, room_id);
+
+        let content = mtr_pb.write_to_bytes().unwrap();
+        // 推送给成员的所有设备, 同时同步给发送方的其他设备
+        let mut sessions: Vec<_> = members
+            .iter()
+            .flat_map(|&uid| self.session_manager.load_all(uid))
+            .collect();
+        sessions.extend(self.other_devices());
+        for session in sessions {
+            let mut package = Package::new();
+            package.set_action(MSG_TO_ROOM);
+            package.set_content(content.clone());
+            let _ = session.send(package);
         }
     }
 }
+
+fn new_timer(config: &ServerConfig, clock: Arc<dyn Clock>) -> crate::Result<WheelTimer> {
+    let timer = WheelTimer::with_clock(
+        config.tick_duration_millis,
+        config.ticks_per_wheel,
+        config.timer_levels,
+        clock,
+    )
+    .map_err(|e| IMError::InvalidConfig(e.to_string()))?;
+    timer.set_max_pending_timeouts(config.max_pending_timeouts);
+    timer.set_worker_threads(config.timer_threads);
+    Ok(timer)
+}
+
+fn parse<M: Message>(p: &Package) -> crate::Result<M> {
+    M::parse_from_bytes(p.get_content()).map_err(|_| IMError::MalformedPayload(p.action))
+}
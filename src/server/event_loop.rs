@@ -0,0 +1,388 @@
+use super::{Handler, Handlers, IMServer};
+use crate::delivery::Delivery;
+use crate::{IMError, WriteQueue};
+use log::{debug, warn};
+use mio::net::{TcpListener, TcpStream};
+use mio::{Events, Interest, Poll, Token, Waker};
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::net;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+const LISTENER: Token = Token(0);
+const WAKER: Token = Token(1);
+const EVENTS_CAPACITY: usize = 1024;
+
+// 事件循环中的一个连接
+struct Client {
+    // 注册到 Poll 的 socket, 与 handler 中的 Connection 共享同一个连接
+    source: TcpStream,
+    handler: Handler,
+    handshaken: bool,
+    logged_in: bool,
+    queue: WriteQueue, // 连接的发送队列, 由事件循环在连接可写时写出
+    output: Vec<u8>,   // 正在写出的数据包
+    written: usize,    // output 中已经写出的字节数
+    writable: bool,    // 是否关注了可写事件
+}
+
+impl Client {
+    // 写出发送队列中的数据包, 返回是否已全部写完, 发送缓冲区已满时返回 false.
+    // 队列已关闭时返回 ConnectionClosed
+    fn write(&mut self) -> crate::Result<bool> {
+        let connection = &mut self.handler.connection;
+        loop {
+            if self.written < self.output.len() {
+                self.written += connection.try_write(&self.output[self.written..])?;
+                if self.written < self.output.len() {
+                    return Ok(false);
+                }
+            }
+            let package = match self.queue.try_pop() {
+                Some(v) => v,
+                None if self.queue.is_closed() => return Err(IMError::ConnectionClosed),
+                None => return connection.try_flush(),
+            };
+            self.written = 0;
+            self.output = match connection.get_codec().encode(package) {
+                Ok(v) => v,
+                // 协议版本较低的客户端无法接收过长的消息, 没有写出任何数据, 连接仍然可用
+                Err(IMError::ContentMaxLen) => {
+                    warn!("数据包超过对端支持的最大长度, 丢弃");
+                    Vec::new()
+                }
+                Err(e) => return Err(e),
+            };
+        }
+    }
+}
+
+impl IMServer {
+    /// 以非阻塞方式运行服务端: 单个线程通过 epoll/kqueue 等待所有连接的可读事件,
+    /// 每次可读时读完 socket 中的数据并逐个处理解码出的数据包, 连接不再独占线程.
+    /// 数据包的处理与 run 相同, 同样可以通过 shutdown_handle 关闭. 会话的发送队列不启动写线程,
+    /// 由事件循环非阻塞地写出, 写不完时等待连接可写.
+    /// WebSocket 连接仍然每个连接一个线程.
+    /// 监听失败或等待事件失败时返回错误.
+    pub fn run_event_loop(&mut self) -> crate::Result<()> {
+        let listener = net::TcpListener::bind(&self.config.listen_address)?;
+        listener.set_nonblocking(true)?;
+        self.shutdown.listening(listener.local_addr()?);
+        let poll = Poll::new()?;
+        let waker = Arc::new(Waker::new(poll.registry(), WAKER)?);
+        let mut event_loop = EventLoop {
+            poll,
+            waker,
+            notified: Arc::default(),
+            listener: TcpListener::from_std(listener),
+            clients: HashMap::new(),
+            last_token: WAKER.0,
+            login_deadlines: VecDeque::new(),
+            drain_deadline: None,
+        };
+        event_loop.poll.registry().register(
+            &mut event_loop.listener,
+            LISTENER,
+            Interest::READABLE,
+        )?;
+        let websocket = self.bind_websocket()?;
+        let delivery = Delivery::new(
+            self.session_manager.clone(),
+            self.offline_store.clone(),
+            self.timer.clone(),
+            self.retransmit_policy,
+        );
+        let handlers = Handlers::default();
+        let ret = thread::scope(|s| {
+            if let Some(websocket) = websocket {
+                s.spawn(|| self.serve(websocket, &delivery, &handlers, true));
+            }
+            let ret = event_loop.run(self, &delivery);
+            if ret.is_err() {
+                // 让 WebSocket 的 accept 线程退出
+                self.shutdown.shutdown();
+            }
+            ret
+        });
+        self.drain(&handlers);
+        self.close(&delivery);
+        ret
+    }
+}
+
+struct EventLoop {
+    poll: Poll,
+    waker: Arc<Waker>,
+    notified: Arc<Mutex<Vec<Token>>>, // 其他线程放入了数据包或关闭了发送队列的连接
+    listener: TcpListener,
+    clients: HashMap<Token, Client>,
+    last_token: usize, // Token 只增不减, 已关闭连接的 Token 不会被复用
+    login_deadlines: VecDeque<(Instant, Token)>, // 按接入顺序排列, 截止时间递增
+    drain_deadline: Option<Instant>, // 开始关闭后, 等待连接断开的截止时间
+}
+
+impl EventLoop {
+    // 处理事件直到服务端关闭并且连接已排空, 等待事件失败时关闭所有连接并返回错误
+    fn run(&mut self, server: &IMServer, delivery: &Delivery) -> crate::Result<()> {
+        let mut events = Events::with_capacity(EVENTS_CAPACITY);
+        loop {
+            if server.shutdown.is_shutdown() && self.drain(server) {
+                return Ok(());
+            }
+            let deadline = match (self.login_deadlines.front(), self.drain_deadline) {
+                (Some(&(login, _)), Some(drain)) => Some(login.min(drain)),
+                (Some(&(login, _)), None) => Some(login),
+                (None, drain) => drain,
+            };
+            let timeout = deadline.map(|v| v.saturating_duration_since(Instant::now()));
+            if let Err(e) = self.poll.poll(&mut events, timeout) {
+                if e.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                warn!("Poll failed: {}", e);
+                self.close_all();
+                return Err(e.into());
+            }
+            for event in events.iter() {
+                match event.token() {
+                    LISTENER => self.accept(server, delivery),
+                    WAKER => self.notified(),
+                    token => {
+                        if event.is_writable() {
+                            self.write(token);
+                        }
+                        if event.is_readable() || event.is_read_closed() || event.is_error() {
+                            self.readable(token);
+                            // 处理数据包时放入发送队列的回复立即写出
+                            self.write(token);
+                        }
+                    }
+                }
+            }
+            self.expire_logins();
+        }
+    }
+
+    // 开始关闭时通知在线用户, 返回连接是否已排空. 超时后强制关闭剩余的连接.
+    fn drain(&mut self, server: &IMServer) -> bool {
+        let deadline = match self.drain_deadline {
+            Some(v) => v,
+            None => {
+                // 停止接收新连接
+                let _ = self.poll.registry().deregister(&mut self.listener);
+                server.notify_closing();
+                let deadline = Instant::now() + server.config.drain_timeout();
+                self.drain_deadline = Some(deadline);
+                deadline
+            }
+        };
+        if !self.clients.is_empty() && Instant::now() < deadline {
+            return false;
+        }
+        if !self.clients.is_empty() {
+            debug!("强制关闭 {} 个连接", self.clients.len());
+        }
+        self.close_all();
+        true
+    }
+
+    fn close_all(&mut self) {
+        let tokens: Vec<Token> = self.clients.keys().copied().collect();
+        for token in tokens {
+            self.remove(token).handler.close();
+        }
+    }
+
+    // 边沿触发, 需要一直 accept 直到没有新连接
+    fn accept(&mut self, server: &IMServer, delivery: &Delivery) {
+        loop {
+            let stream = match self.listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return,
+                Err(e) => {
+                    warn!("Connection failed: {}", e);
+                    return;
+                }
+            };
+            // 关闭之后的连接(包括 shutdown 用于唤醒的连接)直接断开
+            if server.shutdown.is_shutdown() {
+                continue;
+            }
+            if let Err(e) = self.register(stream, server, delivery) {
+                warn!("Connection failed: {}", e);
+            }
+        }
+    }
+
+    fn register(
+        &mut self,
+        stream: TcpStream,
+        server: &IMServer,
+        delivery: &Delivery,
+    ) -> crate::Result<()> {
+        let stream = net::TcpStream::from(stream);
+        let mut source = TcpStream::from_std(stream.try_clone()?);
+        let connection = server.new_connection(stream)?;
+        debug!("new conn remote_address = {}", connection.remote_address());
+
+        self.last_token += 1;
+        let token = Token(self.last_token);
+        self.poll
+            .registry()
+            .register(&mut source, token, Interest::READABLE)?;
+        let notified = self.notified.clone();
+        let waker = self.waker.clone();
+        let queue = WriteQueue::with_notify(
+            server.config.write_queue_capacity,
+            server.config.overflow_policy,
+            Arc::new(move || {
+                notified.lock().unwrap().push(token);
+                if let Err(e) = waker.wake() {
+                    warn!("唤醒事件循环失败: {}", e);
+                }
+            }),
+        );
+        let mut handler = Handler::new(connection, server, delivery.clone());
+        handler.write_queue = Some(queue.clone());
+        let client = Client {
+            source,
+            handler,
+            handshaken: false,
+            logged_in: false,
+            queue,
+            output: Vec::new(),
+            written: 0,
+            writable: false,
+        };
+        self.clients.insert(token, client);
+        let deadline = Instant::now() + server.config.login_timeout();
+        self.login_deadlines.push_back((deadline, token));
+        Ok(())
+    }
+
+    // 完成握手后, 读出 socket 中全部完整的数据包并交给 Handler 处理
+    fn readable(&mut self, token: Token) {
+        let client = match self.clients.get_mut(&token) {
+            Some(v) => v,
+            None => return,
+        };
+        if !client.handshaken {
+            // 握手的回复与数据包一样由 write 非阻塞地写出
+            let version = match client.handler.connection.try_negotiate() {
+                Ok(Some((version, reply))) => {
+                    client.output = reply;
+                    client.written = 0;
+                    version
+                }
+                Ok(None) => return,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = version {
+                debug!("握手失败: {}", e);
+                self.close(token);
+                return;
+            }
+            client.handshaken = true;
+        }
+        loop {
+            let package = match client.handler.connection.try_read_package() {
+                Ok(Some(p)) => p,
+                Ok(None) => return,
+                Err(e) => {
+                    debug!("连接读取失败: {}", e);
+                    self.remove(token).handler.close();
+                    return;
+                }
+            };
+            let ok = if client.logged_in {
+                client.handler.on_package(package)
+            } else {
+                client.logged_in = client.handler.on_login(package);
+                client.logged_in
+            };
+            if !ok {
+                self.close(token);
+                return;
+            }
+        }
+    }
+
+    // 写出其他线程放入发送队列的数据包, 发送队列已关闭时关闭连接
+    fn notified(&mut self) {
+        let tokens = std::mem::take(&mut *self.notified.lock().unwrap());
+        for token in tokens {
+            self.write(token);
+        }
+    }
+
+    // 写出发送队列中的数据包直到队列为空或 socket 不可写, 写不完时关注可写事件, 写完后取消关注.
+    // 队列已关闭或写入失败时关闭连接
+    fn write(&mut self, token: Token) {
+        let client = match self.clients.get_mut(&token) {
+            Some(v) => v,
+            None => return,
+        };
+        let done = match client.write() {
+            Ok(v) => v,
+            Err(e) => {
+                debug!("写入失败, 关闭连接: {}", e);
+                self.remove(token).handler.close();
+                return;
+            }
+        };
+        if client.writable != done {
+            return;
+        }
+        let interest = match done {
+            true => Interest::READABLE,
+            false => Interest::READABLE | Interest::WRITABLE,
+        };
+        match self
+            .poll
+            .registry()
+            .reregister(&mut client.source, token, interest)
+        {
+            Ok(()) => client.writable = !done,
+            Err(e) => {
+                warn!("注册可写事件失败, 关闭连接: {}", e);
+                self.remove(token).handler.close();
+            }
+        }
+    }
+
+    // 关闭超时仍未完成登录的连接
+    fn expire_logins(&mut self) {
+        let now = Instant::now();
+        while let Some(&(deadline, token)) = self.login_deadlines.front() {
+            if deadline > now {
+                return;
+            }
+            self.login_deadlines.pop_front();
+            let logged_in = match self.clients.get(&token) {
+                Some(client) => client.logged_in,
+                None => continue,
+            };
+            if !logged_in {
+                debug!("登录超时, 关闭连接");
+                self.remove(token).handler.close();
+            }
+        }
+    }
+
+    // 服务端主动关闭连接, 关闭前尽力写出登录失败等已放入发送队列的回复, 不等待连接可写
+    fn close(&mut self, token: Token) {
+        let mut client = self.remove(token);
+        if let Err(e) = client.write() {
+            debug!("关闭前写入失败: {}", e);
+        }
+        client.handler.close();
+    }
+
+    fn remove(&mut self, token: Token) -> Client {
+        let mut client = self.clients.remove(&token).unwrap();
+        let _ = self.poll.registry().deregister(&mut client.source);
+        client
+    }
+}
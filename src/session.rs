@@ -1,23 +1,88 @@
-use crate::Connection;
-use std::borrow::BorrowMut;
-use std::collections::HashMap;
+use crate::proto::Package;
+use crate::{Clock, Connection, OverflowPolicy, Result, SystemClock, Timeout, WriteQueue};
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
-#[derive(Clone)]
+/// 每个会话记住最近的消息序列号, 用于识别客户端重传的消息.
+const RECENT_SEQ_CAPACITY: usize = 1024;
+
+/// SessionManager 默认的分片数量.
+pub const DEFAULT_SESSION_SHARDS: usize = 16;
+
+// 每次登录或恢复得到的会话编号, 区分同一个 session_id 先后所在的连接
+static NEXT_SERIAL: AtomicU64 = AtomicU64::new(1);
+
+/// 同一用户再次登录时对已有会话的处理策略.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoginPolicy {
+    /// 每个设备一个会话, 多个设备同时在线, 同一设备再次登录时踢掉该设备的旧会话
+    #[default]
+    MultiDevice,
+    /// 每个用户一个会话, 再次登录时踢掉所有旧会话
+    KickOlder,
+}
+
+/// 会话最近收到的消息序列号, 用于识别客户端重传的消息, 恢复会话时一并恢复.
+#[derive(Default)]
+pub struct RecentSeqs {
+    message_ids: HashMap<u64, u64>, // key => seq, value => message_id
+    order: VecDeque<u64>,
+}
+
+impl RecentSeqs {
+    pub fn get(&self, seq: u64) -> Option<u64> {
+        self.message_ids.get(&seq).copied()
+    }
+
+    /// 记录消息序列号, 超过容量时淘汰最早的记录.
+    pub fn insert(&mut self, seq: u64, message_id: u64) {
+        self.message_ids.insert(seq, message_id);
+        self.order.push_back(seq);
+        if self.order.len() > RECENT_SEQ_CAPACITY {
+            if let Some(seq) = self.order.pop_front() {
+                self.message_ids.remove(&seq);
+            }
+        }
+    }
+}
+
+/// 已登录连接的会话, 由 SessionManager 以 Arc 共享, 查找会话不会复制连接.
 pub struct Session {
     session_id: String,
+    resume_token: String, // 恢复会话的凭证, 只下发给登录的客户端
     uid: u64,
-    connection: Connection,
+    device_id: String,
+    connection: Mutex<Connection>,
+    write_queue: WriteQueue,
+    recent_seqs: Arc<Mutex<RecentSeqs>>,
+    serial: u64,
+    idle_timeout: Mutex<Option<Timeout>>, // 读空闲检测任务, 会话关闭时取消
 }
 
 impl Session {
-    pub fn new(uid: u64, connection: Connection) -> Session {
+    /// 创建会话, 之后写入连接的数据都要通过 write_queue.
+    pub fn new(
+        uid: u64,
+        device_id: &str,
+        connection: Connection,
+        write_queue: WriteQueue,
+    ) -> Session {
         let uuid = Uuid::new_v4();
         Session {
             session_id: uuid.to_string(),
+            resume_token: Uuid::new_v4().to_string(),
             uid,
-            connection,
+            device_id: device_id.to_string(),
+            connection: Mutex::new(connection),
+            write_queue,
+            recent_seqs: Arc::default(),
+            serial: NEXT_SERIAL.fetch_add(1, Ordering::SeqCst),
+            idle_timeout: Mutex::default(),
         }
     }
 
@@ -25,73 +90,384 @@ impl Session {
         self.session_id.clone()
     }
 
+    /// 恢复会话时需要与 session_id 一起提交的凭证.
+    pub fn get_resume_token(&self) -> String {
+        self.resume_token.clone()
+    }
+
     pub fn get_uid(&self) -> u64 {
         self.uid
     }
 
-    pub fn borrow_connection(&mut self) -> &mut Connection {
-        self.connection.borrow_mut()
+    /// 登录时上报的设备ID, 没有上报时为空.
+    pub fn get_device_id(&self) -> &str {
+        &self.device_id
+    }
+
+    pub fn get_recent_seqs(&self) -> Arc<Mutex<RecentSeqs>> {
+        self.recent_seqs.clone()
+    }
+
+    /// 数据包放入发送队列, 由写线程写出.
+    pub fn send(&self, package: Package) -> Result<()> {
+        self.write_queue.push(package)
+    }
+
+    pub fn get_overflow_policy(&self) -> OverflowPolicy {
+        self.write_queue.get_policy()
+    }
+
+    /// 等待发送队列中的数据包写完, 超时返回 false.
+    pub fn flush(&self, timeout: Duration) -> bool {
+        self.write_queue.flush(timeout)
+    }
+
+    /// 记录读空闲检测任务, 会话已经关闭时直接取消.
+    pub fn set_idle_timeout(&self, timeout: Timeout) {
+        let mut idle_timeout = self.idle_timeout.lock().unwrap();
+        if self.connection.lock().unwrap().is_closed() {
+            timeout.cancel();
+            return;
+        }
+        *idle_timeout = Some(timeout);
+    }
+
+    /// 关闭发送队列并断开连接, 取消读空闲检测任务.
+    pub fn close(&self) {
+        self.write_queue.close();
+        self.connection.lock().unwrap().shutdown();
+        if let Some(timeout) = self.idle_timeout.lock().unwrap().take() {
+            timeout.cancel();
+        }
+    }
+}
+
+/// 连接断开后保留的会话, 宽限期内客户端可以凭 session_id 与 resume_token 恢复同一个用户.
+pub struct DetachedSession {
+    session_id: String,
+    resume_token: String,
+    uid: u64,
+    device_id: String,
+    recent_seqs: Arc<Mutex<RecentSeqs>>,
+    expire_at: Instant, // 按单调时间计算, 系统时间调整不影响宽限期
+}
+
+impl DetachedSession {
+    pub fn get_uid(&self) -> u64 {
+        self.uid
+    }
+
+    pub fn get_device_id(&self) -> &str {
+        &self.device_id
     }
 }
 
+// 一个分片内的会话, 按 session_id 与 uid 分别建立索引
+#[derive(Default)]
+struct Shard {
+    sessions: HashMap<String, Arc<Session>>, // key => session_id
+    users: HashMap<u64, Vec<Arc<Session>>>,  // key => uid, value => 用户所有设备的会话
+}
+
+impl Shard {
+    fn insert(&mut self, session: Arc<Session>) {
+        self.users
+            .entry(session.uid)
+            .or_default()
+            .push(session.clone());
+        self.sessions.insert(session.session_id.clone(), session);
+    }
+
+    fn remove(&mut self, session_id: &str) -> Option<Arc<Session>> {
+        let session = self.sessions.remove(session_id)?;
+        if let Some(sessions) = self.users.get_mut(&session.uid) {
+            sessions.retain(|v| v.session_id != session_id);
+            if sessions.is_empty() {
+                self.users.remove(&session.uid);
+            }
+        }
+        Some(session)
+    }
+
+    fn is_current(&self, session: &Session) -> bool {
+        match self.sessions.get(&session.session_id) {
+            Some(v) => v.serial == session.serial,
+            None => false,
+        }
+    }
+}
+
+/// 在线会话管理. 会话按 uid 分散到多个分片, 每个分片一把锁, 不同用户的查找互不阻塞;
+/// 分片内按 uid 与 session_id 建立索引, 查找不需要遍历所有会话.
+///
+/// 锁的顺序: 用户所在的分片 => 等待恢复的会话 => 离线队列.
 pub struct SessionManager {
-    last_uid: AtomicU64,
-    session_map: HashMap<String, Session>, // 管理会话session, key => session_id, value => session
+    shards: Vec<Mutex<Shard>>,
+    detached: Mutex<HashMap<String, DetachedSession>>, // 等待恢复的会话, key => session_id
+    clock: Mutex<Arc<dyn Clock>>,
+}
+
+impl Default for SessionManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SessionManager {
     pub fn new() -> SessionManager {
+        Self::with_shards(DEFAULT_SESSION_SHARDS)
+    }
+
+    /// 指定分片数量, 至少为1.
+    pub fn with_shards(shards: usize) -> SessionManager {
         SessionManager {
-            last_uid: AtomicU64::new(1),
-            session_map: HashMap::new(),
+            shards: (0..shards.max(1)).map(|_| Mutex::default()).collect(),
+            detached: Mutex::new(HashMap::new()),
+            clock: Mutex::new(Arc::new(SystemClock)),
         }
     }
 
-    pub fn new_session(&mut self, connection: Connection) -> Session {
-        let uid = self.last_uid.fetch_add(1, Ordering::SeqCst);
-        let session = Session::new(uid, connection);
-        self.store(session.clone());
-        session
+    /// 计算会话恢复宽限期使用的时钟, 默认为系统时钟.
+    pub fn set_clock(&self, clock: Arc<dyn Clock>) {
+        *self.clock.lock().unwrap() = clock;
+    }
+
+    fn now(&self) -> Instant {
+        self.clock.lock().unwrap().now()
+    }
+
+    /// 锁住用户所在的分片, 在持有期间对该用户的会话做一组不可分割的操作,
+    /// 例如登录时踢掉旧会话并创建新会话, 或确认用户不在线后把消息转入离线队列.
+    pub fn lock_user(&self, uid: u64) -> UserSessions<'_> {
+        let index = (uid % self.shards.len() as u64) as usize;
+        UserSessions {
+            uid,
+            shard: self.shards[index].lock().unwrap(),
+        }
     }
 
-    fn store(&mut self, session: Session) {
-        let value = session.clone();
-        self.session_map.insert(session.session_id, value);
+    pub fn new_session(
+        &self,
+        uid: u64,
+        device_id: &str,
+        connection: Connection,
+        write_queue: WriteQueue,
+    ) -> Arc<Session> {
+        self.lock_user(uid)
+            .new_session(device_id, connection, write_queue)
     }
 
-    pub fn load(&self, uid: u64) -> Option<Session> {
-        for (_, value) in self.session_map.clone() {
-            if value.uid == uid {
-                return Some(value);
+    /// 在新的连接上恢复会话, 沿用原来的 session_id 与消息序列号.
+    pub fn restore_session(
+        &self,
+        detached: DetachedSession,
+        connection: Connection,
+        write_queue: WriteQueue,
+    ) -> Arc<Session> {
+        self.lock_user(detached.uid)
+            .restore_session(detached, connection, write_queue)
+    }
+
+    /// 移除仍是当前会话的 session, 保留 grace 时长等待恢复, 同时清理已过期的会话.
+    /// 已被踢掉或已在其他连接上恢复的会话返回 false.
+    pub fn detach(&self, session: &Session, grace: Duration) -> bool {
+        let mut user = self.lock_user(session.uid);
+        if !user.shard.is_current(session) {
+            return false;
+        }
+        user.shard.remove(&session.session_id);
+        // 持有分片锁时放入, 恢复会话时不会在两处都找不到
+        let now = self.now();
+        let mut detached = self.detached.lock().unwrap();
+        detached.retain(|_, v| v.expire_at > now);
+        detached.insert(
+            session.session_id.clone(),
+            DetachedSession {
+                session_id: session.session_id.clone(),
+                resume_token: session.resume_token.clone(),
+                uid: session.uid,
+                device_id: session.device_id.clone(),
+                recent_seqs: session.get_recent_seqs(),
+                expire_at: now + grace,
+            },
+        );
+        true
+    }
+
+    /// 凭 session_id 与登录时下发的 resume_token 取出可以恢复的会话, 每个会话只能恢复一次.
+    /// 服务端还未发现旧连接断开时, 关闭旧连接后恢复. resume_token 不匹配、已过期或不存在时返回 None,
+    /// 不影响原来的会话.
+    pub fn resume(&self, session_id: &str, resume_token: &str) -> Option<DetachedSession> {
+        // 只知道 session_id, 依次查找每个分片的索引
+        for shard in &self.shards {
+            let mut shard = shard.lock().unwrap();
+            let session = match shard.sessions.get(session_id) {
+                Some(v) => v.clone(),
+                None => continue,
+            };
+            if session.resume_token != resume_token {
+                return None;
             }
+            shard.remove(session_id);
+            session.close();
+            return Some(DetachedSession {
+                session_id: session.session_id.clone(),
+                resume_token: session.resume_token.clone(),
+                uid: session.uid,
+                device_id: session.device_id.clone(),
+                recent_seqs: session.get_recent_seqs(),
+                expire_at: self.now(),
+            });
+        }
+        let mut detached = self.detached.lock().unwrap();
+        match detached.get(session_id) {
+            Some(v) if v.resume_token == resume_token => {}
+            _ => return None,
         }
-        None
+        let detached = detached.remove(session_id)?;
+        if detached.expire_at <= self.now() {
+            return None;
+        }
+        Some(detached)
+    }
+
+    /// 用户任意一个设备的会话.
+    pub fn load(&self, uid: u64) -> Option<Arc<Session>> {
+        self.lock_user(uid).load_all().into_iter().next()
+    }
+
+    /// 用户所有设备的会话.
+    pub fn load_all(&self, uid: u64) -> Vec<Arc<Session>> {
+        self.lock_user(uid).load_all()
     }
 
     pub fn exist(&self, uid: u64) -> bool {
-        for (_, value) in self.session_map.clone() {
-            if value.uid == uid {
-                return true;
-            }
+        self.lock_user(uid).exist()
+    }
+
+    /// 移除用户所有设备的会话.
+    pub fn remove(&self, uid: u64) -> Vec<Arc<Session>> {
+        self.lock_user(uid).remove_all()
+    }
+
+    /// 移除用户在 device_id 上的会话.
+    pub fn remove_device(&self, uid: u64, device_id: &str) -> Option<Arc<Session>> {
+        self.lock_user(uid).remove_device(device_id)
+    }
+
+    /// session 仍是 session_id 当前对应的会话, 没有被恢复到其他连接上.
+    pub fn is_current(&self, session: &Session) -> bool {
+        self.lock_user(session.uid).shard.is_current(session)
+    }
+
+    /// 移除仍是当前会话的 session, 已被踢掉或已恢复到其他连接上时返回 None.
+    pub fn remove_session(&self, session: &Session) -> Option<Arc<Session>> {
+        let mut user = self.lock_user(session.uid);
+        if !user.shard.is_current(session) {
+            return None;
         }
-        false
+        user.shard.remove(&session.session_id)
     }
 
-    pub fn remove(&mut self, uid: u64) -> Option<Session> {
-        match self.load(uid) {
-            None => return None,
-            Some(v) => {
-                let ref session_id = v.session_id;
-                self.session_map.remove(session_id)
-            }
+    pub fn sessions(&self) -> Vec<Arc<Session>> {
+        let mut sessions = Vec::new();
+        for shard in &self.shards {
+            sessions.extend(shard.lock().unwrap().sessions.values().cloned());
         }
+        sessions
+    }
+
+    /// 在线会话的数量.
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.lock().unwrap().sessions.len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 
+    /// 在线的用户, 多个设备在线的用户只出现一次.
     pub fn online_users(&self) -> Vec<u64> {
-        let mut uids = Vec::new();
-        for (_, value) in self.session_map.clone() {
-            uids.push(value.uid);
+        let mut users = Vec::new();
+        for shard in &self.shards {
+            users.extend(shard.lock().unwrap().users.keys().copied());
         }
-        uids
+        users.sort_unstable();
+        users
+    }
+}
+
+/// 持有用户所在分片的锁, 由 SessionManager::lock_user 返回, 释放后其他线程才能访问该分片.
+pub struct UserSessions<'a> {
+    uid: u64,
+    shard: MutexGuard<'a, Shard>,
+}
+
+impl UserSessions<'_> {
+    /// 用户所有设备的会话.
+    pub fn load_all(&self) -> Vec<Arc<Session>> {
+        self.shard.users.get(&self.uid).cloned().unwrap_or_default()
+    }
+
+    pub fn exist(&self) -> bool {
+        self.shard.users.contains_key(&self.uid)
+    }
+
+    pub fn new_session(
+        &mut self,
+        device_id: &str,
+        connection: Connection,
+        write_queue: WriteQueue,
+    ) -> Arc<Session> {
+        let session = Arc::new(Session::new(self.uid, device_id, connection, write_queue));
+        self.shard.insert(session.clone());
+        session
+    }
+
+    /// 在新的连接上恢复会话, 沿用原来的 session_id 与消息序列号.
+    pub fn restore_session(
+        &mut self,
+        detached: DetachedSession,
+        connection: Connection,
+        write_queue: WriteQueue,
+    ) -> Arc<Session> {
+        let session = Arc::new(Session {
+            session_id: detached.session_id,
+            resume_token: detached.resume_token,
+            uid: detached.uid,
+            device_id: detached.device_id,
+            connection: Mutex::new(connection),
+            write_queue,
+            recent_seqs: detached.recent_seqs,
+            serial: NEXT_SERIAL.fetch_add(1, Ordering::SeqCst),
+            idle_timeout: Mutex::default(),
+        });
+        self.shard.insert(session.clone());
+        session
+    }
+
+    /// 移除用户所有设备的会话.
+    pub fn remove_all(&mut self) -> Vec<Arc<Session>> {
+        let sessions = self.shard.users.remove(&self.uid).unwrap_or_default();
+        for session in &sessions {
+            self.shard.sessions.remove(&session.session_id);
+        }
+        sessions
+    }
+
+    /// 移除用户在 device_id 上的会话.
+    pub fn remove_device(&mut self, device_id: &str) -> Option<Arc<Session>> {
+        let session_id = self
+            .shard
+            .users
+            .get(&self.uid)?
+            .iter()
+            .find(|session| session.device_id == device_id)?
+            .session_id
+            .clone();
+        self.shard.remove(&session_id)
     }
 }
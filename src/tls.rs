@@ -0,0 +1,235 @@
+use crate::{IMError, Result, Transport};
+use rustls::crypto::ring;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::{ClientConnection, RootCertStore, ServerConnection};
+use std::convert::TryFrom;
+use std::fs;
+use std::io;
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// 每次从 socket 读取的密文长度
+const READ_BUF_LEN: usize = 16 * 1024;
+
+/// 服务端的 TLS 配置, 用证书与私钥完成握手.
+#[derive(Clone)]
+pub struct TlsAcceptor {
+    config: Arc<rustls::ServerConfig>,
+}
+
+impl TlsAcceptor {
+    /// cert_pem 为 PEM 格式的证书链, key_pem 为 PEM 格式的私钥.
+    pub fn new(cert_pem: &[u8], key_pem: &[u8]) -> Result<TlsAcceptor> {
+        let certs = CertificateDer::pem_slice_iter(cert_pem)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| invalid(format!("bad certificate: {}", e)))?;
+        if certs.is_empty() {
+            return Err(invalid("no certificate found".to_string()));
+        }
+        let key = PrivateKeyDer::from_pem_slice(key_pem)
+            .map_err(|e| invalid(format!("bad private key: {}", e)))?;
+        let config =
+            rustls::ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .map_err(|e| invalid(e.to_string()))?
+                .with_no_client_auth()
+                .with_single_cert(certs, key)
+                .map_err(|e| invalid(e.to_string()))?;
+        Ok(TlsAcceptor {
+            config: Arc::new(config),
+        })
+    }
+
+    pub fn from_files(cert_file: &str, key_file: &str) -> Result<TlsAcceptor> {
+        Self::new(&read_file(cert_file)?, &read_file(key_file)?)
+    }
+
+    /// 包装新接入的连接, TLS 握手在第一次读写时完成.
+    pub fn accept(&self, socket: TcpStream) -> Result<TlsTransport> {
+        let session = ServerConnection::new(self.config.clone()).map_err(io::Error::other)?;
+        Ok(TlsTransport::new(socket, session.into()))
+    }
+}
+
+/// 客户端的 TLS 配置, 用 CA 证书校验服务端.
+#[derive(Clone)]
+pub struct TlsConnector {
+    config: Arc<rustls::ClientConfig>,
+}
+
+impl TlsConnector {
+    /// ca_pem 为 PEM 格式的 CA 证书, 可以包含多个证书.
+    pub fn new(ca_pem: &[u8]) -> Result<TlsConnector> {
+        let mut roots = RootCertStore::empty();
+        for cert in CertificateDer::pem_slice_iter(ca_pem) {
+            let cert = cert.map_err(|e| invalid(format!("bad CA certificate: {}", e)))?;
+            roots
+                .add(cert)
+                .map_err(|e| invalid(format!("bad CA certificate: {}", e)))?;
+        }
+        if roots.is_empty() {
+            return Err(invalid("no CA certificate found".to_string()));
+        }
+        let config =
+            rustls::ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .map_err(|e| invalid(e.to_string()))?
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+        Ok(TlsConnector {
+            config: Arc::new(config),
+        })
+    }
+
+    pub fn from_file(ca_file: &str) -> Result<TlsConnector> {
+        Self::new(&read_file(ca_file)?)
+    }
+
+    /// 包装已建立的连接, server_name 需与服务端证书中的域名一致.
+    pub fn connect(&self, server_name: &str, socket: TcpStream) -> Result<TlsTransport> {
+        let name = ServerName::try_from(server_name.to_string())
+            .map_err(|_| invalid(format!("invalid server name: {}", server_name)))?;
+        let session = ClientConnection::new(self.config.clone(), name).map_err(io::Error::other)?;
+        Ok(TlsTransport::new(socket, session.into()))
+    }
+}
+
+// 所有 clone 出来的 TlsTransport 共享同一个 TLS 会话
+struct TlsState {
+    session: rustls::Connection,
+    // 已从 socket 读到但还未交给 TLS 会话的密文
+    incoming: Vec<u8>,
+}
+
+/// TLS 加密的 TcpStream. 读取 socket 时不持有会话的锁, 一个线程阻塞读取时其他线程仍然可以写入.
+pub struct TlsTransport {
+    socket: TcpStream,
+    state: Arc<Mutex<TlsState>>,
+}
+
+impl TlsTransport {
+    fn new(socket: TcpStream, session: rustls::Connection) -> TlsTransport {
+        TlsTransport {
+            socket,
+            state: Arc::new(Mutex::new(TlsState {
+                session,
+                incoming: Vec::new(),
+            })),
+        }
+    }
+}
+
+impl Read for TlsTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            {
+                let mut guard = self.state.lock().unwrap();
+                let state = &mut *guard;
+                match state.session.reader().read(buf) {
+                    Ok(n) => return Ok(n),
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                    Err(e) => return Err(e),
+                }
+                if !state.incoming.is_empty() {
+                    let mut rd: &[u8] = &state.incoming;
+                    let n = state.session.read_tls(&mut rd)?;
+                    if n == 0 {
+                        return Err(io::ErrorKind::InvalidData.into());
+                    }
+                    state.incoming.drain(..n);
+                    if let Err(e) = state.session.process_new_packets() {
+                        // 尽量把 alert 发给对端
+                        let _ = write_tls(&mut state.session, &self.socket);
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+                    }
+                    continue;
+                }
+                // 握手过程中需要先把回复发给对端, 发送缓冲区已满时留到下次再发
+                match write_tls(&mut state.session, &self.socket) {
+                    Err(e) if e.kind() != io::ErrorKind::WouldBlock => return Err(e),
+                    _ => {}
+                }
+            }
+            let mut data = [0; READ_BUF_LEN];
+            let n = (&self.socket).read(&mut data)?;
+            if n == 0 {
+                return Ok(0);
+            }
+            let mut state = self.state.lock().unwrap();
+            state.incoming.extend_from_slice(&data[..n]);
+        }
+    }
+}
+
+impl Write for TlsTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.state.lock().unwrap();
+        let n = state.session.writer().write(buf)?;
+        match write_tls(&mut state.session, &self.socket) {
+            // 明文已写入会话的缓冲区, 由之后的 flush 发送
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock && n > 0 => Ok(n),
+            Err(e) => Err(e),
+            Ok(()) if n == 0 => Err(io::ErrorKind::WouldBlock.into()),
+            Ok(()) => Ok(n),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        write_tls(&mut state.session, &self.socket)?;
+        (&self.socket).flush()
+    }
+}
+
+impl Transport for TlsTransport {
+    fn try_clone(&self) -> io::Result<Box<dyn Transport>> {
+        Ok(Box::new(TlsTransport {
+            socket: self.socket.try_clone()?,
+            state: self.state.clone(),
+        }))
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.socket.set_nonblocking(nonblocking)
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.socket.set_read_timeout(timeout)
+    }
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.socket.set_write_timeout(timeout)
+    }
+
+    fn remote_address(&self) -> String {
+        Transport::remote_address(&self.socket)
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        {
+            let mut state = self.state.lock().unwrap();
+            state.session.send_close_notify();
+            let _ = write_tls(&mut state.session, &self.socket);
+        }
+        self.socket.shutdown(Shutdown::Both)
+    }
+}
+
+// 把会话中待发送的密文全部写入 socket
+fn write_tls(session: &mut rustls::Connection, mut socket: &TcpStream) -> io::Result<()> {
+    while session.wants_write() {
+        session.write_tls(&mut socket)?;
+    }
+    Ok(())
+}
+
+fn read_file(path: &str) -> Result<Vec<u8>> {
+    fs::read(path).map_err(|e| invalid(format!("{}: {}", path, e)))
+}
+
+fn invalid(reason: String) -> IMError {
+    IMError::InvalidConfig(reason)
+}
@@ -0,0 +1,195 @@
+use std::collections::VecDeque;
+use std::io;
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Connection 的传输层, 例如明文的 TCP, TLS 或测试用的内存管道.
+/// try_clone 出来的 Transport 共享同一个连接, 一个线程阻塞读取时其他线程仍然可以写入.
+pub trait Transport: Read + Write + Send {
+    fn try_clone(&self) -> io::Result<Box<dyn Transport>>;
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()>;
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+
+    fn remote_address(&self) -> String;
+
+    /// 关闭连接的读写两端, 阻塞在 read 上的线程会立即返回.
+    fn shutdown(&self) -> io::Result<()>;
+}
+
+impl Transport for TcpStream {
+    fn try_clone(&self) -> io::Result<Box<dyn Transport>> {
+        Ok(Box::new(TcpStream::try_clone(self)?))
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        TcpStream::set_nonblocking(self, nonblocking)
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        TcpStream::set_read_timeout(self, timeout)
+    }
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        TcpStream::set_write_timeout(self, timeout)
+    }
+
+    fn remote_address(&self) -> String {
+        match self.peer_addr() {
+            Ok(v) => v.to_string(),
+            Err(_) => "unknown".to_string(),
+        }
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        TcpStream::shutdown(self, Shutdown::Both)
+    }
+}
+
+// 单向的管道
+#[derive(Default)]
+struct Pipe {
+    state: Mutex<PipeState>,
+    condvar: Condvar,
+}
+
+#[derive(Default)]
+struct PipeState {
+    data: VecDeque<u8>,
+    closed: bool,
+}
+
+impl Pipe {
+    fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.condvar.notify_all();
+    }
+}
+
+#[derive(Default)]
+struct Options {
+    nonblocking: bool,
+    read_timeout: Option<Duration>,
+}
+
+/// 进程内的双向管道, 行为与 TcpStream 相同, 用于在测试中连接两个 Connection.
+pub struct MemoryTransport {
+    incoming: Arc<Pipe>,
+    outgoing: Arc<Pipe>,
+    options: Arc<Mutex<Options>>,
+}
+
+impl MemoryTransport {
+    /// 创建一对相连的管道, 一端写入的数据从另一端读出.
+    pub fn pair() -> (MemoryTransport, MemoryTransport) {
+        let a = Arc::new(Pipe::default());
+        let b = Arc::new(Pipe::default());
+        let left = MemoryTransport {
+            incoming: a.clone(),
+            outgoing: b.clone(),
+            options: Arc::default(),
+        };
+        let right = MemoryTransport {
+            incoming: b,
+            outgoing: a,
+            options: Arc::default(),
+        };
+        (left, right)
+    }
+}
+
+impl Read for MemoryTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let (nonblocking, timeout) = {
+            let options = self.options.lock().unwrap();
+            (options.nonblocking, options.read_timeout)
+        };
+        let deadline = timeout.map(|v| Instant::now() + v);
+        let mut state = self.incoming.state.lock().unwrap();
+        loop {
+            if !state.data.is_empty() {
+                let n = buf.len().min(state.data.len());
+                for (i, b) in state.data.drain(..n).enumerate() {
+                    buf[i] = b;
+                }
+                return Ok(n);
+            }
+            if state.closed {
+                return Ok(0);
+            }
+            if nonblocking {
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+            state = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(io::ErrorKind::WouldBlock.into());
+                    }
+                    self.incoming
+                        .condvar
+                        .wait_timeout(state, deadline - now)
+                        .unwrap()
+                        .0
+                }
+                None => self.incoming.condvar.wait(state).unwrap(),
+            };
+        }
+    }
+}
+
+impl Write for MemoryTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.outgoing.state.lock().unwrap();
+        if state.closed {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+        state.data.extend(buf);
+        self.outgoing.condvar.notify_all();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn try_clone(&self) -> io::Result<Box<dyn Transport>> {
+        Ok(Box::new(MemoryTransport {
+            incoming: self.incoming.clone(),
+            outgoing: self.outgoing.clone(),
+            options: self.options.clone(),
+        }))
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.options.lock().unwrap().nonblocking = nonblocking;
+        Ok(())
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.options.lock().unwrap().read_timeout = timeout;
+        Ok(())
+    }
+
+    // 写入不会阻塞
+    fn set_write_timeout(&self, _timeout: Option<Duration>) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn remote_address(&self) -> String {
+        "memory".to_string()
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        self.incoming.close();
+        self.outgoing.close();
+        Ok(())
+    }
+}
@@ -0,0 +1,262 @@
+use crate::proto::Package;
+use crate::{Buffer, Codec, IMError, Result, Transport};
+use log::debug;
+use protobuf::Message as _;
+use std::collections::VecDeque;
+use std::io;
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tungstenite::protocol::WebSocketConfig;
+use tungstenite::{HandshakeError, Message, WebSocket};
+
+// 每次从 socket 读取的数据长度
+const READ_BUF_LEN: usize = 16 * 1024;
+// Package 中 action 与 content 两个字段的头部长度上限
+const PACKAGE_OVERHEAD: usize = 16;
+
+/// WebSocket 连接, 浏览器在每个二进制消息中发送一个 protobuf 编码的 Package.
+///
+/// 对 Connection 而言与 TCP 连接没有区别: 收到的 Package 按 Codec 编码为帧后读出,
+/// 写入的帧解码为 Package 后逐个作为二进制消息发送. 浏览器不发送 MAGIC 握手,
+/// 连接直接使用 Codec 的版本. WebSocket 握手在第一次读取时完成.
+pub struct WebSocketTransport {
+    // 读取 socket 时不持有锁, 一个线程阻塞读取时其他线程仍然可以写入
+    reader: Box<dyn Transport>,
+    state: Arc<Mutex<WebSocketState>>,
+}
+
+struct WebSocketState {
+    upgrade: Upgrade,
+    codec: Codec,
+    // 已收到但还未读出的帧
+    received: VecDeque<u8>,
+    // 已写入但还不足一个数据包的帧
+    written: Buffer,
+}
+
+enum Upgrade {
+    Pending(WsStream),
+    Done(Box<WebSocket<WsStream>>),
+    Failed,
+}
+
+impl WebSocketTransport {
+    /// 包装新接入的连接, transport 可以是 TcpStream 或 TlsTransport.
+    pub fn accept(transport: Box<dyn Transport>, codec: Codec) -> Result<WebSocketTransport> {
+        let reader = transport.try_clone()?;
+        let stream = WsStream {
+            transport,
+            incoming: VecDeque::new(),
+            direct: true,
+        };
+        Ok(WebSocketTransport {
+            reader,
+            state: Arc::new(Mutex::new(WebSocketState {
+                upgrade: Upgrade::Pending(stream),
+                codec,
+                received: VecDeque::new(),
+                written: Buffer::new(),
+            })),
+        })
+    }
+}
+
+impl WebSocketState {
+    // 阻塞读取浏览器的 HTTP 升级请求并回复, 超时由 socket 的读超时决定
+    fn upgrade(&mut self) -> io::Result<&mut WebSocket<WsStream>> {
+        if let Upgrade::Pending(_) = self.upgrade {
+            let stream = match std::mem::replace(&mut self.upgrade, Upgrade::Failed) {
+                Upgrade::Pending(v) => v,
+                _ => unreachable!(),
+            };
+            let max_message_size = self.codec.get_content_max_len() + PACKAGE_OVERHEAD;
+            let config = WebSocketConfig::default().max_message_size(Some(max_message_size));
+            let mut socket = match tungstenite::accept_with_config(stream, Some(config)) {
+                Ok(v) => v,
+                Err(HandshakeError::Interrupted(_)) => {
+                    return Err(io::ErrorKind::TimedOut.into());
+                }
+                Err(HandshakeError::Failure(e)) => return Err(into_io_error(e)),
+            };
+            socket.get_mut().direct = false;
+            self.upgrade = Upgrade::Done(Box::new(socket));
+        }
+        match &mut self.upgrade {
+            Upgrade::Done(socket) => Ok(socket),
+            _ => Err(io::ErrorKind::NotConnected.into()),
+        }
+    }
+
+    // 收到的 Package 按 Codec 编码为帧
+    fn receive(&mut self, data: &[u8]) -> io::Result<()> {
+        let package = Package::parse_from_bytes(data)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let frames = self.codec.encode(package).map_err(into_invalid_data)?;
+        self.received.extend(frames);
+        Ok(())
+    }
+}
+
+impl Read for WebSocketTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            {
+                let mut guard = self.state.lock().unwrap();
+                let state = &mut *guard;
+                if !state.received.is_empty() {
+                    let n = buf.len().min(state.received.len());
+                    for (i, b) in state.received.drain(..n).enumerate() {
+                        buf[i] = b;
+                    }
+                    return Ok(n);
+                }
+                let socket = state.upgrade()?;
+                match socket.read() {
+                    Ok(Message::Binary(data)) => {
+                        state.receive(&data)?;
+                        continue;
+                    }
+                    Ok(Message::Text(_)) => {
+                        debug!("忽略 WebSocket 文本消息");
+                        continue;
+                    }
+                    // Ping/Pong/Close 由 tungstenite 自动回复
+                    Ok(_) => continue,
+                    Err(tungstenite::Error::Io(e)) if e.kind() == io::ErrorKind::WouldBlock => {}
+                    Err(tungstenite::Error::ConnectionClosed)
+                    | Err(tungstenite::Error::AlreadyClosed) => return Ok(0),
+                    Err(e) => return Err(into_io_error(e)),
+                }
+            }
+            let mut data = [0; READ_BUF_LEN];
+            let n = self.reader.read(&mut data)?;
+            if n == 0 {
+                return Ok(0);
+            }
+            let mut state = self.state.lock().unwrap();
+            if let Upgrade::Done(socket) = &mut state.upgrade {
+                socket.get_mut().incoming.extend(&data[..n]);
+            }
+        }
+    }
+}
+
+impl Write for WebSocketTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut guard = self.state.lock().unwrap();
+        let state = &mut *guard;
+        let mut rd = buf;
+        while !rd.is_empty() {
+            state
+                .written
+                .read_from_reader(&mut rd)
+                .map_err(into_invalid_data)?;
+        }
+        let socket = match &mut state.upgrade {
+            Upgrade::Done(socket) => socket,
+            _ => return Err(io::ErrorKind::NotConnected.into()),
+        };
+        loop {
+            let package = match state.codec.decode(&mut state.written) {
+                Ok(p) => p,
+                Err(IMError::NotEnoughData) => break,
+                Err(e) => return Err(into_invalid_data(e)),
+            };
+            let data = package.write_to_bytes()?;
+            socket
+                .write(Message::Binary(data.into()))
+                .map_err(into_io_error)?;
+        }
+        // 发送缓冲区已满时由之后的 flush 继续发送
+        match socket.flush() {
+            Err(tungstenite::Error::Io(e)) if e.kind() == io::ErrorKind::WouldBlock => {}
+            ret => ret.map_err(into_io_error)?,
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        match &mut state.upgrade {
+            Upgrade::Done(socket) => socket.flush().map_err(into_io_error),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Transport for WebSocketTransport {
+    fn try_clone(&self) -> io::Result<Box<dyn Transport>> {
+        Ok(Box::new(WebSocketTransport {
+            reader: self.reader.try_clone()?,
+            state: self.state.clone(),
+        }))
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.reader.set_nonblocking(nonblocking)
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.reader.set_read_timeout(timeout)
+    }
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.reader.set_write_timeout(timeout)
+    }
+
+    fn remote_address(&self) -> String {
+        self.reader.remote_address()
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        {
+            let mut state = self.state.lock().unwrap();
+            if let Upgrade::Done(socket) = &mut state.upgrade {
+                let _ = socket.close(None);
+                let _ = socket.flush();
+            }
+        }
+        self.reader.shutdown()
+    }
+}
+
+// WebSocket 读取的数据先由 WebSocketTransport 从 socket 读出, 只有握手时直接读取 socket
+struct WsStream {
+    transport: Box<dyn Transport>,
+    incoming: VecDeque<u8>,
+    direct: bool,
+}
+
+impl Read for WsStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.incoming.is_empty() {
+            if self.direct {
+                return self.transport.read(buf);
+            }
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        self.incoming.read(buf)
+    }
+}
+
+impl Write for WsStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.transport.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.transport.flush()
+    }
+}
+
+fn into_io_error(e: tungstenite::Error) -> io::Error {
+    match e {
+        tungstenite::Error::Io(e) => e,
+        e => io::Error::new(io::ErrorKind::InvalidData, e),
+    }
+}
+
+fn into_invalid_data(e: IMError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
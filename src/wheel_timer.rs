@@ -1,36 +1,82 @@
-use log::debug;
+use crate::clock::{Clock, SystemClock};
+use crate::IMError;
+use log::{debug, error};
+use std::any::Any;
 use std::cell::{RefCell, RefMut};
+use std::collections::HashMap;
 use std::error::Error;
 use std::ops::Deref;
+use std::panic;
+use std::panic::AssertUnwindSafe;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
 use std::sync::mpsc;
 use std::sync::mpsc::{Receiver, Sender};
 use std::sync::{Arc, Condvar, Mutex};
 use std::thread;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
 
 const WORKER_STATE_INIT: u8 = 0;
 const WORKER_STATE_STARTED: u8 = 1;
 const WORKER_STATE_SHUTDOWN: u8 = 2;
 
+/// WheelTimer 默认的层数, 每高一层每格的时长乘以 ticks_per_wheel.
+pub const DEFAULT_WHEEL_LEVELS: u32 = 4;
+
+/// 定时任务 panic 时的回调, 参数为 panic 的信息.
+pub type PanicHook = Arc<dyn Fn(&str) + Send + Sync>;
+
+type Job = Box<dyn TimerTask + Send>;
+
 #[derive(Clone)]
 pub struct WheelTimer {
     worker_state: Arc<AtomicU8>, // 0 - init, 1 - started, 2 - shutdown
-    start_time: u64,
-    tick_duration: u64, // the duration between tick, time unit is millisecond
+    start_time: Instant,         // 由 Worker 初始化, 任务的到期时间是相对它的毫秒数
+    tick_duration: u64,          // the duration between tick, time unit is millisecond
     ticks_per_wheel: u32,
-    mask: u64,
-    condvar: Arc<(Mutex<u64>, Condvar)>,
+    levels: u32,
+    condvar: Arc<(Mutex<Option<Instant>>, Condvar)>,
     sender: Option<Sender<WheelTimeout>>,
+    cancelled: Option<Sender<u64>>, // 被取消的任务ID, 由 Worker 从 bucket 中移除
+    last_task_id: Arc<AtomicU64>,
+    pending: Arc<AtomicU64>,              // 等待执行的任务数
+    max_pending_timeouts: Arc<AtomicU64>, // 0 表示不限制
+    panic_hook: Arc<Mutex<Option<PanicHook>>>,
+    pool: Arc<Mutex<Option<Sender<Job>>>>, // 执行到期任务的线程池, 没有时在时间轮线程上执行
+    clock: Arc<dyn Clock>,
 }
 
 impl WheelTimer {
     pub fn new(tick_duration: u64, ticks_per_wheel: u32) -> Result<WheelTimer, Box<dyn Error>> {
-        if tick_duration <= 0 {
+        WheelTimer::with_levels(tick_duration, ticks_per_wheel, DEFAULT_WHEEL_LEVELS)
+    }
+
+    /// 创建 levels 层的时间轮, 延迟较长的任务放在高层, 到期前逐层降到最低一层,
+    /// 每个任务最多移动 levels 次. 只有一层时超出一圈的任务每圈都要重新放置.
+    pub fn with_levels(
+        tick_duration: u64,
+        ticks_per_wheel: u32,
+        levels: u32,
+    ) -> Result<WheelTimer, Box<dyn Error>> {
+        WheelTimer::with_clock(
+            tick_duration,
+            ticks_per_wheel,
+            levels,
+            Arc::new(SystemClock),
+        )
+    }
+
+    /// 使用 clock 计算任务的到期时间和推进 tick, 测试时可以传入 MockClock.
+    pub fn with_clock(
+        tick_duration: u64,
+        ticks_per_wheel: u32,
+        levels: u32,
+        clock: Arc<dyn Clock>,
+    ) -> Result<WheelTimer, Box<dyn Error>> {
+        if tick_duration == 0 {
             return Err(format!("tickDuration must be greater than 0: {}", tick_duration).into());
         }
-        if ticks_per_wheel <= 0 {
+        if ticks_per_wheel == 0 {
             return Err(
                 format!("ticksPerWheel must be greater than 0: {}", ticks_per_wheel).into(),
             );
@@ -42,8 +88,10 @@ impl WheelTimer {
             )
             .into());
         }
+        if levels == 0 {
+            return Err(format!("levels must be greater than 0: {}", levels).into());
+        }
         let ticks_per_wheel = normalize_ticks_per_wheel(ticks_per_wheel);
-        let mask = (ticks_per_wheel - 1) as u64;
 
         // Prevent overflow
         if tick_duration >= u64::MAX / ticks_per_wheel as u64 {
@@ -56,12 +104,19 @@ impl WheelTimer {
         }
         let mut timer = WheelTimer {
             worker_state: Arc::new(AtomicU8::new(WORKER_STATE_INIT)),
-            start_time: 0,
+            start_time: clock.now(),
             tick_duration,
             ticks_per_wheel,
-            mask,
-            condvar: Arc::new((Mutex::new(0), Condvar::new())),
+            levels,
+            condvar: Arc::new((Mutex::new(None), Condvar::new())),
             sender: None,
+            cancelled: None,
+            last_task_id: Arc::new(AtomicU64::new(1)),
+            pending: Arc::default(),
+            max_pending_timeouts: Arc::default(),
+            panic_hook: Arc::default(),
+            pool: Arc::default(),
+            clock,
         };
         timer.start().unwrap();
         Ok(timer)
@@ -78,23 +133,15 @@ impl WheelTimer {
                 );
                 match ret {
                     Ok(_) => {
+                        // Worker 只使用共享的状态, 不持有发送端, 停止之后 new_timeout 会发送失败
+                        let timer = self.clone();
                         let (tx, rx) = mpsc::channel();
                         self.sender = Some(tx);
-                        let worker_state = self.worker_state.clone();
-                        let condvar = self.condvar.clone();
-                        let tick_duration = self.tick_duration;
-                        let mask = self.mask;
-                        let ticks_per_wheel = self.ticks_per_wheel;
+                        let (cancelled_tx, cancelled_rx) = mpsc::channel();
+                        self.cancelled = Some(cancelled_tx);
 
                         thread::spawn(move || {
-                            let mut worker = Worker::new(
-                                worker_state,
-                                condvar,
-                                tick_duration,
-                                mask,
-                                ticks_per_wheel,
-                                rx,
-                            );
+                            let mut worker = Worker::new(&timer, rx, cancelled_rx);
                             worker.start();
                         });
                     }
@@ -112,10 +159,13 @@ impl WheelTimer {
         // Wait worker thread initialize start_time finish
         let (lock, condvar) = self.condvar.deref();
         let mut guard = lock.lock()?;
-        while *guard == 0 {
-            guard = condvar.wait(guard)?;
-            self.start_time = *guard;
-        }
+        // Worker 可能在等待之前就已经初始化完成
+        self.start_time = loop {
+            match *guard {
+                Some(v) => break v,
+                None => guard = condvar.wait(guard)?,
+            }
+        };
         Ok(())
     }
 
@@ -135,13 +185,152 @@ impl WheelTimer {
                     .swap(WORKER_STATE_SHUTDOWN, Ordering::SeqCst);
             }
         }
+        // 线程池执行完已经交给它的任务后退出
+        *self.pool.lock().unwrap() = None;
+    }
+
+    /// 时间轮使用的时钟.
+    pub fn get_clock(&self) -> Arc<dyn Clock> {
+        self.clock.clone()
+    }
+
+    /// 等待执行的任务数, 包括还没有放入时间轮的任务.
+    pub fn pending_timeouts(&self) -> u64 {
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    /// 限制等待执行的任务数, 超过后 new_timeout 返回错误, 0 表示不限制. 所有克隆共享这个限制.
+    pub fn set_max_pending_timeouts(&self, max_pending_timeouts: u64) {
+        self.max_pending_timeouts
+            .store(max_pending_timeouts, Ordering::SeqCst);
     }
 
-    pub fn new_timeout(&mut self, task: Box<dyn TimerTask + Send>, delay: Duration) {
-        let deadline = system_time_unix() + delay.as_millis() as u64 - self.start_time;
-        let timeout = WheelTimeout::new(task, deadline);
+    /// 定时任务 panic 时调用 hook, 没有设置时记录错误日志. 任务 panic 不影响其他任务和时间轮.
+    pub fn set_panic_hook<F: Fn(&str) + Send + Sync + 'static>(&self, hook: F) {
+        *self.panic_hook.lock().unwrap() = Some(Arc::new(hook));
+    }
+
+    /// 到期的任务交给 threads 个线程执行, 耗时的任务不会推迟其他任务的 tick.
+    /// 0 表示在时间轮线程上执行. 替换之前的线程池, 之前已经交给它的任务仍会执行.
+    pub fn set_worker_threads(&self, threads: usize) {
+        let pool = match threads {
+            0 => None,
+            _ => {
+                let (tx, rx) = mpsc::channel::<Job>();
+                let rx = Arc::new(Mutex::new(rx));
+                for _ in 0..threads {
+                    let rx = rx.clone();
+                    let panic_hook = self.panic_hook.clone();
+                    thread::spawn(move || loop {
+                        // 线程池被替换或时间轮被释放后退出
+                        let task = match rx.lock().unwrap().recv() {
+                            Ok(v) => v,
+                            Err(_) => return,
+                        };
+                        let hook = panic_hook.lock().unwrap().clone();
+                        run_task(task, &hook);
+                    });
+                }
+                Some(tx)
+            }
+        };
+        *self.pool.lock().unwrap() = pool;
+    }
+
+    /// 延迟 delay 之后执行 task, 返回的 Timeout 可以在任务执行之前取消.
+    /// 等待执行的任务数已达到上限时返回 IMError::TooManyTimeouts, 时间轮已停止时返回 IMError::TimerStopped.
+    pub fn new_timeout(
+        &mut self,
+        task: Box<dyn TimerTask + Send>,
+        delay: Duration,
+    ) -> crate::Result<Timeout> {
+        if self.worker_state.load(Ordering::SeqCst) == WORKER_STATE_SHUTDOWN {
+            return Err(IMError::TimerStopped);
+        }
+        let pending = self.pending.fetch_add(1, Ordering::SeqCst) + 1;
+        let max_pending_timeouts = self.max_pending_timeouts.load(Ordering::SeqCst);
+        if max_pending_timeouts > 0 && pending > max_pending_timeouts {
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+            return Err(IMError::TooManyTimeouts(max_pending_timeouts));
+        }
+        // 使用单调时间, 系统时间调整不影响到期时间
+        let elapsed = self.clock.now().saturating_duration_since(self.start_time);
+        let deadline = (elapsed + delay).as_millis() as u64;
+        let task_id = self.last_task_id.fetch_add(1, Ordering::SeqCst);
+        let state = Arc::new(AtomicU8::new(ST_INIT));
+        let timeout = WheelTimeout::new(task_id, state.clone(), task, deadline);
         let sender = self.sender.as_ref().unwrap();
-        sender.send(timeout).unwrap();
+        // Worker 已退出, 任务不会再执行
+        if sender.send(timeout).is_err() {
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+            return Err(IMError::TimerStopped);
+        }
+        Ok(Timeout {
+            task_id,
+            state,
+            deadline: self.clock.now_millis() + delay.as_millis() as u64,
+            cancelled: self.cancelled.clone().unwrap(),
+        })
+    }
+}
+
+/// WheelTimer::new_timeout 返回的句柄, 用于取消任务或查询任务的状态.
+#[derive(Clone)]
+pub struct Timeout {
+    task_id: u64,
+    state: Arc<AtomicU8>,
+    deadline: u64,
+    cancelled: Sender<u64>,
+}
+
+impl Timeout {
+    /// 取消任务, 任务已经执行或已经取消时返回 false. 取消的任务在下一个 tick 从 bucket 中移除.
+    pub fn cancel(&self) -> bool {
+        let ret =
+            self.state
+                .compare_exchange(ST_INIT, ST_CANCELLED, Ordering::SeqCst, Ordering::Acquire);
+        if ret.is_err() {
+            return false;
+        }
+        // Worker 已停止时任务不会再执行
+        let _ = self.cancelled.send(self.task_id);
+        true
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.state.load(Ordering::SeqCst) == ST_EXPIRED
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.load(Ordering::SeqCst) == ST_CANCELLED
+    }
+
+    /// 创建时估计的执行时间, unix 时间戳, 单位毫秒. 之后系统时间调整时不会更新.
+    pub fn deadline(&self) -> u64 {
+        self.deadline
+    }
+}
+
+// 执行任务, 任务 panic 时调用 hook
+fn run_task(mut task: Job, hook: &Option<PanicHook>) {
+    let ret = panic::catch_unwind(AssertUnwindSafe(|| task.run()));
+    let message = match ret {
+        Ok(_) => return,
+        Err(e) => panic_message(e.as_ref()),
+    };
+    match hook {
+        Some(hook) => hook(&message),
+        None => error!("定时任务 panic: {}", message),
+    }
+}
+
+fn panic_message(e: &(dyn Any + Send)) -> String {
+    if let Some(v) = e.downcast_ref::<&str>() {
+        return v.to_string();
+    }
+    match e.downcast_ref::<String>() {
+        Some(v) => v.clone(),
+        None => "unknown panic".to_string(),
     }
 }
 
@@ -155,52 +344,54 @@ fn normalize_ticks_per_wheel(ticks_per_wheel: u32) -> u32 {
 
 struct Worker {
     worker_state: Arc<AtomicU8>,
-    condvar: Arc<(Mutex<u64>, Condvar)>,
+    condvar: Arc<(Mutex<Option<Instant>>, Condvar)>,
     tick: u64,
     tick_duration: u64,
     mask: u64,
-    wheel: Vec<WheelBucket>,
-    start_time: u64,
+    bits: u32,                     // 每层格数的二进制位数
+    wheels: Vec<Vec<WheelBucket>>, // 第 n 层每格的时长为 tick_duration * ticks_per_wheel^n
+    start_time: Instant,
     receiver: Receiver<WheelTimeout>,
-    last_task_id: AtomicU64,
+    cancelled: Receiver<u64>,
+    timeouts: HashMap<u64, Rc<RefCell<BucketTimeout>>>, // 还在 bucket 中的任务, key => task_id
+    pending: Arc<AtomicU64>,
+    panic_hook: Arc<Mutex<Option<PanicHook>>>,
+    pool: Arc<Mutex<Option<Sender<Job>>>>,
+    clock: Arc<dyn Clock>,
 }
 
 impl Worker {
-    fn new(
-        worker_state: Arc<AtomicU8>,
-        condvar: Arc<(Mutex<u64>, Condvar)>,
-        tick_duration: u64,
-        mask: u64,
-        ticks_per_wheel: u32,
-        rx: Receiver<WheelTimeout>,
-    ) -> Worker {
-        let wheel = create_wheel(ticks_per_wheel).unwrap();
+    fn new(timer: &WheelTimer, rx: Receiver<WheelTimeout>, cancelled: Receiver<u64>) -> Worker {
+        let ticks_per_wheel = timer.ticks_per_wheel as u64;
+        let wheels = create_wheels(timer.ticks_per_wheel, timer.levels);
 
         Worker {
-            worker_state,
-            condvar,
+            worker_state: timer.worker_state.clone(),
+            condvar: timer.condvar.clone(),
             tick: 0,
-            tick_duration,
-            mask,
-            wheel,
-            start_time: 0,
+            tick_duration: timer.tick_duration,
+            mask: ticks_per_wheel - 1,
+            bits: ticks_per_wheel.trailing_zeros(),
+            wheels,
+            start_time: timer.start_time,
             receiver: rx,
-            last_task_id: AtomicU64::new(1),
+            cancelled,
+            timeouts: HashMap::new(),
+            pending: timer.pending.clone(),
+            panic_hook: timer.panic_hook.clone(),
+            pool: timer.pool.clone(),
+            clock: timer.clock.clone(),
         }
     }
 
     fn start(&mut self) {
         // Initialize the startTime.
-        let mut start_time = system_time_unix();
-        if start_time == 0 {
-            start_time = 1;
-        }
-        self.start_time = start_time;
+        self.start_time = self.clock.now();
 
         // Notify the other thread waiting for the initialization at start()
         let (lock, condvar) = self.condvar.deref();
         let mut guard = lock.lock().unwrap();
-        *guard = start_time;
+        *guard = Some(self.start_time);
         condvar.notify_one();
         drop(guard);
 
@@ -208,25 +399,29 @@ impl Worker {
             let deadline = self.wait_for_next_tick();
             if deadline > 0 {
                 self.transfer_timeouts_to_buckets();
-                let idx = self.tick & self.mask;
-                let bucket = self.wheel.get_mut(idx as usize).unwrap();
-                bucket.expire_timeouts(deadline);
+                self.process_cancelled_tasks();
+                self.cascade();
+                self.expire_timeouts();
                 self.tick += 1;
             }
         }
+        self.clear();
         debug!("Worker shutdown")
     }
 
+    // 等到下一个 tick 的结束时间, 返回相对 start_time 的毫秒数, 已停止时返回 0
     fn wait_for_next_tick(&self) -> u64 {
-        let deadline = (self.tick_duration * (self.tick + 1)) as i64;
+        let deadline = Duration::from_millis(self.tick_duration * (self.tick + 1));
         loop {
-            let current_time = (system_time_unix() - self.start_time) as i64;
-            let sleep_time_ms = (deadline - current_time + 999999) / 1000000;
-
-            if sleep_time_ms <= 0 {
-                return current_time as u64;
+            // 时钟可能不再前进, 停止后不再等待
+            if self.worker_state.load(Ordering::SeqCst) != WORKER_STATE_STARTED {
+                return 0;
+            }
+            let current_time = self.clock.now().saturating_duration_since(self.start_time);
+            if current_time >= deadline {
+                return current_time.as_millis() as u64;
             }
-            thread::sleep(Duration::new(0, (sleep_time_ms * 1000000) as u32));
+            self.clock.sleep(deadline - current_time);
         }
     }
 
@@ -234,22 +429,20 @@ impl Worker {
         for _ in 0..100000 {
             match self.receiver.try_recv() {
                 Ok(timeout) => {
-                    let task_id = self.last_task_id.fetch_add(1, Ordering::SeqCst);
-                    let calculated = timeout.deadline / self.tick_duration;
-
-                    let mut bucket_timeout =
-                        BucketTimeout::new(task_id, timeout.task, timeout.deadline);
-                    bucket_timeout.remaining_rounds =
-                        (calculated - self.tick) / self.wheel.len() as u64;
-
-                    let mut ticks = self.tick;
-                    if calculated > self.tick {
-                        ticks = calculated;
+                    // 放入 bucket 之前已经取消
+                    if timeout.state.load(Ordering::SeqCst) == ST_CANCELLED {
+                        self.pending.fetch_sub(1, Ordering::SeqCst);
+                        continue;
                     }
-                    let stop_index = ticks & self.mask;
-
-                    let bucket = self.wheel.get_mut(stop_index as usize).unwrap();
-                    bucket.add_timeout(bucket_timeout);
+                    let bucket_timeout = BucketTimeout::new(
+                        timeout.task_id,
+                        timeout.state,
+                        timeout.task,
+                        timeout.deadline,
+                    );
+                    let rc_timeout = Rc::new(RefCell::new(bucket_timeout));
+                    self.timeouts.insert(timeout.task_id, rc_timeout.clone());
+                    self.add_timeout(rc_timeout);
                 }
                 Err(_) => {
                     break;
@@ -257,17 +450,142 @@ impl Worker {
             }
         }
     }
+
+    // 按到期的 tick 放入对应的层, 距离越远层次越高. 超出最高一层范围的任务
+    // 放在最后经过的格子, 级联时重新计算位置
+    fn add_timeout(&mut self, timeout: Rc<RefCell<BucketTimeout>>) {
+        let calculated = RefCell::borrow(&timeout).deadline / self.tick_duration;
+        let mut expiration = calculated.max(self.tick);
+        let diff = expiration - self.tick;
+        let levels = self.wheels.len();
+        let mut level = 0;
+        while level + 1 < levels && shr(diff, self.bits * (level as u32 + 1)) != 0 {
+            level += 1;
+        }
+        if shr(diff, self.bits * levels as u32) != 0 {
+            expiration = self.tick + (1 << (self.bits * levels as u32)) - 1;
+        }
+        let idx = (shr(expiration, self.bits * level as u32) & self.mask) as usize;
+        {
+            let mut timeout_mut = RefCell::borrow_mut(&timeout);
+            timeout_mut.level = level;
+            timeout_mut.bucket = idx;
+        }
+        self.wheels[level][idx].add_timeout(timeout);
+    }
+
+    // 从 bucket 中移除已取消的任务, 释放任务占用的资源
+    fn process_cancelled_tasks(&mut self) {
+        while let Ok(task_id) = self.cancelled.try_recv() {
+            // 还没有放入 bucket 的任务在 transfer 时丢弃
+            let timeout = match self.timeouts.remove(&task_id) {
+                Some(v) => v,
+                None => continue,
+            };
+            let (level, idx) = {
+                let timeout = RefCell::borrow(&timeout);
+                (timeout.level, timeout.bucket)
+            };
+            self.wheels[level][idx].remove(RefCell::borrow_mut(&timeout));
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    // tick 转到高层某一格的起点时, 把这一格的任务重新放到更低的层, 从最高层开始
+    fn cascade(&mut self) {
+        for level in (1..self.wheels.len()).rev() {
+            let shift = self.bits * level as u32;
+            if self.tick & low_mask(shift) != 0 {
+                continue;
+            }
+            let idx = (shr(self.tick, shift) & self.mask) as usize;
+            let mut bucket = self.wheels[level][idx].take();
+            while let Some(timeout) = bucket.poll() {
+                if RefCell::borrow(&timeout).is_cancelled() {
+                    self.discard(&timeout);
+                } else {
+                    self.add_timeout(timeout);
+                }
+            }
+        }
+    }
+
+    // 执行最低一层当前格子里到期的任务, 有线程池时交给线程池执行
+    fn expire_timeouts(&mut self) {
+        let idx = (self.tick & self.mask) as usize;
+        let mut bucket = self.wheels[0][idx].take();
+        if bucket.head.is_none() {
+            return;
+        }
+        let hook = self.panic_hook.lock().unwrap().clone();
+        let pool = self.pool.lock().unwrap().clone();
+        while let Some(timeout) = bucket.poll() {
+            let calculated = RefCell::borrow(&timeout).deadline / self.tick_duration;
+            let cancelled = RefCell::borrow(&timeout).is_cancelled();
+            // 超出最高一层范围的任务还没有到期
+            if calculated > self.tick && !cancelled {
+                self.add_timeout(timeout);
+                continue;
+            }
+            self.discard(&timeout);
+            let task = match RefCell::borrow_mut(&timeout).expire() {
+                Some(v) => v,
+                None => continue,
+            };
+            match &pool {
+                Some(pool) => {
+                    if let Err(e) = pool.send(task) {
+                        run_task(e.0, &hook);
+                    }
+                }
+                None => run_task(task, &hook),
+            }
+        }
+    }
+
+    // 停止后丢弃所有没有执行的任务, 断开 bucket 中的循环引用
+    fn clear(&mut self) {
+        for level in 0..self.wheels.len() {
+            for idx in 0..self.wheels[level].len() {
+                let mut bucket = self.wheels[level][idx].take();
+                while let Some(timeout) = bucket.poll() {
+                    self.discard(&timeout);
+                }
+            }
+        }
+    }
+
+    // 任务离开时间轮, 不再计入等待执行的任务
+    fn discard(&mut self, timeout: &Rc<RefCell<BucketTimeout>>) {
+        self.timeouts.remove(&RefCell::borrow(timeout).task_id);
+        self.pending.fetch_sub(1, Ordering::SeqCst);
+    }
 }
 
-fn create_wheel(ticks_per_wheel: u32) -> Result<Vec<WheelBucket>, Box<dyn Error>> {
-    let mut wheel = Vec::with_capacity(ticks_per_wheel as usize);
-    for _ in 0..ticks_per_wheel {
-        wheel.push(WheelBucket {
-            head: None,
-            tail: None,
-        })
+fn shr(value: u64, bits: u32) -> u64 {
+    value.checked_shr(bits).unwrap_or(0)
+}
+
+fn low_mask(bits: u32) -> u64 {
+    match 1u64.checked_shl(bits) {
+        Some(v) if bits < 64 => v - 1,
+        _ => u64::MAX,
+    }
+}
+
+fn create_wheels(ticks_per_wheel: u32, levels: u32) -> Vec<Vec<WheelBucket>> {
+    let mut wheels = Vec::with_capacity(levels as usize);
+    for _ in 0..levels {
+        let mut wheel = Vec::with_capacity(ticks_per_wheel as usize);
+        for _ in 0..ticks_per_wheel {
+            wheel.push(WheelBucket {
+                head: None,
+                tail: None,
+            })
+        }
+        wheels.push(wheel);
     }
-    Ok(wheel)
+    wheels
 }
 
 pub fn system_time_unix() -> u64 {
@@ -283,66 +601,36 @@ pub struct WheelBucket {
 }
 
 impl WheelBucket {
-    fn add_timeout(&mut self, mut timeout: BucketTimeout) {
-        match self.head.as_ref() {
+    fn add_timeout(&mut self, timeout: Rc<RefCell<BucketTimeout>>) {
+        match self.tail.take() {
             None => {
-                let rc_timeout = Rc::new(RefCell::new(timeout));
-                self.head = Some(rc_timeout.clone());
-                self.tail = Some(rc_timeout);
+                self.head = Some(timeout.clone());
             }
-            Some(_) => {
-                let rc_tail = self.tail.as_ref().unwrap().clone();
-                timeout.prev = Some(rc_tail);
-                let rc_timeout = Rc::new(RefCell::new(timeout));
-                {
-                    let mut tail = self.tail.as_ref().unwrap().deref().borrow_mut();
-                    tail.next = Some(rc_timeout.clone());
-                }
-                self.tail = Some(rc_timeout);
+            Some(tail) => {
+                RefCell::borrow_mut(&timeout).prev = Some(tail.clone());
+                RefCell::borrow_mut(&tail).next = Some(timeout.clone());
             }
         }
+        self.tail = Some(timeout);
     }
 
-    fn expire_timeouts(&mut self, deadline: u64) {
-        let mut current = self.head.clone();
-        loop {
-            match current {
-                None => {
-                    return;
-                }
-                Some(timeout) => {
-                    let mut next = RefCell::borrow(&timeout).next.clone();
-
-                    let mut timeout_mut = RefCell::borrow_mut(&timeout);
-                    if timeout_mut.remaining_rounds <= 0 {
-                        next = self.remove(timeout_mut);
-
-                        let mut timeout_mut = RefCell::borrow_mut(&timeout);
-                        timeout_mut.prev = None;
-                        timeout_mut.next = None;
-                        if timeout_mut.deadline <= deadline {
-                            timeout_mut.expire();
-                        } else {
-                            // The timeout was placed into a wrong slot. This should never happen.
-                            panic!(
-                                "timeout.deadline {} > deadline {}",
-                                timeout_mut.deadline, deadline
-                            )
-                        }
-                    } else if timeout_mut.is_cancelled() {
-                        next = self.remove(timeout_mut);
-                    } else {
-                        timeout_mut.remaining_rounds -= 1;
-                    }
-                    current = next;
-                }
-            }
+    // 取出所有任务, 处理过程中重新放回的任务不会再次取出
+    fn take(&mut self) -> WheelBucket {
+        WheelBucket {
+            head: self.head.take(),
+            tail: self.tail.take(),
         }
     }
 
-    fn remove(&mut self, timeout: RefMut<BucketTimeout>) -> Option<Rc<RefCell<BucketTimeout>>> {
-        let prev = timeout.prev.clone();
-        let next = timeout.next.clone();
+    fn poll(&mut self) -> Option<Rc<RefCell<BucketTimeout>>> {
+        let head = self.head.clone()?;
+        self.remove(RefCell::borrow_mut(&head));
+        Some(head)
+    }
+
+    fn remove(&mut self, mut timeout: RefMut<BucketTimeout>) -> Option<Rc<RefCell<BucketTimeout>>> {
+        let prev = timeout.prev.take();
+        let next = timeout.next.take();
         match prev.clone() {
             None => {}
             Some(v) => {
@@ -383,22 +671,29 @@ const ST_EXPIRED: u8 = 2;
 
 struct BucketTimeout {
     task_id: u64,
-    state: AtomicU8, // 0: init, 1: cancelled, 2: expired
+    state: Arc<AtomicU8>, // 0: init, 1: cancelled, 2: expired, 与 Timeout 共享
     deadline: u64,
-    remaining_rounds: u64,
-    task: Box<dyn TimerTask + Send>,
+    level: usize,      // 所在的层
+    bucket: usize,     // 所在 bucket 的下标
+    task: Option<Job>, // 到期时取出执行
     prev: Option<Rc<RefCell<BucketTimeout>>>,
     next: Option<Rc<RefCell<BucketTimeout>>>,
 }
 
 impl BucketTimeout {
-    fn new(task_id: u64, task: Box<dyn TimerTask + Send>, deadline: u64) -> BucketTimeout {
+    fn new(
+        task_id: u64,
+        state: Arc<AtomicU8>,
+        task: Box<dyn TimerTask + Send>,
+        deadline: u64,
+    ) -> BucketTimeout {
         BucketTimeout {
             task_id,
-            state: AtomicU8::new(ST_INIT),
+            state,
             deadline,
-            task,
-            remaining_rounds: 0,
+            task: Some(task),
+            level: 0,
+            bucket: 0,
             prev: None,
             next: None,
         }
@@ -413,20 +708,17 @@ impl BucketTimeout {
     }
 
     fn compare_exchange(&self, expected: u8, state: u8) -> bool {
-        let ret = self
-            .state
-            .compare_exchange(expected, state, Ordering::SeqCst, Ordering::Acquire);
-        match ret {
-            Err(_) => false,
-            Ok(_) => true,
-        }
+        self.state
+            .compare_exchange(expected, state, Ordering::SeqCst, Ordering::Acquire)
+            .is_ok()
     }
 
-    fn expire(&mut self) {
+    // 取出需要执行的任务, 已经取消时返回 None
+    fn expire(&mut self) -> Option<Job> {
         if !self.compare_exchange(ST_INIT, ST_EXPIRED) {
-            return;
+            return None;
         }
-        self.task.run();
+        self.task.take()
     }
 }
 
@@ -435,12 +727,24 @@ pub trait TimerTask {
 }
 
 struct WheelTimeout {
+    task_id: u64,
+    state: Arc<AtomicU8>,
     deadline: u64,
     task: Box<dyn TimerTask + Send>,
 }
 
 impl WheelTimeout {
-    fn new(task: Box<dyn TimerTask + Send>, deadline: u64) -> WheelTimeout {
-        WheelTimeout { deadline, task }
+    fn new(
+        task_id: u64,
+        state: Arc<AtomicU8>,
+        task: Box<dyn TimerTask + Send>,
+        deadline: u64,
+    ) -> WheelTimeout {
+        WheelTimeout {
+            task_id,
+            state,
+            deadline,
+            task,
+        }
     }
 }
@@ -0,0 +1,233 @@
+use crate::proto::Package;
+use crate::{Connection, IMError, Result};
+use log::{debug, warn};
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::ops::Deref;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// 发送队列默认最多缓存1024个数据包.
+pub const DEFAULT_WRITE_QUEUE_CAPACITY: usize = 1024;
+
+/// 发送队列已满时对新数据包的处理策略.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowPolicy {
+    /// 丢弃新的数据包, 推送的消息仍会由重传任务重试
+    Drop,
+    /// 接收方太慢, 断开连接
+    Disconnect,
+    /// 推送的消息转入离线队列, 其他数据包丢弃
+    #[default]
+    SpillOffline,
+}
+
+struct QueueState {
+    packages: VecDeque<Package>,
+    closed: bool,
+    writing: bool, // 写线程正在写出取出的数据包
+}
+
+/// 连接的发送队列, 由一个写线程(或事件循环)按顺序写出, 其他线程只负责入队,
+/// 不会被慢速的接收方阻塞, 也不会交错写入各自数据包的字节.
+#[derive(Clone)]
+pub struct WriteQueue {
+    state: Arc<(Mutex<QueueState>, Condvar)>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    notify: Option<Arc<dyn Fn() + Send + Sync>>, // 由事件循环写出时, 通知有新的数据包或队列已关闭
+}
+
+impl WriteQueue {
+    /// 创建发送队列并启动写线程, 写线程是之后唯一向 connection 写数据的线程,
+    /// 单个数据包超过 write_timeout 仍未写完时关闭连接.
+    pub fn start(
+        connection: Connection,
+        capacity: usize,
+        policy: OverflowPolicy,
+        write_timeout: Duration,
+    ) -> WriteQueue {
+        let queue = WriteQueue::new(capacity, policy);
+        queue.spawn_writer(connection, write_timeout);
+        queue
+    }
+
+    /// 为 new 创建的队列启动写线程, 之前入队的数据包同样会写出.
+    pub fn spawn_writer(&self, connection: Connection, write_timeout: Duration) {
+        let writer = Writer {
+            queue: self.clone(),
+            connection,
+            write_timeout,
+        };
+        thread::spawn(move || writer.run());
+    }
+
+    /// 创建由事件循环写出的发送队列: 空队列有新的数据包或者队列关闭时调用 notify,
+    /// 事件循环随后用 try_pop 取出数据包, 在连接可写时写出.
+    pub fn with_notify(
+        capacity: usize,
+        policy: OverflowPolicy,
+        notify: Arc<dyn Fn() + Send + Sync>,
+    ) -> WriteQueue {
+        WriteQueue {
+            notify: Some(notify),
+            ..WriteQueue::new(capacity, policy)
+        }
+    }
+
+    /// 创建没有写线程的发送队列, 数据包只会留在队列中, 用于测试或压测会话管理.
+    pub fn new(capacity: usize, policy: OverflowPolicy) -> WriteQueue {
+        WriteQueue {
+            state: Arc::new((
+                Mutex::new(QueueState {
+                    packages: VecDeque::new(),
+                    closed: false,
+                    writing: false,
+                }),
+                Condvar::new(),
+            )),
+            capacity,
+            policy,
+            notify: None,
+        }
+    }
+
+    /// 数据包入队, 队列已满时按 OverflowPolicy 处理并返回 WriteQueueFull.
+    pub fn push(&self, package: Package) -> Result<()> {
+        let (lock, condvar) = self.state.deref();
+        let mut state = lock.lock().unwrap();
+        if state.closed {
+            return Err(IMError::ConnectionClosed);
+        }
+        if state.packages.len() >= self.capacity {
+            if self.policy == OverflowPolicy::Disconnect {
+                debug!("发送队列已满, 断开连接");
+                state.closed = true;
+                state.packages.clear();
+                condvar.notify_all();
+                drop(state);
+                self.notify();
+            }
+            return Err(IMError::WriteQueueFull);
+        }
+        state.packages.push_back(package);
+        // 正在写出时, 写完之后会继续取出新的数据包, 不需要通知
+        let idle = state.packages.len() == 1 && !state.writing;
+        condvar.notify_all();
+        drop(state);
+        if idle {
+            self.notify();
+        }
+        Ok(())
+    }
+
+    /// 关闭队列, 丢弃未写出的数据包, 写线程随即退出.
+    pub fn close(&self) {
+        let (lock, condvar) = self.state.deref();
+        let mut state = lock.lock().unwrap();
+        let closed = state.closed;
+        state.closed = true;
+        state.packages.clear();
+        condvar.notify_all();
+        drop(state);
+        if !closed {
+            self.notify();
+        }
+    }
+
+    /// 由事件循环写出时取出下一个数据包, 队列为空或已关闭时返回 None.
+    /// 取出的数据包写完之后才能再次调用, flush 据此判断数据包是否已经写出.
+    pub fn try_pop(&self) -> Option<Package> {
+        let (lock, condvar) = self.state.deref();
+        let mut state = lock.lock().unwrap();
+        let package = match state.closed {
+            true => None,
+            false => state.packages.pop_front(),
+        };
+        state.writing = package.is_some();
+        condvar.notify_all();
+        package
+    }
+
+    fn notify(&self) {
+        if let Some(notify) = self.notify.as_ref() {
+            notify();
+        }
+    }
+
+    /// 等待队列中的数据包全部写出, 队列关闭时立即返回, 超时返回 false.
+    pub fn flush(&self, timeout: Duration) -> bool {
+        let (lock, condvar) = self.state.deref();
+        let state = lock.lock().unwrap();
+        let (state, _) = condvar
+            .wait_timeout_while(state, timeout, |state| {
+                !state.closed && (state.writing || !state.packages.is_empty())
+            })
+            .unwrap();
+        state.closed || (!state.writing && state.packages.is_empty())
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.state.0.lock().unwrap().closed
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.0.lock().unwrap().packages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get_policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    // 取出下一个数据包, 队列关闭后返回 None
+    fn pop(&self) -> Option<Package> {
+        let (lock, condvar) = self.state.deref();
+        let mut state = lock.lock().unwrap();
+        state.writing = false;
+        condvar.notify_all();
+        loop {
+            if state.closed {
+                return None;
+            }
+            if let Some(package) = state.packages.pop_front() {
+                state.writing = true;
+                return Some(package);
+            }
+            state = condvar.wait(state).unwrap();
+        }
+    }
+}
+
+// 写线程
+struct Writer {
+    queue: WriteQueue,
+    connection: Connection,
+    write_timeout: Duration,
+}
+
+impl Writer {
+    fn run(mut self) {
+        while let Some(package) = self.queue.pop() {
+            match self.connection.write_package(package, self.write_timeout) {
+                Ok(()) => {}
+                // 协议版本较低的客户端无法接收过长的消息, 没有写出任何数据, 连接仍然可用
+                Err(IMError::ContentMaxLen) => {
+                    warn!("数据包超过对端支持的最大长度, 丢弃");
+                }
+                Err(e) => {
+                    debug!("写入失败, 关闭连接: {}", e);
+                    self.queue.close();
+                    break;
+                }
+            }
+        }
+        // 队列关闭时连接不再可用, 断开后读线程会清理会话
+        self.connection.shutdown();
+    }
+}
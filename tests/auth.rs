@@ -0,0 +1,68 @@
+mod common;
+
+use cathy::proto::{Action, ConnectedReply, Login, LoginFailedReply, Package};
+use cathy::{Authenticator, FileUserStore};
+use protobuf::Message;
+use std::time::Duration;
+
+fn login(username: &str, password: &str, token: &str) -> Login {
+    let mut login = Login::new();
+    login.set_username(username.to_string());
+    login.set_password(password.to_string());
+    login.set_token(token.to_string());
+    login
+}
+
+#[test]
+fn test_file_user_store() {
+    let store = common::user_store();
+    assert_eq!(store.len(), 7);
+    assert_eq!(
+        store.authenticate(&login("cathy", "cathy123", "")).unwrap(),
+        1
+    );
+    assert_eq!(store.authenticate(&login("", "", "mars-token")).unwrap(), 2);
+    assert!(store.authenticate(&login("cathy", "wrong", "")).is_err());
+    assert!(store
+        .authenticate(&login("nobody", "cathy123", ""))
+        .is_err());
+    assert!(store.authenticate(&login("", "", "bad-token")).is_err());
+
+    assert!(FileUserStore::parse("0 cathy cathy123").is_err());
+    assert!(FileUserStore::parse("1 cathy").is_err());
+    assert!(FileUserStore::parse("1 cathy a\n2 cathy b").is_err());
+}
+
+#[test]
+fn test_login_handshake() {
+    let address = &common::start_server(common::server_config());
+
+    // 认证成功, uid 在多次登录之间保持不变
+    for _ in 0..2 {
+        let mut connection = common::connect(address);
+        let reply = common::login(&mut connection, "cathy", "cathy123");
+        assert_eq!(reply.get_action(), Action::CONNECTED);
+        let connected = ConnectedReply::parse_from_bytes(reply.get_content()).unwrap();
+        assert_eq!(connected.get_uid(), 1);
+        assert!(!connected.get_session_id().is_empty());
+    }
+
+    // 认证失败, 回复失败原因后断开连接
+    let mut connection = common::connect(address);
+    let reply = common::login(&mut connection, "cathy", "wrong");
+    assert_eq!(reply.get_action(), Action::LOGIN_FAILED);
+    let failed = LoginFailedReply::parse_from_bytes(reply.get_content()).unwrap();
+    assert!(!failed.get_reason().is_empty());
+    assert!(connection.read_package().is_err());
+
+    // 未登录就发送其他消息
+    let mut connection = common::connect(address);
+    let mut package = Package::new();
+    package.set_action(Action::HEARTBEAT);
+    connection
+        .write_package(package, Duration::from_secs(1))
+        .unwrap();
+    let reply = connection.read_package().unwrap();
+    assert_eq!(reply.get_action(), Action::LOGIN_FAILED);
+    assert!(connection.read_package().is_err());
+}
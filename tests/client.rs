@@ -0,0 +1,128 @@
+mod common;
+
+use cathy::{ClientConfig, Event, IMClient, SendState};
+use std::net::TcpListener;
+use std::sync::mpsc::Receiver;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+// 所有测试共用一个服务端
+fn address() -> &'static str {
+    static ADDRESS: OnceLock<String> = OnceLock::new();
+    ADDRESS.get_or_init(|| common::start_server(common::server_config()))
+}
+
+fn connect() -> (IMClient, Receiver<Event>) {
+    common::connect_client(address(), ClientConfig::default())
+}
+
+fn next_event(events: &Receiver<Event>) -> Event {
+    events.recv_timeout(Duration::from_secs(2)).unwrap()
+}
+
+#[test]
+fn test_client_message() {
+    let (mut cathy, cathy_events) = connect();
+    let (mut mars, mars_events) = connect();
+    cathy.login("cathy", "cathy123").unwrap();
+    mars.login("mars", "mars123").unwrap();
+    match next_event(&cathy_events) {
+        Event::Connected(reply) => assert_eq!(reply.get_uid(), 1),
+        e => panic!("unexpected event {:?}", e),
+    }
+    assert!(matches!(next_event(&mars_events), Event::Connected(_)));
+
+    let handle = cathy.send_to_user(2, "hello").unwrap();
+    match next_event(&mars_events) {
+        Event::Message(msg) => {
+            assert_eq!(msg.get_sender_uid(), 1);
+            assert_eq!(msg.get_content(), "hello");
+            mars.read_receipt(msg.get_message_id()).unwrap();
+        }
+        e => panic!("unexpected event {:?}", e),
+    }
+    match next_event(&cathy_events) {
+        Event::Accepted { seq, message_id } => {
+            assert_eq!(seq, handle.get_seq());
+            assert_eq!(message_id, handle.get_message_id());
+        }
+        e => panic!("unexpected event {:?}", e),
+    }
+    match next_event(&cathy_events) {
+        Event::Delivered { seq, receiver_uid } => {
+            assert_eq!((seq, receiver_uid), (handle.get_seq(), 2))
+        }
+        e => panic!("unexpected event {:?}", e),
+    }
+    assert!(matches!(next_event(&cathy_events), Event::Read { .. }));
+    assert_eq!(handle.state(), Some(SendState::Read));
+    // 已回复过已读的消息不能再次回复
+    assert!(mars.read_receipt(handle.get_message_id()).is_err());
+
+    mars.close();
+    assert!(matches!(next_event(&mars_events), Event::Disconnected(_)));
+    assert!(mars_events
+        .recv_timeout(Duration::from_millis(100))
+        .is_err());
+    assert!(mars.send_to_user(1, "closed").is_err());
+}
+
+#[test]
+fn test_client_login_failed() {
+    let (mut client, events) = connect();
+    client.login("cathy", "wrong").unwrap();
+    assert!(matches!(next_event(&events), Event::Disconnected(_)));
+}
+
+#[test]
+fn test_client_callback() {
+    let (mut client, _) = connect();
+    // 事件只能取出一次
+    assert!(client.events().is_none());
+    assert!(client.on_event(|_| {}).is_err());
+
+    let mut client = IMClient::connect(address(), ClientConfig::default()).unwrap();
+    let (sender, receiver) = std::sync::mpsc::channel();
+    client
+        .on_event(move |event| {
+            let _ = sender.send(event);
+        })
+        .unwrap();
+    client.login("venus", "venus123").unwrap();
+    assert!(matches!(next_event(&receiver), Event::Connected(_)));
+}
+
+#[test]
+fn test_client_room_message() {
+    let (mut luna, events) = connect();
+    luna.login("luna", "luna123").unwrap();
+    assert!(matches!(next_event(&events), Event::Connected(_)));
+    luna.create_room("lounge").unwrap();
+    let room_id = match next_event(&events) {
+        Event::RoomReply(reply) if reply.get_success() => reply.get_room_id(),
+        e => panic!("unexpected event {:?}", e),
+    };
+
+    // 聊天室消息的确认不会当作单聊消息的 Accepted
+    let seq = luna.send_to_room(room_id, "hello").unwrap();
+    match next_event(&events) {
+        Event::RoomAccepted {
+            seq: accepted,
+            room_id: id,
+            ..
+        } => assert_eq!((accepted, id), (seq, room_id)),
+        e => panic!("unexpected event {:?}", e),
+    }
+    assert_eq!(luna.message_state(seq), None);
+}
+
+#[test]
+fn test_client_connect_failed() {
+    // 没有服务端监听的端口
+    let address = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .to_string();
+    assert!(IMClient::connect(&address, ClientConfig::default()).is_err());
+}
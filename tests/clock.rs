@@ -0,0 +1,316 @@
+mod common;
+
+use cathy::proto::{Action, Package};
+use cathy::{
+    ClientConfig, Clock, Codec, Connection, IMClient, IMServer, MockClock, SystemClock, TimerTask,
+    Transport, WheelTimer,
+};
+use std::io::{self, Read, Write};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const HOUR: u64 = 3_600_000;
+
+struct FlagTask {
+    fired: Arc<AtomicBool>,
+}
+
+impl TimerTask for FlagTask {
+    fn run(&mut self) {
+        self.fired.store(true, Ordering::SeqCst);
+    }
+}
+
+// 记录每次 sleep 的时长
+#[derive(Default)]
+struct RecordClock {
+    sleeps: Mutex<Vec<Duration>>,
+}
+
+impl Clock for RecordClock {
+    fn now(&self) -> Instant {
+        SystemClock.now()
+    }
+
+    fn now_millis(&self) -> u64 {
+        SystemClock.now_millis()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.sleeps.lock().unwrap().push(duration);
+        SystemClock.sleep(duration);
+    }
+}
+
+// 发送缓冲区一直是满的
+struct FullTransport;
+
+impl Read for FullTransport {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Err(io::ErrorKind::WouldBlock.into())
+    }
+}
+
+impl Write for FullTransport {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        Err(io::ErrorKind::WouldBlock.into())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Transport for FullTransport {
+    fn try_clone(&self) -> io::Result<Box<dyn Transport>> {
+        Ok(Box::new(FullTransport))
+    }
+
+    fn set_nonblocking(&self, _nonblocking: bool) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_read_timeout(&self, _timeout: Option<Duration>) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_write_timeout(&self, _timeout: Option<Duration>) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn remote_address(&self) -> String {
+        "full".to_string()
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn start_server(clock: Arc<MockClock>) -> String {
+    let mut server =
+        IMServer::new(common::server_config(), Box::new(common::user_store())).unwrap();
+    server.set_clock(clock);
+    common::spawn(server)
+}
+
+fn login(address: &str) -> (Connection, Receiver<Option<Action>>) {
+    let connection = common::login_as(address, "cathy");
+    let receiver = receive(connection.clone());
+    (connection, receiver)
+}
+
+fn wait_until<F: Fn() -> bool>(f: F) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while Instant::now() < deadline {
+        if f() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    false
+}
+
+// 在单独的线程中读取数据包, 连接断开时发送 None
+fn receive(mut connection: Connection) -> Receiver<Option<Action>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || loop {
+        match connection.read_package() {
+            Ok(package) => {
+                if sender.send(Some(package.get_action())).is_err() {
+                    return;
+                }
+            }
+            Err(_) => {
+                let _ = sender.send(None);
+                return;
+            }
+        }
+    });
+    receiver
+}
+
+// 跳过其他数据包, 等待 action 或连接断开(None)
+fn wait_for(receiver: &Receiver<Option<Action>>, action: Option<Action>) {
+    let deadline = Instant::now() + Duration::from_secs(2);
+    loop {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        if receiver.recv_timeout(timeout).unwrap() == action {
+            return;
+        }
+    }
+}
+
+// 一段时间内没有收到 action
+fn assert_quiet(receiver: &Receiver<Option<Action>>, action: Option<Action>) {
+    let deadline = Instant::now() + Duration::from_millis(300);
+    loop {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match receiver.recv_timeout(timeout) {
+            Ok(v) => assert_ne!(v, action),
+            Err(RecvTimeoutError::Timeout) => return,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+#[test]
+fn test_mock_clock_timeout() {
+    let clock = Arc::new(MockClock::new());
+    let mut timer = WheelTimer::with_clock(100, 64, 4, clock.clone()).unwrap();
+    let fired = Arc::new(AtomicBool::new(false));
+    let task = FlagTask {
+        fired: fired.clone(),
+    };
+    let start = Instant::now();
+    let timeout = timer
+        .new_timeout(Box::new(task), Duration::from_secs(60))
+        .unwrap();
+    assert_eq!(timeout.deadline(), clock.now_millis() + 60_000);
+
+    clock.advance(Duration::from_secs(59));
+    thread::sleep(Duration::from_millis(100));
+    assert!(!fired.load(Ordering::SeqCst));
+
+    // 到期时间所在的 tick 结束时执行
+    clock.advance(Duration::from_millis(1100));
+    assert!(wait_until(|| fired.load(Ordering::SeqCst)));
+    assert!(timeout.is_expired());
+    assert!(start.elapsed() < Duration::from_secs(2));
+    timer.stop();
+}
+
+#[test]
+fn test_wall_clock_jump() {
+    let clock = Arc::new(MockClock::new());
+    let mut timer = WheelTimer::with_clock(100, 64, 4, clock.clone()).unwrap();
+    let fired = Arc::new(AtomicBool::new(false));
+    let task = FlagTask {
+        fired: fired.clone(),
+    };
+    let now = clock.now_millis();
+    timer
+        .new_timeout(Box::new(task), Duration::from_secs(60))
+        .unwrap();
+
+    // 系统时间向后调整不会 panic 也不推迟任务, 向前调整不会提前执行
+    clock.set_millis(now - HOUR);
+    clock.advance(Duration::from_secs(30));
+    clock.set_millis(now + HOUR);
+    clock.advance(Duration::from_secs(29));
+    thread::sleep(Duration::from_millis(100));
+    assert!(!fired.load(Ordering::SeqCst));
+
+    clock.advance(Duration::from_millis(1100));
+    assert!(wait_until(|| fired.load(Ordering::SeqCst)));
+    timer.stop();
+}
+
+#[test]
+fn test_tick_sleep() {
+    let clock = Arc::new(RecordClock::default());
+    let timer = WheelTimer::with_clock(20, 64, 4, clock.clone()).unwrap();
+    thread::sleep(Duration::from_millis(200));
+    timer.stop();
+    // 每个 tick 一次睡到下一个 tick, 而不是每次只睡 1 毫秒
+    let sleeps = clock.sleeps.lock().unwrap();
+    assert!(!sleeps.is_empty() && sleeps.len() < 30, "{:?}", sleeps);
+    assert!(sleeps.iter().all(|v| *v <= Duration::from_millis(20)));
+    assert!(sleeps.iter().any(|v| *v >= Duration::from_millis(10)));
+}
+
+#[test]
+fn test_write_timeout() {
+    let clock = Arc::new(MockClock::new());
+    let mut connection = Connection::with_transport(Box::new(FullTransport), Codec::default());
+    connection.set_clock(clock.clone());
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut package = Package::new();
+        package.set_action(Action::HEARTBEAT);
+        let ret = connection.write_package(package, Duration::from_secs(60));
+        let _ = sender.send(ret.is_err());
+    });
+
+    // 写超时按注入的时钟计算
+    assert!(receiver.recv_timeout(Duration::from_millis(100)).is_err());
+    clock.advance(Duration::from_secs(61));
+    assert!(receiver.recv_timeout(Duration::from_secs(2)).unwrap());
+}
+
+#[test]
+fn test_reader_idle_disconnect() {
+    let clock = Arc::new(MockClock::new());
+    let address = start_server(clock.clone());
+    let (mut connection, receiver) = login(&address);
+
+    // 读空闲 60 秒, 期间收到心跳后重新计时
+    clock.advance(Duration::from_secs(40));
+    let mut package = Package::new();
+    package.set_action(Action::HEARTBEAT);
+    connection
+        .write_package(package, Duration::from_secs(1))
+        .unwrap();
+    wait_for(&receiver, Some(Action::HEARTBEAT));
+
+    clock.advance(Duration::from_secs(40));
+    assert_quiet(&receiver, None);
+
+    clock.advance(Duration::from_secs(21));
+    wait_for(&receiver, None);
+}
+
+#[test]
+fn test_client_heartbeat() {
+    // 只完成握手并转发收到的数据包的服务端
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut connection = Connection::new(stream);
+        connection.accept_handshake(Duration::from_secs(1)).unwrap();
+        while let Ok(package) = connection.read_package() {
+            if sender.send(Some(package.get_action())).is_err() {
+                return;
+            }
+        }
+    });
+
+    let clock = Arc::new(MockClock::new());
+    let config = ClientConfig::default();
+    let writer_idle_seconds = config.writer_idle_seconds;
+    let mut client = IMClient::connect_with_clock(&address, config, clock.clone()).unwrap();
+    client.login("cathy", "cathy123").unwrap();
+    wait_for(&receiver, Some(Action::LOGIN));
+
+    clock.advance(Duration::from_secs(writer_idle_seconds - 1));
+    assert_quiet(&receiver, Some(Action::HEARTBEAT));
+
+    clock.advance(Duration::from_secs(2));
+    wait_for(&receiver, Some(Action::HEARTBEAT));
+    client.close();
+}
+
+#[test]
+fn test_reader_idle_wall_clock_jump() {
+    let clock = Arc::new(MockClock::new());
+    let address = start_server(clock.clone());
+    let (_connection, receiver) = login(&address);
+
+    // 系统时间跳变不影响读空闲检测
+    let now = clock.now_millis();
+    clock.set_millis(now + 24 * HOUR);
+    clock.advance(Duration::from_secs(30));
+    assert_quiet(&receiver, None);
+    clock.set_millis(now - 24 * HOUR);
+    clock.advance(Duration::from_secs(29));
+    assert_quiet(&receiver, None);
+
+    clock.advance(Duration::from_secs(2));
+    wait_for(&receiver, None);
+}
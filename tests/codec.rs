@@ -1,5 +1,33 @@
+mod common;
+
+use cathy::proto::{Action, MsgToUser, Package};
+use cathy::{Buffer, Codec, IMError, PROTOCOL_VERSION};
 use protobuf::Message;
-use rust_im::chat_room::MsgToUser;
+
+fn package(len: usize) -> Package {
+    let mut package = Package::new();
+    package.set_action(Action::MSG_TO_USER);
+    package.set_content((0..len).map(|i| i as u8).collect());
+    package
+}
+
+// 每次最多向 Buffer 写入 step 个字节, 直到解码出一个数据包
+fn decode_by_step(codec: &Codec, buffer: &mut Buffer, mut bytes: &[u8], step: usize) -> Package {
+    loop {
+        match codec.decode(buffer) {
+            Ok(p) => return p,
+            Err(IMError::NotEnoughData) => {
+                let (mut chunk, rest) = bytes.split_at(step.min(bytes.len()));
+                bytes = rest;
+                // 缓冲区空闲空间不足时一次只能读入一部分
+                while !chunk.is_empty() {
+                    buffer.read_from_reader(&mut chunk).unwrap();
+                }
+            }
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
 
 #[test]
 fn test_big_endian() {
@@ -37,11 +65,167 @@ fn test_overflow() {
 fn test_proto() {
     let mut mtu_pb = MsgToUser::new();
     mtu_pb.seq = 1;
-    mtu_pb.from_uid = 2;
-    mtu_pb.to_uid = 3;
-    mtu_pb.text = "hello".to_string();
+    mtu_pb.sender_uid = 2;
+    mtu_pb.receiver_uid = 3;
+    mtu_pb.content = "hello".to_string();
 
     let pb_bytes = mtu_pb.write_to_bytes().unwrap();
     let new_mtu_pb = MsgToUser::parse_from_bytes(pb_bytes.as_slice()).unwrap();
     println!("{:?}", new_mtu_pb)
 }
+
+#[test]
+fn test_frame_roundtrip() {
+    let codec = Codec::default();
+    for len in [0, 1, 4092, 4093, 100_000] {
+        let bytes = codec.encode(package(len)).unwrap();
+        assert_eq!(bytes[0], PROTOCOL_VERSION);
+        let mut buffer = Buffer::new();
+        let p = decode_by_step(&codec, &mut buffer, &bytes, 1500);
+        assert_eq!(p.get_action(), Action::MSG_TO_USER);
+        assert_eq!(p.get_content(), package(len).get_content());
+        assert!(buffer.is_empty());
+    }
+}
+
+#[test]
+fn test_chunked_frames() {
+    let codec = Codec::new(1000, 64 * 1024);
+    let bytes = codec.encode(package(10_500)).unwrap();
+    // 11 个帧, 每帧 8 字节头部
+    assert_eq!(bytes.len(), 10_500 + 11 * 8);
+
+    // 两个数据包连续到达, 逐字节读入时分帧不会被提前消费
+    let mut stream = bytes.clone();
+    stream.extend(codec.encode(package(10)).unwrap());
+    let mut buffer = Buffer::new();
+    let p = decode_by_step(&codec, &mut buffer, &stream[..bytes.len()], 7);
+    assert_eq!(p.get_content(), package(10_500).get_content());
+    let p = decode_by_step(&codec, &mut buffer, &stream[bytes.len()..], 1);
+    assert_eq!(p.get_content().len(), 10);
+}
+
+#[test]
+fn test_content_max_len() {
+    let codec = Codec::new(1000, 5000);
+    assert!(matches!(
+        codec.encode(package(5001)),
+        Err(IMError::ContentMaxLen)
+    ));
+    // 对端的分帧或拼接后的长度超过限制
+    let bytes = Codec::new(2000, 5000).encode(package(3000)).unwrap();
+    let mut buffer = Buffer::new();
+    buffer.read_from_reader(&mut bytes.as_slice()).unwrap();
+    assert!(matches!(
+        codec.decode(&mut buffer),
+        Err(IMError::ContentMaxLen)
+    ));
+    let bytes = Codec::new(1000, 10_000).encode(package(6000)).unwrap();
+    let mut buffer = Buffer::new();
+    let mut reader = bytes.as_slice();
+    while !reader.is_empty() {
+        buffer.read_from_reader(&mut reader).unwrap();
+    }
+    assert!(matches!(
+        codec.decode(&mut buffer),
+        Err(IMError::ContentMaxLen)
+    ));
+}
+
+#[test]
+fn test_unsupported_version() {
+    // 版本1的头部: Type(2字节) | len(2字节)
+    let mut buffer = Buffer::new();
+    buffer
+        .read_from_reader(&mut [0u8, 3, 0, 0, 0, 0, 0, 0].as_slice())
+        .unwrap();
+    assert!(matches!(
+        Codec::default().decode(&mut buffer),
+        Err(IMError::UnsupportedVersion(0))
+    ));
+}
+
+#[test]
+fn test_buffer_grow() {
+    let codec = Codec::default();
+    let bytes = codec.encode(package(50_000)).unwrap();
+    let mut buffer = Buffer::new();
+    let initial = buffer.capacity();
+    decode_by_step(&codec, &mut buffer, &bytes, bytes.len());
+    assert!(buffer.capacity() > 50_000);
+    // 数据消费完之后再读取时释放扩容的空间
+    decode_by_step(&codec, &mut buffer, &codec.encode(package(1)).unwrap(), 100);
+    assert_eq!(buffer.capacity(), initial);
+}
+
+#[test]
+fn test_large_message() {
+    let address = &common::start_server(common::server_config());
+
+    let mut sender = common::login_as(address, "cathy");
+    let mut receiver = common::login_as(address, "mars");
+
+    // 超过默认的 max_frame_size, 按多个帧发送和推送
+    let content = "长消息".repeat(20_000);
+    let mut msg = MsgToUser::new();
+    msg.set_seq(1);
+    msg.set_receiver_uid(2);
+    msg.set_content(content.clone());
+    common::send(&mut sender, Action::MSG_TO_USER, &msg);
+    let msg: MsgToUser = common::expect(&mut receiver, Action::MSG_TO_USER);
+    assert_eq!(msg.get_sender_uid(), 1);
+    assert_eq!(msg.get_content(), content);
+}
+
+// 版本2的帧: Version | Flags | Type | len | body
+fn frame(more: bool, body: &[u8]) -> Vec<u8> {
+    let mut frame = vec![PROTOCOL_VERSION, more as u8];
+    frame.extend_from_slice(&(Action::MSG_TO_USER as u16).to_be_bytes());
+    frame.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    frame.extend_from_slice(body);
+    frame
+}
+
+#[test]
+fn test_malformed_frames() {
+    let codec = Codec::new(1000, 5000);
+    // 空的中间帧
+    let mut buffer = Buffer::new();
+    buffer
+        .read_from_reader(&mut frame(true, &[]).as_slice())
+        .unwrap();
+    assert!(matches!(
+        codec.decode(&mut buffer),
+        Err(IMError::MalformedFrame)
+    ));
+    // 总长度没有超过限制, 但帧数超过 content_max_len / max_frame_size + 1
+    let bytes: Vec<u8> = (0..codec.max_frames() + 1)
+        .flat_map(|_| frame(true, &[1]))
+        .collect();
+    let mut buffer = Buffer::new();
+    buffer.read_from_reader(&mut bytes.as_slice()).unwrap();
+    assert!(matches!(
+        codec.decode(&mut buffer),
+        Err(IMError::ContentMaxLen)
+    ));
+}
+
+#[test]
+fn test_buffer_limit() {
+    let codec = Codec::new(1000, 5000);
+    let bytes = codec.encode(package(5000)).unwrap();
+    assert!(bytes.len() <= codec.max_packet_len());
+    let mut buffer = Buffer::with_limit(codec.max_packet_len());
+    decode_by_step(&codec, &mut buffer, &bytes, 100);
+    // 不完整的数据包超过上限后不再扩容
+    let mut buffer = Buffer::with_limit(codec.max_packet_len());
+    let zeros = vec![0u8; 64 * 1024];
+    let mut reader = zeros.as_slice();
+    let ret = loop {
+        if let Err(e) = buffer.read_from_reader(&mut reader) {
+            break e;
+        }
+    };
+    assert!(matches!(ret, IMError::ContentMaxLen));
+    assert!(buffer.capacity() <= codec.max_packet_len());
+}
@@ -0,0 +1,128 @@
+#![allow(dead_code)]
+
+use cathy::proto::{Action, ConnectedReply, Login, Package};
+use cathy::{
+    ClientConfig, Connection, Event, FileUserStore, IMClient, IMServer, ServerConfig,
+    ShutdownHandle,
+};
+use protobuf::Message;
+use std::net::TcpStream;
+use std::sync::mpsc::Receiver;
+use std::thread;
+use std::time::Duration;
+
+/// 所有测试共用的用户, 密码为用户名加 123.
+pub const USERS: &str = "
+# uid username password [token]
+1 cathy cathy123
+2 mars  mars123 mars-token
+3 venus venus123
+4 pluto pluto123
+5 luna  luna123
+6 nova  nova123
+7 sol   sol123
+";
+
+pub fn user_store() -> FileUserStore {
+    FileUserStore::parse(USERS).unwrap()
+}
+
+/// 监听在随机端口上的默认配置, 实际的地址在服务端启动后从 listening 读取.
+pub fn server_config() -> ServerConfig {
+    ServerConfig {
+        listen_address: "127.0.0.1:0".to_string(),
+        ..ServerConfig::default()
+    }
+}
+
+/// 等到服务端绑定了 count 个地址, 按绑定的顺序返回, 配置了 WebSocket 时第二个是 WebSocket 地址.
+pub fn listening(shutdown: &ShutdownHandle, count: usize) -> Vec<String> {
+    for _ in 0..250 {
+        let addresses = shutdown.get_addresses();
+        if addresses.len() >= count {
+            return addresses.iter().map(|v| v.to_string()).collect();
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    panic!("The server is not listening...")
+}
+
+/// 在新线程中运行服务端, 返回监听地址.
+pub fn spawn(server: IMServer) -> String {
+    spawn_with(server, IMServer::run)
+}
+
+/// 在新线程中以 run 运行服务端, 返回监听地址.
+pub fn spawn_with(mut server: IMServer, run: fn(&mut IMServer) -> cathy::Result<()>) -> String {
+    let shutdown = server.shutdown_handle();
+    thread::spawn(move || run(&mut server).unwrap());
+    listening(&shutdown, 1).remove(0)
+}
+
+/// 以 USERS 中的用户运行服务端, 返回监听地址.
+pub fn start_server(config: ServerConfig) -> String {
+    spawn(IMServer::new(config, Box::new(user_store())).unwrap())
+}
+
+pub fn connect(address: &str) -> Connection {
+    for _ in 0..50 {
+        if let Ok(stream) = TcpStream::connect(address) {
+            let mut connection = Connection::new(stream);
+            connection.handshake(Duration::from_secs(1)).unwrap();
+            return connection;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    panic!("Couldn't connect to the server...")
+}
+
+/// 连接服务端并取出事件, 还没有登录.
+pub fn connect_client(address: &str, config: ClientConfig) -> (IMClient, Receiver<Event>) {
+    let mut client = IMClient::connect(address, config).unwrap();
+    let events = client.events().unwrap();
+    (client, events)
+}
+
+pub fn send<M: Message>(connection: &mut Connection, action: Action, msg: &M) {
+    let mut package = Package::new();
+    package.set_action(action);
+    package.set_content(msg.write_to_bytes().unwrap());
+    connection
+        .write_package(package, Duration::from_secs(1))
+        .unwrap();
+}
+
+pub fn login(connection: &mut Connection, username: &str, password: &str) -> Package {
+    let mut login = Login::new();
+    login.set_username(username.to_string());
+    login.set_password(password.to_string());
+    send(connection, Action::LOGIN, &login);
+    connection.read_package().unwrap()
+}
+
+/// 以 USERS 中的 username 登录, 返回已登录的连接.
+pub fn login_as(address: &str, username: &str) -> Connection {
+    login_device(address, username, "")
+}
+
+/// 以 device_id 登录 USERS 中的 username, 返回已登录的连接.
+pub fn login_device(address: &str, username: &str, device_id: &str) -> Connection {
+    let mut connection = connect(address);
+    let mut login = Login::new();
+    login.set_username(username.to_string());
+    login.set_password(format!("{}123", username));
+    login.set_device_id(device_id.to_string());
+    send(&mut connection, Action::LOGIN, &login);
+    let _: ConnectedReply = expect(&mut connection, Action::CONNECTED);
+    connection
+}
+
+/// 读取下一个指定类型的数据包, 跳过心跳等其他数据包.
+pub fn expect<M: Message>(connection: &mut Connection, action: Action) -> M {
+    loop {
+        let package = connection.read_package().unwrap();
+        if package.get_action() == action {
+            return M::parse_from_bytes(package.get_content()).unwrap();
+        }
+    }
+}
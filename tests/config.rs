@@ -0,0 +1,127 @@
+use cathy::{
+    ClientConfig, FileUserStore, IMError, IMServer, LoginPolicy, OverflowPolicy, ServerConfig,
+    DEFAULT_CONTENT_MAX_LEN,
+};
+use std::env;
+use std::fs;
+use std::process;
+use std::time::Duration;
+
+#[test]
+fn test_default() {
+    let config = ServerConfig::default();
+    assert_eq!(config.listen_address, "127.0.0.1:8099");
+    assert_eq!(config.reader_idle_timeout(), Duration::from_secs(60));
+    assert_eq!(config.content_max_len, DEFAULT_CONTENT_MAX_LEN);
+    let config = ClientConfig::default();
+    assert_eq!(config.writer_idle_timeout(), Duration::from_secs(30));
+    assert_eq!(config.write_timeout(), Duration::from_secs(10));
+}
+
+#[test]
+fn test_from_toml() {
+    let config = ServerConfig::from_toml(
+        r#"
+        listen_address = "0.0.0.0:9000"
+        reader_idle_seconds = 120
+        overflow_policy = "disconnect"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.listen_address, "0.0.0.0:9000");
+    assert_eq!(config.reader_idle_seconds, 120);
+    assert_eq!(config.overflow_policy, OverflowPolicy::Disconnect);
+    // 未配置的项使用默认值
+    assert_eq!(config.login_timeout_seconds, 10);
+
+    assert!(ServerConfig::from_toml("listen_adress = \"0.0.0.0:9000\"").is_err());
+    assert!(ServerConfig::from_toml("overflow_policy = \"block\"").is_err());
+    let ret = ServerConfig::from_toml("max_frame_size = 0");
+    assert!(matches!(ret, Err(IMError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_sample_files() {
+    ServerConfig::from_file("conf/server.toml").unwrap();
+    ClientConfig::from_file("conf/client.toml").unwrap();
+}
+
+// 默认值 < 配置文件 < 环境变量 < 命令行参数
+#[test]
+fn test_load_precedence() {
+    let path = env::temp_dir().join(format!("cathy-config-{}.toml", process::id()));
+    fs::write(
+        &path,
+        "writer_idle_seconds = 5\nwrite_timeout_seconds = 3\nlog_level = \"warn\"\n",
+    )
+    .unwrap();
+    env::set_var("CATHY_CLIENT_WRITE_TIMEOUT_SECONDS", "4");
+    env::set_var("CATHY_CLIENT_LOG_LEVEL", "info");
+    let args = [
+        "--config",
+        path.to_str().unwrap(),
+        "cathy",
+        "--log-level=error",
+        "--server-address",
+        "127.0.0.1:9000",
+        "cathy123",
+    ];
+    let ret = ClientConfig::load(args.iter().map(|v| v.to_string()));
+    env::remove_var("CATHY_CLIENT_WRITE_TIMEOUT_SECONDS");
+    env::remove_var("CATHY_CLIENT_LOG_LEVEL");
+    fs::remove_file(&path).unwrap();
+
+    let (config, positional) = ret.unwrap();
+    assert_eq!(positional, vec!["cathy", "cathy123"]);
+    assert_eq!(config.writer_idle_seconds, 5);
+    assert_eq!(config.write_timeout_seconds, 4);
+    assert_eq!(config.log_level, "error");
+    assert_eq!(config.server_address, "127.0.0.1:9000");
+    assert_eq!(config.ticks_per_wheel, 12);
+}
+
+#[test]
+fn test_invalid_args() {
+    let load = |args: &[&str]| ServerConfig::load(args.iter().map(|v| v.to_string()));
+    assert!(load(&["--reader-idle-seconds", "abc"]).is_err());
+    assert!(load(&["--unknown", "1"]).is_err());
+    assert!(load(&["--listen-address"]).is_err());
+    assert!(load(&["--ticks-per-wheel=0"]).is_err());
+    assert!(load(&["--timer-levels=0"]).is_err());
+    // 超过时间轮的限制
+    assert!(load(&["--ticks-per-wheel=1073741825"]).is_err());
+    assert!(load(&["--tick-duration-millis", &u64::MAX.to_string()]).is_err());
+    assert!(load(&["--log-level", "verbose"]).is_err());
+    let (config, _) = load(&["--overflow-policy", "drop"]).unwrap();
+    assert_eq!(config.overflow_policy, OverflowPolicy::Drop);
+    assert!(load(&["--login-policy", "kick_all"]).is_err());
+    let (config, _) = load(&["--login-policy", "kick_older"]).unwrap();
+    assert_eq!(config.login_policy, LoginPolicy::KickOlder);
+}
+
+#[test]
+fn test_invalid_timer_config() {
+    // 没有经过 load 校验的配置, 创建服务端时返回错误而不是 panic
+    let config = ServerConfig {
+        ticks_per_wheel: u32::MAX,
+        ..ServerConfig::default()
+    };
+    let store = FileUserStore::parse("1 cathy cathy123").unwrap();
+    match IMServer::new(config, Box::new(store)) {
+        Err(IMError::InvalidConfig(_)) => {}
+        _ => panic!("the timer config should be rejected"),
+    }
+}
+
+#[test]
+fn test_feature_options() {
+    let load = |args: &[&str]| ServerConfig::load(args.iter().map(|v| v.to_string()));
+    // 证书与私钥必须同时设置
+    assert!(load(&["--tls-cert-file", "cert.pem"]).is_err());
+    let ret = load(&["--tls-cert-file", "cert.pem", "--tls-key-file", "key.pem"]);
+    assert_eq!(ret.is_ok(), cfg!(feature = "tls"));
+    let ret = ClientConfig::load(vec!["--tls-ca-file".to_string(), "ca.pem".to_string()]);
+    assert_eq!(ret.is_ok(), cfg!(feature = "tls"));
+    let ret = load(&["--websocket-address", "127.0.0.1:8098"]);
+    assert_eq!(ret.is_ok(), cfg!(feature = "websocket"));
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use cathy::proto::{Action, MsgToUser, Receipt};
+use cathy::{ClientConfig, Connection, Event, LoginPolicy};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+fn address() -> &'static str {
+    static ADDRESS: OnceLock<String> = OnceLock::new();
+    ADDRESS.get_or_init(|| common::start_server(common::server_config()))
+}
+
+// 每个用户一个会话, 再次登录时踢掉所有旧会话的服务端
+fn kick_older_address() -> &'static str {
+    static ADDRESS: OnceLock<String> = OnceLock::new();
+    ADDRESS.get_or_init(|| {
+        let mut config = common::server_config();
+        config.login_policy = LoginPolicy::KickOlder;
+        common::start_server(config)
+    })
+}
+
+fn send_to_user(connection: &mut Connection, seq: u64, receiver_uid: u64, content: &str) {
+    let mut msg = MsgToUser::new();
+    msg.set_seq(seq);
+    msg.set_receiver_uid(receiver_uid);
+    msg.set_content(content.to_string());
+    common::send(connection, Action::MSG_TO_USER, &msg);
+}
+
+// 服务端关闭连接后读取失败, 读超时说明连接没有被关闭
+fn assert_kicked(connection: &mut Connection) {
+    connection
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let start = Instant::now();
+    while connection.read_package().is_ok() {}
+    assert!(start.elapsed() < Duration::from_secs(4));
+}
+
+#[test]
+fn test_fan_out_to_devices() {
+    let mut phone = common::login_device(address(), "cathy", "phone");
+    let mut laptop = common::login_device(address(), "cathy", "laptop");
+    let mut mars = common::login_device(address(), "mars", "phone");
+
+    send_to_user(&mut mars, 1, 1, "hello");
+    let first: MsgToUser = common::expect(&mut phone, Action::MSG_TO_USER);
+    let second: MsgToUser = common::expect(&mut laptop, Action::MSG_TO_USER);
+    assert_eq!(first.get_content(), "hello");
+    assert_eq!(first.get_message_id(), second.get_message_id());
+
+    // 任一设备确认送达即通知发送方
+    let mut receipt = Receipt::new();
+    receipt.set_message_id(first.get_message_id());
+    receipt.set_sender_uid(2);
+    common::send(&mut laptop, Action::DELIVERED_ACK, &receipt);
+    let receipt: Receipt = common::expect(&mut mars, Action::DELIVERED_ACK);
+    assert_eq!(receipt.get_message_id(), first.get_message_id());
+    assert_eq!(receipt.get_receiver_uid(), 1);
+}
+
+#[test]
+fn test_sync_to_other_devices() {
+    let mut phone = common::login_device(address(), "venus", "phone");
+    let mut laptop = common::login_device(address(), "venus", "laptop");
+    let mut pluto = common::login_device(address(), "pluto", "phone");
+
+    send_to_user(&mut phone, 1, 4, "sync");
+    let received: MsgToUser = common::expect(&mut pluto, Action::MSG_TO_USER);
+    let synced: MsgToUser = common::expect(&mut laptop, Action::MSG_TO_USER);
+    assert_eq!(synced.get_sender_uid(), 3);
+    assert_eq!(synced.get_receiver_uid(), 4);
+    assert_eq!(synced.get_message_id(), received.get_message_id());
+
+    // 送达回执发给发送方的所有设备
+    let mut receipt = Receipt::new();
+    receipt.set_message_id(received.get_message_id());
+    receipt.set_sender_uid(3);
+    common::send(&mut pluto, Action::DELIVERED_ACK, &receipt);
+    for connection in [&mut phone, &mut laptop].iter_mut() {
+        let receipt: Receipt = common::expect(connection, Action::DELIVERED_ACK);
+        assert_eq!(receipt.get_message_id(), received.get_message_id());
+    }
+}
+
+#[test]
+fn test_same_device_kicked() {
+    let mut first = common::login_device(address(), "luna", "phone");
+    let mut laptop = common::login_device(address(), "luna", "laptop");
+    let mut second = common::login_device(address(), "luna", "phone");
+    assert_kicked(&mut first);
+
+    // 其他设备不受影响
+    let mut nova = common::login_device(address(), "nova", "phone");
+    send_to_user(&mut nova, 1, 5, "still online");
+    for connection in [&mut laptop, &mut second].iter_mut() {
+        let msg: MsgToUser = common::expect(connection, Action::MSG_TO_USER);
+        assert_eq!(msg.get_content(), "still online");
+    }
+}
+
+#[test]
+fn test_kick_older() {
+    let mut phone = common::login_device(kick_older_address(), "cathy", "phone");
+    let mut laptop = common::login_device(kick_older_address(), "cathy", "laptop");
+    let _tablet = common::login_device(kick_older_address(), "cathy", "tablet");
+    assert_kicked(&mut phone);
+    assert_kicked(&mut laptop);
+}
+
+#[test]
+fn test_client_synced_event() {
+    let config = ClientConfig {
+        device_id: Some("desktop".to_string()),
+        ..ClientConfig::default()
+    };
+    let (mut desktop, events) = common::connect_client(address(), config);
+    desktop.login("sol", "sol123").unwrap();
+    assert!(matches!(
+        events.recv_timeout(Duration::from_secs(2)).unwrap(),
+        Event::Connected(_)
+    ));
+
+    let mut phone = common::login_device(address(), "sol", "phone");
+    send_to_user(&mut phone, 1, 6, "from phone");
+    match events.recv_timeout(Duration::from_secs(2)).unwrap() {
+        Event::Synced(msg) => {
+            assert_eq!(msg.get_receiver_uid(), 6);
+            assert_eq!(msg.get_content(), "from phone");
+        }
+        e => panic!("unexpected event {:?}", e),
+    }
+}
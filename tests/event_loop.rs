@@ -0,0 +1,136 @@
+#![cfg(feature = "event-loop")]
+
+mod common;
+
+use cathy::proto::{Action, MsgAck, MsgToUser, Package, Receipt};
+use cathy::{Codec, Connection, IMServer, RetransmitPolicy, MAGIC};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+fn message(seq: u64, receiver_uid: u64) -> MsgToUser {
+    let mut msg = MsgToUser::new();
+    msg.set_seq(seq);
+    msg.set_receiver_uid(receiver_uid);
+    msg.set_content("hello".to_string());
+    msg
+}
+
+#[test]
+fn test_event_loop_server() {
+    let server = IMServer::new(common::server_config(), Box::new(common::user_store())).unwrap();
+    let address = &common::spawn_with(server, IMServer::run_event_loop);
+
+    // 认证失败的连接被关闭
+    let mut connection = common::connect(address);
+    let reply = common::login(&mut connection, "cathy", "wrong");
+    assert_eq!(reply.get_action(), Action::LOGIN_FAILED);
+    assert!(connection.read_package().is_err());
+
+    let mut sender = common::connect(address);
+    let reply = common::login(&mut sender, "cathy", "cathy123");
+    assert_eq!(reply.get_action(), Action::CONNECTED);
+    let mut receiver = common::connect(address);
+    let reply = common::login(&mut receiver, "mars", "mars123");
+    assert_eq!(reply.get_action(), Action::CONNECTED);
+
+    // 一次写入多个数据包, 事件循环需要逐个解码
+    for seq in 1..=3 {
+        common::send(&mut sender, Action::MSG_TO_USER, &message(seq, 2));
+    }
+    let mut message_ids = Vec::new();
+    for seq in 1..=3 {
+        let ack: MsgAck = common::expect(&mut sender, Action::MSG_ACK);
+        assert_eq!(ack.get_seq(), seq);
+        message_ids.push(ack.get_message_id());
+    }
+    for message_id in message_ids {
+        let msg: MsgToUser = common::expect(&mut receiver, Action::MSG_TO_USER);
+        assert_eq!(msg.get_message_id(), message_id);
+        assert_eq!(msg.get_sender_uid(), 1);
+
+        let mut receipt = Receipt::new();
+        receipt.set_message_id(msg.get_message_id());
+        receipt.set_sender_uid(msg.get_sender_uid());
+        common::send(&mut receiver, Action::DELIVERED_ACK, &receipt);
+        let delivered: Receipt = common::expect(&mut sender, Action::DELIVERED_ACK);
+        assert_eq!(delivered.get_message_id(), message_id);
+    }
+
+    let mut package = Package::new();
+    package.set_action(Action::HEARTBEAT);
+    package.set_content(b"PING".to_vec());
+    sender
+        .write_package(package, Duration::from_secs(1))
+        .unwrap();
+    let pong = sender.read_package().unwrap();
+    assert_eq!(pong.get_action(), Action::HEARTBEAT);
+    assert_eq!(pong.get_content(), b"PONG");
+
+    // 同一用户重复登录, 旧连接被关闭
+    let mut again = common::connect(address);
+    let reply = common::login(&mut again, "mars", "mars123");
+    assert_eq!(reply.get_action(), Action::CONNECTED);
+    while receiver.read_package().is_ok() {}
+}
+
+// 接收方不读取时, 发送缓冲区写满后等待可写事件, 事件循环继续处理其他连接
+#[test]
+fn test_event_loop_slow_receiver() {
+    let mut server =
+        IMServer::new(common::server_config(), Box::new(common::user_store())).unwrap();
+    server.set_retransmit_policy(RetransmitPolicy {
+        initial_delay: Duration::from_secs(60),
+        ..RetransmitPolicy::default()
+    });
+    let address = &common::spawn_with(server, IMServer::run_event_loop);
+    let mut receiver = common::login_as(address, "venus");
+    let mut sender = common::login_as(address, "pluto");
+    sender
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+
+    let content = "x".repeat(64 * 1024);
+    for seq in 1..=100 {
+        let mut msg = message(seq, 3);
+        msg.set_content(content.clone());
+        common::send(&mut sender, Action::MSG_TO_USER, &msg);
+        let ack: MsgAck = common::expect(&mut sender, Action::MSG_ACK);
+        assert_eq!(ack.get_seq(), seq);
+    }
+    let mut package = Package::new();
+    package.set_action(Action::HEARTBEAT);
+    package.set_content(b"PING".to_vec());
+    sender
+        .write_package(package, Duration::from_secs(1))
+        .unwrap();
+    let pong: Package = sender.read_package().unwrap();
+    assert_eq!(pong.get_action(), Action::HEARTBEAT);
+
+    for seq in 1..=100 {
+        let msg: MsgToUser = common::expect(&mut receiver, Action::MSG_TO_USER);
+        assert_eq!(msg.get_seq(), seq);
+        assert_eq!(msg.get_content().len(), content.len());
+    }
+}
+
+// 握手的回复同样由事件循环非阻塞地写出
+#[test]
+fn test_event_loop_handshake() {
+    let server = IMServer::new(common::server_config(), Box::new(common::user_store())).unwrap();
+    let address = &common::spawn_with(server, IMServer::run_event_loop);
+
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream.write_all(&Codec::encode_hello(200, 255)).unwrap();
+    let mut reply = [0; 5];
+    stream.read_exact(&mut reply).unwrap();
+    assert_eq!(reply[..4], MAGIC);
+    assert_eq!(reply[4], 0);
+    assert_eq!(stream.read(&mut reply).unwrap(), 0);
+
+    // 不握手的旧客户端
+    let stream = TcpStream::connect(address).unwrap();
+    let mut old = Connection::with_codec(stream, Codec::default().with_version(1));
+    let reply = common::login(&mut old, "cathy", "cathy123");
+    assert_eq!(reply.get_action(), Action::CONNECTED);
+}
@@ -0,0 +1,124 @@
+//! 随机字节与随机数据包的属性测试, 使用固定种子保证失败时可以复现.
+
+use cathy::proto::{Action, Package};
+use cathy::{Buffer, Codec, IMError};
+use protobuf::ProtobufEnum;
+
+const ROUNDS: usize = 2000;
+
+// xorshift64*, 测试不需要密码学安全的随机数
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.next() as u8).collect()
+    }
+}
+
+fn codec(rng: &mut Rng) -> Codec {
+    let codec = Codec::new(1 + rng.below(2048), 1 + rng.below(16 * 1024));
+    codec.with_version(1 + rng.below(2) as u8)
+}
+
+// 逐段写入 Buffer 并解码直到数据用完, 任何输入都不能 panic
+fn decode_all(codec: &Codec, mut bytes: &[u8], rng: &mut Rng) -> Vec<Package> {
+    let mut buffer = Buffer::new();
+    let mut packages = Vec::new();
+    loop {
+        match codec.decode(&mut buffer) {
+            Ok(p) => packages.push(p),
+            Err(IMError::UnknownAction(_)) => {}
+            Err(IMError::NotEnoughData) if !bytes.is_empty() => {
+                let (mut chunk, rest) = bytes.split_at(1 + rng.below(bytes.len()));
+                bytes = rest;
+                while !chunk.is_empty() {
+                    buffer.read_from_reader(&mut chunk).unwrap();
+                }
+            }
+            // 数据用完, 或者遇到无法继续解码的错误
+            Err(_) => return packages,
+        }
+    }
+}
+
+#[test]
+fn test_random_bytes() {
+    let mut rng = Rng(0x5eed_0001);
+    for _ in 0..ROUNDS {
+        let codec = codec(&mut rng);
+        let len = rng.below(8192);
+        let mut bytes = rng.bytes(len);
+        // 让一部分输入带有合法的版本号, 覆盖到头部之后的解析
+        if !bytes.is_empty() && rng.below(2) == 0 {
+            bytes[0] = codec.get_version();
+        }
+        decode_all(&codec, &bytes, &mut rng);
+        // 握手数据同样不能 panic
+        let mut buffer = Buffer::new();
+        buffer.read_from_reader(&mut bytes.as_slice()).ok();
+        let _ = Codec::decode_hello(&mut buffer);
+        let _ = Codec::decode_hello_reply(&mut buffer);
+    }
+}
+
+#[test]
+fn test_roundtrip() {
+    let mut rng = Rng(0x5eed_0002);
+    let actions = Action::values();
+    for _ in 0..ROUNDS {
+        let codec = codec(&mut rng);
+        let max_len = if codec.get_version() == 1 {
+            codec.get_content_max_len().min(4092)
+        } else {
+            codec.get_content_max_len()
+        };
+        let mut expected = Vec::new();
+        let mut bytes = Vec::new();
+        for _ in 0..1 + rng.below(5) {
+            let mut package = Package::new();
+            package.set_action(actions[rng.below(actions.len())]);
+            let len = rng.below(max_len + 1);
+            package.set_content(rng.bytes(len));
+            bytes.extend(codec.encode(package.clone()).unwrap());
+            expected.push(package);
+        }
+        assert_eq!(decode_all(&codec, &bytes, &mut rng), expected);
+    }
+}
+
+#[test]
+fn test_skip_unknown_action() {
+    let mut rng = Rng(0x5eed_0003);
+    for _ in 0..ROUNDS {
+        let codec = codec(&mut rng);
+        let mut package = Package::new();
+        package.set_action(Action::HEARTBEAT);
+        let len = rng.below(codec.get_content_max_len().min(64) + 1);
+        package.set_content(rng.bytes(len));
+        let mut bytes = codec.encode(package.clone()).unwrap();
+        // 把 Action 改为未知的值, 后面再跟一个正常的数据包
+        let offset = if codec.get_version() == 1 { 0 } else { 2 };
+        bytes[offset..offset + 2].copy_from_slice(&0xfffe_u16.to_be_bytes());
+        bytes.extend(codec.encode(package.clone()).unwrap());
+
+        let mut buffer = Buffer::new();
+        buffer.read_from_reader(&mut bytes.as_slice()).unwrap();
+        assert!(matches!(
+            codec.decode(&mut buffer),
+            Err(IMError::UnknownAction(0xfffe))
+        ));
+        assert_eq!(codec.decode(&mut buffer).unwrap(), package);
+        assert!(buffer.is_empty());
+    }
+}
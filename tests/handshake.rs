@@ -0,0 +1,97 @@
+mod common;
+
+use cathy::proto::{Action, ConnectedReply, MsgToUser, Package};
+use cathy::{Codec, Connection, IMError, MAGIC, PROTOCOL_VERSION};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+// 启动一次服务端, 返回一个尚未握手的连接
+fn start_server() -> TcpStream {
+    static ADDRESS: OnceLock<String> = OnceLock::new();
+    let address = ADDRESS.get_or_init(|| common::start_server(common::server_config()));
+    TcpStream::connect(address).unwrap()
+}
+
+// 旧版本的客户端: 不发送握手, 直接按版本1的格式发送 LOGIN
+fn connect_v1() -> Connection {
+    Connection::with_codec(start_server(), Codec::default().with_version(1))
+}
+
+fn message(seq: u64, receiver_uid: u64, content: &str) -> MsgToUser {
+    let mut msg = MsgToUser::new();
+    msg.set_seq(seq);
+    msg.set_receiver_uid(receiver_uid);
+    msg.set_content(content.to_string());
+    msg
+}
+
+fn connected(package: Package) -> ConnectedReply {
+    assert_eq!(package.get_action(), Action::CONNECTED);
+    protobuf::Message::parse_from_bytes(package.get_content()).unwrap()
+}
+
+#[test]
+fn test_negotiate() {
+    assert_eq!(
+        Codec::negotiate(1, PROTOCOL_VERSION),
+        Some(PROTOCOL_VERSION)
+    );
+    assert_eq!(Codec::negotiate(1, 200), Some(PROTOCOL_VERSION));
+    assert_eq!(Codec::negotiate(1, 1), Some(1));
+    assert_eq!(Codec::negotiate(0, 0), None);
+    assert_eq!(Codec::negotiate(200, 255), None);
+}
+
+#[test]
+fn test_v1_frame() {
+    let codec = Codec::default().with_version(1);
+    let mut package = Package::new();
+    package.set_action(Action::HEARTBEAT);
+    package.set_content(b"PING".to_vec());
+    assert_eq!(codec.encode(package).unwrap(), b"\x00\x01\x00\x04PING");
+
+    let mut package = Package::new();
+    package.set_content(vec![0; 4093]);
+    assert!(matches!(codec.encode(package), Err(IMError::ContentMaxLen)));
+}
+
+#[test]
+fn test_old_and_new_clients() {
+    let mut new = Connection::new(start_server());
+    new.handshake(Duration::from_secs(1)).unwrap();
+    assert_eq!(new.get_codec().get_version(), PROTOCOL_VERSION);
+    let reply = connected(common::login(&mut new, "cathy", "cathy123"));
+    assert_eq!(reply.get_version(), PROTOCOL_VERSION as u32);
+    assert_eq!(reply.get_supported_versions(), &[1, 2]);
+
+    let mut old = connect_v1();
+    let reply = connected(common::login(&mut old, "mars", "mars123"));
+    assert_eq!(reply.get_version(), 1);
+
+    common::send(&mut new, Action::MSG_TO_USER, &message(1, 2, "hello old"));
+    let msg: MsgToUser = common::expect(&mut old, Action::MSG_TO_USER);
+    assert_eq!(msg.get_content(), "hello old");
+    common::send(&mut old, Action::MSG_TO_USER, &message(1, 1, "hello new"));
+    let msg: MsgToUser = common::expect(&mut new, Action::MSG_TO_USER);
+    assert_eq!(msg.get_content(), "hello new");
+
+    // 新客户端也可以主动选择较低的版本
+    let mut pinned = Connection::with_codec(start_server(), Codec::default().with_version(1));
+    assert_eq!(pinned.handshake(Duration::from_secs(1)).unwrap(), 1);
+    let reply = connected(common::login(&mut pinned, "nova", "nova123"));
+    assert_eq!(reply.get_version(), 1);
+}
+
+#[test]
+fn test_unsupported_version() {
+    let mut stream = start_server();
+    stream.write_all(&Codec::encode_hello(200, 255)).unwrap();
+    let mut reply = [0; 5];
+    stream.read_exact(&mut reply).unwrap();
+    assert_eq!(reply[..4], MAGIC);
+    assert_eq!(reply[4], 0);
+    // 服务端随后关闭连接
+    assert_eq!(stream.read(&mut reply).unwrap(), 0);
+}
@@ -0,0 +1,182 @@
+use cathy::proto::MsgToUser;
+use cathy::{IMError, LogMessageStore, MessageStore, MessageSystem, Result};
+use std::fs;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cathy-{}-{}", name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn message(content: &str) -> MsgToUser {
+    let mut msg = MsgToUser::new();
+    msg.set_sender_uid(1);
+    msg.set_receiver_uid(2);
+    msg.set_content(content.to_string());
+    msg
+}
+
+#[test]
+fn test_resume_message_id_after_restart() {
+    let dir = temp_dir("resume");
+    {
+        let store = LogMessageStore::open(&dir).unwrap();
+        let mut message_system = MessageSystem::with_store(Box::new(store));
+        for i in 0..3 {
+            let id = message_system.save(&mut message(&i.to_string())).unwrap();
+            assert_eq!(id, 10000 + i);
+        }
+    }
+    let store = LogMessageStore::open(&dir).unwrap();
+    assert_eq!(store.last_message_id(), Some(10002));
+    let mut message_system = MessageSystem::with_store(Box::new(store));
+    assert_eq!(message_system.save(&mut message("3")).unwrap(), 10003);
+    let msg = message_system.load(10001).unwrap().unwrap();
+    assert_eq!(msg.get_content(), "1");
+    assert!(message_system.load(9999).unwrap().is_none());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_segment_roll() {
+    let dir = temp_dir("roll");
+    let mut store = LogMessageStore::with_segment_max_bytes(&dir, 64).unwrap();
+    for id in 1..=20 {
+        let mut msg = message(&format!("message {}", id));
+        msg.set_message_id(id);
+        store.append(&msg).unwrap();
+    }
+    let segments = fs::read_dir(&dir).unwrap().count();
+    assert!(segments > 2, "expected several segments, got {}", segments);
+
+    let mut store = LogMessageStore::with_segment_max_bytes(&dir, 64).unwrap();
+    assert_eq!(store.last_message_id(), Some(20));
+    for id in 1..=20 {
+        let msg = store.load(id).unwrap().unwrap();
+        assert_eq!(msg.get_content(), format!("message {}", id));
+    }
+    assert!(store.load(21).unwrap().is_none());
+
+    let mut msg = message("stale");
+    msg.set_message_id(20);
+    assert!(store.append(&msg).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_truncate_partial_record() {
+    let dir = temp_dir("truncate");
+    {
+        let mut store = LogMessageStore::open(&dir).unwrap();
+        for id in 1..=2 {
+            let mut msg = message(&id.to_string());
+            msg.set_message_id(id);
+            store.append(&msg).unwrap();
+        }
+    }
+    // 模拟写了一半就崩溃的记录
+    let log = dir.join(format!("{:020}.log", 1));
+    let mut file = OpenOptions::new().append(true).open(&log).unwrap();
+    file.write_all(&[0, 0, 0, 100, 1, 2, 3]).unwrap();
+    drop(file);
+
+    let mut store = LogMessageStore::open(&dir).unwrap();
+    assert_eq!(store.last_message_id(), Some(2));
+    let mut msg = message("3");
+    msg.set_message_id(3);
+    store.append(&msg).unwrap();
+    drop(store);
+
+    let mut store = LogMessageStore::open(&dir).unwrap();
+    assert_eq!(store.last_message_id(), Some(3));
+    assert_eq!(store.load(3).unwrap().unwrap().get_content(), "3");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_rollback_failed_append() {
+    let dir = temp_dir("rollback");
+    fs::create_dir_all(&dir).unwrap();
+    // 索引写入 /dev/full 时失败, 日志中已写入的记录要被截掉
+    let index = dir.join(format!("{:020}.index", 10000));
+    std::os::unix::fs::symlink("/dev/full", &index).unwrap();
+    let mut store = LogMessageStore::open(&dir).unwrap();
+    let mut msg = message("lost");
+    msg.set_message_id(10000);
+    assert!(store.append(&msg).is_err());
+    let log = dir.join(format!("{:020}.log", 10000));
+    assert_eq!(fs::metadata(&log).unwrap().len(), 0);
+    assert_eq!(store.last_message_id(), None);
+
+    fs::remove_file(&index).unwrap();
+    for id in 10000..10002 {
+        let mut msg = message(&id.to_string());
+        msg.set_message_id(id);
+        store.append(&msg).unwrap();
+    }
+    for id in 10000..10002 {
+        assert_eq!(
+            store.load(id).unwrap().unwrap().get_content(),
+            id.to_string()
+        );
+    }
+    drop(store);
+
+    let mut store = LogMessageStore::open(&dir).unwrap();
+    assert_eq!(store.last_message_id(), Some(10001));
+    assert_eq!(store.load(10000).unwrap().unwrap().get_content(), "10000");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// 第一次写入失败的存储.
+struct FailOnceStore {
+    failed: bool,
+    messages: Vec<MsgToUser>,
+}
+
+impl MessageStore for FailOnceStore {
+    fn append(&mut self, msg: &MsgToUser) -> Result<()> {
+        if !self.failed {
+            self.failed = true;
+            return Err(IMError::Io(io::Error::other("disk full")));
+        }
+        self.messages.push(msg.clone());
+        Ok(())
+    }
+
+    fn load(&mut self, message_id: u64) -> Result<Option<MsgToUser>> {
+        let msg = self
+            .messages
+            .iter()
+            .find(|v| v.get_message_id() == message_id);
+        Ok(msg.cloned())
+    }
+
+    fn last_message_id(&self) -> Option<u64> {
+        self.messages.last().map(|v| v.get_message_id())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_save_failure_keeps_message_id() {
+    let store = FailOnceStore {
+        failed: false,
+        messages: Vec::new(),
+    };
+    let mut message_system = MessageSystem::with_store(Box::new(store));
+    assert!(message_system.save(&mut message("lost")).is_err());
+    assert_eq!(message_system.save(&mut message("0")).unwrap(), 10000);
+    assert_eq!(message_system.save(&mut message("1")).unwrap(), 10001);
+    let msg = message_system.load(10000).unwrap().unwrap();
+    assert_eq!(msg.get_content(), "0");
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use cathy::proto::{Action, ConnectedReply, MsgToUser};
+use cathy::{FileOfflineStore, OfflineStore};
+use std::fs;
+use std::process;
+
+fn message(seq: u64, receiver_uid: u64, content: &str) -> MsgToUser {
+    let mut msg = MsgToUser::new();
+    msg.set_seq(seq);
+    msg.set_receiver_uid(receiver_uid);
+    msg.set_content(content.to_string());
+    msg
+}
+
+#[test]
+fn test_file_offline_store() {
+    let dir = std::env::temp_dir().join(format!("cathy-offline-{}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let mut store = FileOfflineStore::open(&dir).unwrap();
+    for i in 0..3 {
+        store.push(2, message(i + 1, 2, &i.to_string())).unwrap();
+    }
+    store.push(3, message(4, 3, "other")).unwrap();
+
+    // 重新打开后消息仍然存在
+    let mut store = FileOfflineStore::open(&dir).unwrap();
+    let messages = store.take(2).unwrap();
+    let contents: Vec<&str> = messages.iter().map(|v| v.get_content()).collect();
+    assert_eq!(contents, vec!["0", "1", "2"]);
+    assert!(store.take(2).unwrap().is_empty());
+    assert_eq!(store.take(3).unwrap().len(), 1);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_deliver_on_reconnect() {
+    let address = &common::start_server(common::server_config());
+
+    let mut sender = common::connect(address);
+    assert_eq!(
+        common::login(&mut sender, "cathy", "cathy123").get_action(),
+        Action::CONNECTED
+    );
+    for i in 0..3 {
+        common::send(
+            &mut sender,
+            Action::MSG_TO_USER,
+            &message(i + 1, 2, &i.to_string()),
+        );
+    }
+
+    // 接收方上线后, 在 CONNECTED 之后按顺序收到离线消息
+    let mut receiver = common::connect(address);
+    let reply = common::login(&mut receiver, "mars", "mars123");
+    assert_eq!(reply.get_action(), Action::CONNECTED);
+    let connected: ConnectedReply =
+        protobuf::Message::parse_from_bytes(reply.get_content()).unwrap();
+    assert_eq!(connected.get_uid(), 2);
+    for i in 0..3 {
+        let msg: MsgToUser = common::expect(&mut receiver, Action::MSG_TO_USER);
+        assert_eq!(msg.get_sender_uid(), 1);
+        assert_eq!(msg.get_content(), i.to_string());
+    }
+
+    // 在线时直接投递
+    common::send(&mut sender, Action::MSG_TO_USER, &message(4, 2, "online"));
+    let msg: MsgToUser = common::expect(&mut receiver, Action::MSG_TO_USER);
+    assert_eq!(msg.get_content(), "online");
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use cathy::proto::{Action, Package, Presence, PresenceList, PresenceRequest, PresenceStatus};
+use cathy::{ClientConfig, Connection, Event};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+fn address() -> &'static str {
+    static ADDRESS: OnceLock<String> = OnceLock::new();
+    ADDRESS.get_or_init(|| common::start_server(common::server_config()))
+}
+
+// 读空闲检测为1秒的服务端
+fn idle_address() -> &'static str {
+    static ADDRESS: OnceLock<String> = OnceLock::new();
+    ADDRESS.get_or_init(|| {
+        let mut config = common::server_config();
+        config.reader_idle_seconds = 1;
+        common::start_server(config)
+    })
+}
+
+fn request(connection: &mut Connection, action: Action, uids: &[u64]) {
+    let mut request = PresenceRequest::new();
+    request.set_uids(uids.to_vec());
+    common::send(connection, action, &request);
+}
+
+fn statuses(connection: &mut Connection) -> Vec<(u64, PresenceStatus)> {
+    let list: PresenceList = common::expect(connection, Action::PRESENCE_LIST);
+    list.get_presences()
+        .iter()
+        .map(|v| (v.get_uid(), v.get_status()))
+        .collect()
+}
+
+fn next_presence(connection: &mut Connection) -> (u64, PresenceStatus) {
+    let presence: Presence = common::expect(connection, Action::PRESENCE);
+    (presence.get_uid(), presence.get_status())
+}
+
+#[test]
+fn test_query_presence() {
+    let mut cathy = common::login_as(address(), "cathy");
+    let _mars = common::login_as(address(), "mars");
+    request(&mut cathy, Action::PRESENCE_QUERY, &[2, 7]);
+    assert_eq!(
+        statuses(&mut cathy),
+        vec![(2, PresenceStatus::ONLINE), (7, PresenceStatus::OFFLINE)]
+    );
+    // 不指定用户时查询所有在线用户
+    request(&mut cathy, Action::PRESENCE_QUERY, &[]);
+    let online = statuses(&mut cathy);
+    assert!(online.contains(&(1, PresenceStatus::ONLINE)));
+    assert!(online.contains(&(2, PresenceStatus::ONLINE)));
+}
+
+#[test]
+fn test_subscribe_presence() {
+    let mut venus = common::login_as(address(), "venus");
+    request(&mut venus, Action::PRESENCE_SUBSCRIBE, &[4]);
+    assert_eq!(statuses(&mut venus), vec![(4, PresenceStatus::OFFLINE)]);
+
+    let mut pluto = common::login_as(address(), "pluto");
+    assert_eq!(next_presence(&mut venus), (4, PresenceStatus::ONLINE));
+
+    let mut away = Presence::new();
+    away.set_status(PresenceStatus::AWAY);
+    common::send(&mut pluto, Action::PRESENCE, &away);
+    assert_eq!(next_presence(&mut venus), (4, PresenceStatus::AWAY));
+
+    pluto.shutdown();
+    assert_eq!(next_presence(&mut venus), (4, PresenceStatus::OFFLINE));
+
+    // 取消订阅后不再通知
+    request(&mut venus, Action::PRESENCE_UNSUBSCRIBE, &[4]);
+    let _pluto = common::login_as(address(), "pluto");
+    request(&mut venus, Action::PRESENCE_QUERY, &[4]);
+    assert_eq!(statuses(&mut venus), vec![(4, PresenceStatus::ONLINE)]);
+}
+
+#[test]
+fn test_reader_idle_offline() {
+    let mut luna = common::login_as(idle_address(), "luna");
+    request(&mut luna, Action::PRESENCE_SUBSCRIBE, &[6]);
+    assert_eq!(statuses(&mut luna), vec![(6, PresenceStatus::OFFLINE)]);
+    // 订阅方之后只由心跳线程写入, 被订阅方不发送任何数据直到读空闲超时
+    let stop = Arc::new(AtomicBool::new(false));
+    let heartbeat = {
+        let mut connection = luna.clone();
+        let stop = stop.clone();
+        thread::spawn(move || {
+            while !stop.load(Ordering::SeqCst) {
+                let mut package = Package::new();
+                package.set_action(Action::HEARTBEAT);
+                connection
+                    .write_package(package, Duration::from_secs(1))
+                    .unwrap();
+                thread::sleep(Duration::from_millis(200));
+            }
+        })
+    };
+    let _nova = common::login_as(idle_address(), "nova");
+    assert_eq!(next_presence(&mut luna), (6, PresenceStatus::ONLINE));
+    assert_eq!(next_presence(&mut luna), (6, PresenceStatus::OFFLINE));
+    stop.store(true, Ordering::SeqCst);
+    heartbeat.join().unwrap();
+}
+
+#[test]
+fn test_client_presence() {
+    let (mut client, events) = common::connect_client(address(), ClientConfig::default());
+    client.login("luna", "luna123").unwrap();
+    assert!(matches!(
+        events.recv_timeout(Duration::from_secs(2)).unwrap(),
+        Event::Connected(_)
+    ));
+    client.subscribe_presence(&[6]).unwrap();
+    match events.recv_timeout(Duration::from_secs(2)).unwrap() {
+        Event::PresenceList(list) => {
+            assert_eq!(list.len(), 1);
+            assert_eq!(list[0].get_status(), PresenceStatus::OFFLINE);
+        }
+        e => panic!("unexpected event {:?}", e),
+    }
+    let _nova = common::login_as(address(), "nova");
+    match events.recv_timeout(Duration::from_secs(2)).unwrap() {
+        Event::Presence(presence) => {
+            assert_eq!(presence.get_uid(), 6);
+            assert_eq!(presence.get_status(), PresenceStatus::ONLINE);
+        }
+        e => panic!("unexpected event {:?}", e),
+    }
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use cathy::proto::{Action, MsgToUser, Package};
+use cathy::{Codec, Connection, ServerConfig};
+use protobuf::Message;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+fn heartbeat(connection: &mut Connection) -> Package {
+    let mut package = Package::new();
+    package.set_action(Action::HEARTBEAT);
+    package.set_content(b"PING".to_vec());
+    connection
+        .write_package(package, Duration::from_secs(1))
+        .unwrap();
+    connection.read_package().unwrap()
+}
+
+// 未知 Action 的帧与无法解析的消息体被跳过, 累计超过上限后断开连接
+#[test]
+fn test_protocol_violations() {
+    let config = ServerConfig {
+        max_protocol_violations: 3,
+        ..common::server_config()
+    };
+    let address = &common::start_server(config);
+
+    // 手动握手, 保留原始的 socket 用于写入任意字节
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream.write_all(&Codec::encode_hello(1, 2)).unwrap();
+    let mut reply = [0; 5];
+    stream.read_exact(&mut reply).unwrap();
+    let mut connection = Connection::new(stream.try_clone().unwrap());
+    common::login(&mut connection, "cathy", "cathy123");
+
+    // 较新的客户端发送的未知 Action
+    let mut unknown = Package::new();
+    unknown.set_action(Action::HEARTBEAT);
+    unknown.set_content(b"future".to_vec());
+    let mut bytes = Codec::default().encode(unknown).unwrap();
+    bytes[2..4].copy_from_slice(&999_u16.to_be_bytes());
+    stream.write_all(&bytes).unwrap();
+    assert_eq!(heartbeat(&mut connection).get_content(), b"PONG");
+
+    // 无法解析的消息体
+    let mut malformed = Package::new();
+    malformed.set_action(Action::MSG_TO_USER);
+    malformed.set_content(vec![0xff; 8]);
+    connection
+        .write_package(malformed.clone(), Duration::from_secs(1))
+        .unwrap();
+    assert!(MsgToUser::parse_from_bytes(malformed.get_content()).is_err());
+    // 客户端不应发送的 Action
+    let mut unexpected = Package::new();
+    unexpected.set_action(Action::CONNECTED);
+    connection
+        .write_package(unexpected, Duration::from_secs(1))
+        .unwrap();
+    assert_eq!(heartbeat(&mut connection).get_content(), b"PONG");
+
+    // 第4次违规后连接被关闭
+    connection
+        .write_package(malformed, Duration::from_secs(1))
+        .unwrap();
+    assert!(connection.read_package().is_err());
+}
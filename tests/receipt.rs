@@ -0,0 +1,120 @@
+mod common;
+
+use cathy::proto::{Action, MsgAck, MsgToUser, Receipt};
+use cathy::{Outbox, SendState};
+use std::thread;
+use std::time::Duration;
+
+fn message(seq: u64, receiver_uid: u64) -> MsgToUser {
+    let mut msg = MsgToUser::new();
+    msg.set_seq(seq);
+    msg.set_receiver_uid(receiver_uid);
+    msg.set_content("hello".to_string());
+    msg
+}
+
+#[test]
+fn test_outbox_state() {
+    let mut outbox = Outbox::new();
+    outbox.add(message(1, 2));
+    outbox.add(message(2, 2));
+    assert_eq!(outbox.state(1), Some(SendState::Sending));
+    assert_eq!(outbox.pending().len(), 2);
+
+    assert!(outbox.accepted(1, 10000).is_some());
+    assert!(outbox.accepted(1, 10000).is_none());
+    assert_eq!(outbox.state(1), Some(SendState::Accepted));
+
+    assert!(outbox.read(10000).is_some());
+    // 迟到的送达确认不会让状态回退
+    assert!(outbox.delivered(10000).is_none());
+    assert_eq!(outbox.state(1), Some(SendState::Read));
+    assert!(outbox.delivered(10001).is_none());
+
+    let pending = outbox.pending();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].seq, 2);
+}
+
+#[test]
+fn test_outbox_eviction() {
+    let mut outbox = Outbox::new();
+    // 接收方一直不上线时, 已接收未送达的消息最多保留 4096 条
+    for seq in 1..=5000 {
+        outbox.add(message(seq, 2));
+        outbox.accepted(seq, 10000 + seq);
+    }
+    assert_eq!(outbox.pending().len(), 4096);
+    assert_eq!(outbox.state(904), None);
+    assert_eq!(outbox.state(905), Some(SendState::Accepted));
+
+    // 已完成的消息最多保留 1024 条, 淘汰最早完成的
+    for seq in 905..=3000 {
+        assert!(outbox.delivered(10000 + seq).is_some());
+    }
+    assert_eq!(outbox.state(1976), None);
+    assert_eq!(outbox.state(1977), Some(SendState::Delivered));
+    assert!(outbox.read(10000 + 1977).is_some());
+    assert_eq!(outbox.state(3001), Some(SendState::Accepted));
+    assert_eq!(outbox.pending().len(), 2000);
+}
+
+#[test]
+fn test_ack_and_receipts() {
+    let address = &common::start_server(common::server_config());
+
+    let mut sender = common::login_as(address, "cathy");
+    let mut receiver = common::login_as(address, "mars");
+
+    common::send(&mut sender, Action::MSG_TO_USER, &message(7, 2));
+    let ack: MsgAck = common::expect(&mut sender, Action::MSG_ACK);
+    assert_eq!(ack.get_seq(), 7);
+    assert!(ack.get_message_id() > 0);
+
+    let msg: MsgToUser = common::expect(&mut receiver, Action::MSG_TO_USER);
+    assert_eq!(msg.get_message_id(), ack.get_message_id());
+
+    let mut receipt = Receipt::new();
+    receipt.set_message_id(msg.get_message_id());
+    receipt.set_sender_uid(msg.get_sender_uid());
+    common::send(&mut receiver, Action::DELIVERED_ACK, &receipt);
+    let delivered: Receipt = common::expect(&mut sender, Action::DELIVERED_ACK);
+    assert_eq!(delivered.get_message_id(), ack.get_message_id());
+    assert_eq!(delivered.get_receiver_uid(), 2);
+
+    common::send(&mut receiver, Action::READ_RECEIPT, &receipt);
+    let read: Receipt = common::expect(&mut sender, Action::READ_RECEIPT);
+    assert_eq!(read.get_message_id(), ack.get_message_id());
+    assert_eq!(read.get_receiver_uid(), 2);
+}
+
+#[test]
+fn test_forged_receipt() {
+    let address = &common::start_server(common::server_config());
+
+    let mut sender = common::login_as(address, "cathy");
+    let mut receiver = common::login_as(address, "mars");
+    let mut other = common::login_as(address, "venus");
+
+    common::send(&mut sender, Action::MSG_TO_USER, &message(1, 2));
+    let msg: MsgToUser = common::expect(&mut receiver, Action::MSG_TO_USER);
+
+    // 不是接收方的用户不能确认送达, 也不能冒充回执
+    let mut receipt = Receipt::new();
+    receipt.set_message_id(msg.get_message_id());
+    receipt.set_sender_uid(1);
+    common::send(&mut other, Action::DELIVERED_ACK, &receipt);
+    common::send(&mut other, Action::READ_RECEIPT, &receipt);
+    thread::sleep(Duration::from_millis(200));
+
+    // 发送方以服务端记录为准, 不使用回执中的 sender_uid
+    receipt.set_sender_uid(3);
+    common::send(&mut receiver, Action::DELIVERED_ACK, &receipt);
+    let delivered: Receipt = common::expect(&mut sender, Action::DELIVERED_ACK);
+    assert_eq!(delivered.get_message_id(), msg.get_message_id());
+    assert_eq!(delivered.get_receiver_uid(), 2);
+    assert_eq!(delivered.get_sender_uid(), 1);
+    common::send(&mut receiver, Action::READ_RECEIPT, &receipt);
+    let read: Receipt = common::expect(&mut sender, Action::READ_RECEIPT);
+    assert_eq!(read.get_receiver_uid(), 2);
+}
@@ -0,0 +1,312 @@
+mod common;
+
+use cathy::proto::{Action, ConnectedReply, Login, LoginFailedReply, MsgToUser};
+use cathy::{ClientConfig, Event, IMClient, IMServer, ReconnectPolicy, SendState, ServerConfig};
+use protobuf::Message;
+use std::collections::HashSet;
+use std::io;
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn address() -> &'static str {
+    static ADDRESS: OnceLock<String> = OnceLock::new();
+    ADDRESS.get_or_init(|| common::start_server(common::server_config()))
+}
+
+// 不保留断线会话的服务端
+fn no_resume_address() -> &'static str {
+    static ADDRESS: OnceLock<String> = OnceLock::new();
+    ADDRESS.get_or_init(|| {
+        let mut config = common::server_config();
+        config.session_resume_seconds = 0;
+        common::start_server(config)
+    })
+}
+
+// 转发到服务端的 TCP 代理, 用于模拟网络中断
+#[derive(Clone)]
+struct Proxy {
+    address: String,
+    streams: Arc<Mutex<Vec<TcpStream>>>,
+    refuse: Arc<AtomicBool>,
+}
+
+impl Proxy {
+    fn start(upstream: &'static str) -> Proxy {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let proxy = Proxy {
+            address: listener.local_addr().unwrap().to_string(),
+            streams: Arc::default(),
+            refuse: Arc::default(),
+        };
+        let cloned = proxy.clone();
+        thread::spawn(move || {
+            for client in listener.incoming() {
+                let client = client.unwrap();
+                if cloned.refuse.load(Ordering::SeqCst) {
+                    continue;
+                }
+                let server = TcpStream::connect(upstream).unwrap();
+                let mut streams = cloned.streams.lock().unwrap();
+                streams.push(client.try_clone().unwrap());
+                streams.push(server.try_clone().unwrap());
+                pipe(client.try_clone().unwrap(), server.try_clone().unwrap());
+                pipe(server, client);
+            }
+        });
+        proxy
+    }
+
+    // 断开所有经过代理的连接
+    fn cut(&self) {
+        for stream in self.streams.lock().unwrap().drain(..) {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+fn pipe(mut from: TcpStream, mut to: TcpStream) {
+    thread::spawn(move || {
+        let _ = io::copy(&mut from, &mut to);
+        let _ = to.shutdown(Shutdown::Both);
+    });
+}
+
+fn fast_policy(max_attempts: u32) -> ReconnectPolicy {
+    ReconnectPolicy {
+        initial_delay: Duration::from_millis(50),
+        max_delay: Duration::from_millis(200),
+        max_attempts,
+    }
+}
+
+// 跳过投递状态等事件, 返回下一个满足条件的事件
+fn wait_for<F: Fn(&Event) -> bool>(events: &Receiver<Event>, f: F) -> Event {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        let event = events.recv_timeout(timeout).unwrap();
+        if f(&event) {
+            return event;
+        }
+    }
+}
+
+fn connected(events: &Receiver<Event>) -> (String, bool) {
+    match wait_for(events, |e| matches!(e, Event::Connected(_))) {
+        Event::Connected(reply) => (reply.get_session_id().to_string(), reply.get_resumed()),
+        _ => unreachable!(),
+    }
+}
+
+fn wait_state(client: &IMClient, seq: u64, state: SendState) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while client.message_state(seq) != Some(state) {
+        assert!(
+            Instant::now() < deadline,
+            "message {} is not {:?}",
+            seq,
+            state
+        );
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn test_reconnect_resume() {
+    let proxy = Proxy::start(address());
+    let (mut cathy, cathy_events) = common::connect_client(&proxy.address, ClientConfig::default());
+    cathy.set_reconnect_policy(fast_policy(20));
+    cathy.login("cathy", "cathy123").unwrap();
+    let (session_id, resumed) = connected(&cathy_events);
+    assert!(!resumed);
+
+    let mut mars = common::login_as(address(), "mars");
+
+    proxy.cut();
+    wait_for(&cathy_events, |e| matches!(e, Event::Reconnecting { .. }));
+    // 断线期间发出的消息在重连后重发
+    let handle = cathy.send_to_user(2, "while offline").unwrap();
+    let (resumed_id, resumed) = connected(&cathy_events);
+    assert!(resumed);
+    assert_eq!(resumed_id, session_id);
+    assert!(cathy.is_connected());
+
+    let received: MsgToUser = common::expect(&mut mars, Action::MSG_TO_USER);
+    assert_eq!(received.get_sender_uid(), 1);
+    assert_eq!(received.get_content(), "while offline");
+    common::send(&mut mars, Action::DELIVERED_ACK, &{
+        let mut receipt = cathy::proto::Receipt::new();
+        receipt.set_message_id(received.get_message_id());
+        receipt.set_sender_uid(1);
+        receipt
+    });
+    wait_state(&cathy, handle.get_seq(), SendState::Delivered);
+}
+
+#[test]
+fn test_reconnect_replay_once() {
+    let proxy = Proxy::start(address());
+    let (mut venus, venus_events) = common::connect_client(&proxy.address, ClientConfig::default());
+    venus.set_reconnect_policy(fast_policy(20));
+    venus.login("venus", "venus123").unwrap();
+    connected(&venus_events);
+
+    let mut pluto = common::login_as(address(), "pluto");
+    let before = venus.send_to_user(4, "before").unwrap();
+    wait_state(&venus, before.get_seq(), SendState::Accepted);
+
+    proxy.cut();
+    let after = venus.send_to_user(4, "after").unwrap();
+    let (_, resumed) = connected(&venus_events);
+    assert!(resumed);
+    wait_state(&venus, after.get_seq(), SendState::Accepted);
+
+    let first: MsgToUser = common::expect(&mut pluto, Action::MSG_TO_USER);
+    let second: MsgToUser = common::expect(&mut pluto, Action::MSG_TO_USER);
+    assert_eq!(first.get_content(), "before");
+    assert_eq!(second.get_content(), "after");
+    assert_ne!(first.get_message_id(), second.get_message_id());
+}
+
+#[test]
+fn test_reconnect_restore_presence() {
+    let proxy = Proxy::start(no_resume_address());
+    let (mut nova, events) = common::connect_client(&proxy.address, ClientConfig::default());
+    nova.set_reconnect_policy(fast_policy(20));
+    nova.login("nova", "nova123").unwrap();
+    connected(&events);
+    nova.subscribe_presence(&[99]).unwrap();
+    wait_for(&events, |e| matches!(e, Event::PresenceList(_)));
+
+    proxy.cut();
+    connected(&events);
+    // 重连后自动重新订阅
+    match wait_for(&events, |e| matches!(e, Event::PresenceList(_))) {
+        Event::PresenceList(list) => assert_eq!(list[0].get_uid(), 99),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn test_reconnect_without_resume() {
+    let proxy = Proxy::start(no_resume_address());
+    let (mut client, events) = common::connect_client(&proxy.address, ClientConfig::default());
+    client.set_reconnect_policy(fast_policy(20));
+    client.login("cathy", "cathy123").unwrap();
+    let (session_id, _) = connected(&events);
+
+    proxy.cut();
+    // 会话没有保留, 用登录凭证重新认证
+    let (new_session_id, resumed) = connected(&events);
+    assert!(!resumed);
+    assert_ne!(new_session_id, session_id);
+}
+
+#[test]
+fn test_reconnect_give_up() {
+    let proxy = Proxy::start(address());
+    let (mut client, events) = common::connect_client(&proxy.address, ClientConfig::default());
+    client.set_reconnect_policy(fast_policy(2));
+    client.login("luna", "luna123").unwrap();
+    connected(&events);
+
+    proxy.refuse.store(true, Ordering::SeqCst);
+    proxy.cut();
+    let mut attempts = Vec::new();
+    loop {
+        match wait_for(&events, |_| true) {
+            Event::Reconnecting { attempt, .. } => attempts.push(attempt),
+            Event::Disconnected(_) => break,
+            _ => {}
+        }
+    }
+    assert_eq!(attempts, vec![1, 2]);
+    assert!(client.is_closed());
+    assert!(client.send_to_user(1, "closed").is_err());
+}
+
+#[test]
+fn test_resume_unknown_session() {
+    let mut connection = common::connect(address());
+    let mut login = Login::new();
+    login.set_session_id("unknown".to_string());
+    common::send(&mut connection, Action::LOGIN, &login);
+    let _: LoginFailedReply = common::expect(&mut connection, Action::LOGIN_FAILED);
+}
+
+#[test]
+fn test_resume_requires_token() {
+    let mut first = common::connect(address());
+    let reply = common::login(&mut first, "sol", "sol123");
+    assert_eq!(reply.get_action(), Action::CONNECTED);
+    let connected = ConnectedReply::parse_from_bytes(reply.get_content()).unwrap();
+    assert!(!connected.get_resume_token().is_empty());
+
+    // 只有 session_id 时不能接管仍在线的会话
+    let mut other = common::connect(address());
+    let mut login = Login::new();
+    login.set_session_id(connected.get_session_id().to_string());
+    common::send(&mut other, Action::LOGIN, &login);
+    let _: LoginFailedReply = common::expect(&mut other, Action::LOGIN_FAILED);
+    common::send(&mut first, Action::HEARTBEAT, &Login::new());
+    assert_eq!(
+        first.read_package().unwrap().get_action(),
+        Action::HEARTBEAT
+    );
+
+    let mut resumed = common::connect(address());
+    login.set_resume_token(connected.get_resume_token().to_string());
+    common::send(&mut resumed, Action::LOGIN, &login);
+    let reply: ConnectedReply = common::expect(&mut resumed, Action::CONNECTED);
+    assert!(reply.get_resumed());
+    assert_eq!(reply.get_session_id(), connected.get_session_id());
+    assert!(first.read_package().is_err());
+}
+
+#[test]
+fn test_reconnect_after_server_closing() {
+    let mut server =
+        IMServer::new(common::server_config(), Box::new(common::user_store())).unwrap();
+    let shutdown = server.shutdown_handle();
+    let stopped = thread::spawn(move || server.run().unwrap());
+    let address = common::listening(&shutdown, 1).remove(0);
+    let (mut client, events) = common::connect_client(&address, ClientConfig::default());
+    client.set_reconnect_policy(fast_policy(50));
+    client.login("pluto", "pluto123").unwrap();
+    connected(&events);
+
+    shutdown.shutdown();
+    stopped.join().unwrap();
+    wait_for(&events, |e| matches!(e, Event::Reconnecting { .. }));
+    // 在同一地址重启的服务端上重新登录
+    let config = ServerConfig {
+        listen_address: address,
+        ..common::server_config()
+    };
+    common::start_server(config);
+    let (_, resumed) = connected(&events);
+    assert!(!resumed);
+    assert!(client.is_connected());
+}
+
+#[test]
+fn test_reconnect_policy_delay() {
+    let policy = ReconnectPolicy {
+        initial_delay: Duration::from_millis(100),
+        max_delay: Duration::from_millis(1000),
+        max_attempts: 10,
+    };
+    for attempt in 1..=10 {
+        let full = Duration::from_millis((100 << (attempt - 1)).min(1000));
+        let delay = policy.delay(attempt);
+        assert!(delay >= full / 2 && delay <= full, "{:?}", delay);
+    }
+    // 多次计算的等待时间带有随机抖动
+    let delays: HashSet<_> = (0..20).map(|_| policy.delay(10)).collect();
+    assert!(delays.len() > 1);
+}
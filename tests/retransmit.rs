@@ -0,0 +1,70 @@
+mod common;
+
+use cathy::proto::{Action, MsgAck, MsgToUser, Receipt};
+use cathy::{IMServer, RetransmitPolicy};
+use std::thread;
+use std::time::Duration;
+
+fn message(seq: u64, content: &str) -> MsgToUser {
+    let mut msg = MsgToUser::new();
+    msg.set_seq(seq);
+    msg.set_receiver_uid(2);
+    msg.set_content(content.to_string());
+    msg
+}
+
+#[test]
+fn test_retransmit_delay() {
+    let policy = RetransmitPolicy {
+        initial_delay: Duration::from_millis(100),
+        max_delay: Duration::from_millis(350),
+        max_attempts: 5,
+    };
+    assert_eq!(policy.delay(1), Duration::from_millis(100));
+    assert_eq!(policy.delay(2), Duration::from_millis(200));
+    assert_eq!(policy.delay(3), Duration::from_millis(350));
+    assert_eq!(policy.delay(100), Duration::from_millis(350));
+}
+
+#[test]
+fn test_retransmit_until_acked() {
+    let mut server =
+        IMServer::new(common::server_config(), Box::new(common::user_store())).unwrap();
+    server.set_retransmit_policy(RetransmitPolicy {
+        initial_delay: Duration::from_millis(200),
+        max_delay: Duration::from_millis(200),
+        max_attempts: 2,
+    });
+    let address = &common::spawn(server);
+
+    let mut sender = common::login_as(address, "cathy");
+    let mut receiver = common::login_as(address, "mars");
+
+    // 客户端重传同一个 seq, 服务端只保存一次
+    common::send(&mut sender, Action::MSG_TO_USER, &message(1, "first"));
+    common::send(&mut sender, Action::MSG_TO_USER, &message(1, "first"));
+    let ack: MsgAck = common::expect(&mut sender, Action::MSG_ACK);
+    let again: MsgAck = common::expect(&mut sender, Action::MSG_ACK);
+    assert_eq!(ack.get_message_id(), again.get_message_id());
+
+    // 不回复送达确认, 服务端按策略重传
+    for _ in 0..3 {
+        let msg: MsgToUser = common::expect(&mut receiver, Action::MSG_TO_USER);
+        assert_eq!(msg.get_message_id(), ack.get_message_id());
+    }
+
+    // 重传次数用尽后转入离线队列, 重新登录后再次投递
+    thread::sleep(Duration::from_millis(500));
+    let mut receiver = common::login_as(address, "mars");
+    let msg: MsgToUser = common::expect(&mut receiver, Action::MSG_TO_USER);
+    assert_eq!(msg.get_message_id(), ack.get_message_id());
+
+    // 回复送达确认后不再重传
+    let mut receipt = Receipt::new();
+    receipt.set_message_id(msg.get_message_id());
+    receipt.set_sender_uid(msg.get_sender_uid());
+    common::send(&mut receiver, Action::DELIVERED_ACK, &receipt);
+    common::send(&mut sender, Action::MSG_TO_USER, &message(2, "second"));
+    let msg: MsgToUser = common::expect(&mut receiver, Action::MSG_TO_USER);
+    assert_eq!(msg.get_content(), "second");
+}
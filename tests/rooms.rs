@@ -0,0 +1,89 @@
+mod common;
+
+use cathy::proto::{Action, MsgAck, MsgToRoom, RoomReply, RoomRequest};
+use cathy::RoomManager;
+
+fn request(room_id: u64, name: &str) -> RoomRequest {
+    let mut request = RoomRequest::new();
+    request.set_room_id(room_id);
+    request.set_name(name.to_string());
+    request
+}
+
+fn message(seq: u64, room_id: u64, content: &str) -> MsgToRoom {
+    let mut msg = MsgToRoom::new();
+    msg.set_seq(seq);
+    msg.set_room_id(room_id);
+    msg.set_content(content.to_string());
+    msg
+}
+
+#[test]
+fn test_room_manager() {
+    let mut room_manager = RoomManager::new();
+    let room = room_manager.create("rust", 1);
+    let room_id = room.get_room_id();
+    assert_eq!(room.get_owner_uid(), 1);
+    assert!(room.is_member(1));
+
+    room_manager.join(room_id, 2).unwrap();
+    assert_eq!(room_manager.other_members(room_id, 1).unwrap(), vec![2]);
+    assert!(room_manager.other_members(room_id, 3).is_err());
+    // 消息ID在聊天室内递增, 每个聊天室各自从 1 开始
+    assert_eq!(room_manager.next_message(room_id, 1).unwrap(), (1, vec![2]));
+    assert_eq!(room_manager.next_message(room_id, 2).unwrap(), (2, vec![1]));
+    assert!(room_manager.next_message(room_id, 3).is_err());
+    assert!(room_manager.join(room_id + 1, 2).is_err());
+    assert!(room_manager.leave(room_id, 3).is_err());
+    let other = room_manager.create("go", 2).get_room_id();
+    assert_eq!(room_manager.next_message(other, 2).unwrap(), (1, vec![]));
+
+    room_manager.leave(room_id, 1).unwrap();
+    room_manager.leave(room_id, 2).unwrap();
+    // 最后一个成员离开后聊天室被删除
+    assert!(room_manager.load(room_id).is_none());
+}
+
+#[test]
+fn test_room_broadcast() {
+    let address = &common::start_server(common::server_config());
+
+    let mut cathy = common::login_as(address, "cathy");
+    let mut mars = common::login_as(address, "mars");
+    let mut guest = common::login_as(address, "venus");
+
+    common::send(&mut cathy, Action::CREATE_ROOM, &request(0, "rust"));
+    let reply: RoomReply = common::expect(&mut cathy, Action::ROOM_REPLY);
+    assert!(reply.get_success());
+    let room_id = reply.get_room_id();
+
+    common::send(&mut mars, Action::JOIN_ROOM, &request(room_id, ""));
+    let reply: RoomReply = common::expect(&mut mars, Action::ROOM_REPLY);
+    assert!(reply.get_success());
+
+    // 非成员不能向聊天室发消息
+    common::send(&mut guest, Action::MSG_TO_ROOM, &message(1, room_id, "hi"));
+    let reply: RoomReply = common::expect(&mut guest, Action::ROOM_REPLY);
+    assert!(!reply.get_success());
+    assert_eq!(reply.get_action(), Action::MSG_TO_ROOM);
+
+    common::send(
+        &mut cathy,
+        Action::MSG_TO_ROOM,
+        &message(1, room_id, "hello"),
+    );
+    let ack: MsgAck = common::expect(&mut cathy, Action::MSG_ACK);
+    assert_eq!(ack.get_seq(), 1);
+    assert_eq!(ack.get_room_id(), room_id);
+    let msg: MsgToRoom = common::expect(&mut mars, Action::MSG_TO_ROOM);
+    assert_eq!(msg.get_message_id(), ack.get_message_id());
+    assert_eq!(msg.get_sender_uid(), 1);
+    assert_eq!(msg.get_content(), "hello");
+
+    common::send(&mut mars, Action::LEAVE_ROOM, &request(room_id, ""));
+    let reply: RoomReply = common::expect(&mut mars, Action::ROOM_REPLY);
+    assert!(reply.get_success());
+    common::send(&mut mars, Action::MSG_TO_ROOM, &message(2, room_id, "bye"));
+    let reply: RoomReply = common::expect(&mut mars, Action::ROOM_REPLY);
+    assert!(!reply.get_success());
+}
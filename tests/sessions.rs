@@ -0,0 +1,120 @@
+use cathy::{
+    Clock, Codec, Connection, MemoryTransport, MockClock, OverflowPolicy, SessionManager,
+    WriteQueue,
+};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+fn connection() -> Connection {
+    let (transport, _) = MemoryTransport::pair();
+    Connection::with_transport(Box::new(transport), Codec::default())
+}
+
+fn write_queue() -> WriteQueue {
+    WriteQueue::new(16, OverflowPolicy::default())
+}
+
+#[test]
+fn test_index_by_uid() {
+    let manager = SessionManager::with_shards(4);
+    let phone = manager.new_session(1, "phone", connection(), write_queue());
+    manager.new_session(1, "laptop", connection(), write_queue());
+    manager.new_session(5, "phone", connection(), write_queue());
+
+    assert_eq!(manager.len(), 3);
+    assert_eq!(manager.load_all(1).len(), 2);
+    assert!(manager.exist(5));
+    assert!(!manager.exist(2));
+    assert_eq!(manager.online_users(), vec![1, 5]);
+
+    let removed = manager.remove_device(1, "phone").unwrap();
+    assert!(Arc::ptr_eq(&removed, &phone));
+    assert!(!manager.is_current(&phone));
+    assert_eq!(manager.load(1).unwrap().get_device_id(), "laptop");
+    assert!(manager.remove_session(&phone).is_none());
+
+    assert_eq!(manager.remove(1).len(), 1);
+    assert!(manager.load_all(1).is_empty());
+    assert_eq!(manager.online_users(), vec![5]);
+}
+
+#[test]
+fn test_detach_and_resume() {
+    let manager = SessionManager::new();
+    let session = manager.new_session(7, "phone", connection(), write_queue());
+    let session_id = session.get_session_id();
+    assert!(manager.detach(&session, Duration::from_secs(10)));
+    // 已经移除的会话不能再次保留
+    assert!(!manager.detach(&session, Duration::from_secs(10)));
+    assert!(!manager.exist(7));
+
+    let token = session.get_resume_token();
+    // 只知道 session_id 不能恢复, 会话继续保留
+    assert!(manager.resume(&session_id, "").is_none());
+    let detached = manager.resume(&session_id, &token).unwrap();
+    assert_eq!(detached.get_uid(), 7);
+    assert!(manager.resume(&session_id, &token).is_none());
+    let restored = manager.restore_session(detached, connection(), write_queue());
+    assert_eq!(restored.get_session_id(), session_id);
+    assert!(manager.is_current(&restored));
+    assert!(!manager.is_current(&session));
+
+    assert_eq!(restored.get_resume_token(), token);
+
+    // 凭证不匹配时不能接管仍在线的会话
+    assert!(manager.resume(&session_id, "guess").is_none());
+    assert!(manager.is_current(&restored));
+    // 旧连接还没断开时恢复, 关闭旧连接
+    assert!(manager.resume(&session_id, &token).is_some());
+    assert!(manager.is_empty());
+}
+
+#[test]
+fn test_resume_grace() {
+    let clock = Arc::new(MockClock::new());
+    let manager = SessionManager::new();
+    manager.set_clock(clock.clone());
+    let first = manager.new_session(7, "phone", connection(), write_queue());
+    let second = manager.new_session(8, "phone", connection(), write_queue());
+    manager.detach(&first, Duration::from_secs(10));
+    manager.detach(&second, Duration::from_secs(10));
+
+    // 宽限期按单调时间计算, 不受系统时间调整的影响
+    let now = clock.now_millis();
+    clock.set_millis(now + 3_600_000);
+    clock.advance(Duration::from_secs(9));
+    assert!(manager
+        .resume(&first.get_session_id(), &first.get_resume_token())
+        .is_some());
+
+    clock.set_millis(now);
+    clock.advance(Duration::from_secs(1));
+    assert!(manager
+        .resume(&second.get_session_id(), &second.get_resume_token())
+        .is_none());
+}
+
+#[test]
+fn test_concurrent_users() {
+    let manager = Arc::new(SessionManager::new());
+    let threads: Vec<_> = (0..4u64)
+        .map(|i| {
+            let manager = manager.clone();
+            let connections: Vec<_> = (0..25).map(|_| connection()).collect();
+            thread::spawn(move || {
+                for (j, connection) in connections.into_iter().enumerate() {
+                    let uid = i * 25 + j as u64 + 1;
+                    let mut user = manager.lock_user(uid);
+                    assert!(!user.exist());
+                    user.new_session("", connection, write_queue());
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert_eq!(manager.len(), 100);
+    assert_eq!(manager.online_users(), (1..=100).collect::<Vec<_>>());
+}
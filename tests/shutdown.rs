@@ -0,0 +1,93 @@
+mod common;
+
+use cathy::proto::{Action, MsgToUser, ServerClosing};
+use cathy::{FileOfflineStore, IMError, IMServer, OfflineStore, RetransmitPolicy, ServerConfig};
+use std::fs;
+use std::net::{TcpListener, TcpStream};
+use std::process;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+fn message(seq: u64, receiver_uid: u64) -> MsgToUser {
+    let mut msg = MsgToUser::new();
+    msg.set_seq(seq);
+    msg.set_receiver_uid(receiver_uid);
+    msg.set_content("hello".to_string());
+    msg
+}
+
+// 接收方未确认的消息在关闭时转入离线队列, 不断开的客户端在排空超时后被强制断开
+fn check_shutdown(name: &str, run: fn(&mut IMServer) -> cathy::Result<()>) {
+    let dir = std::env::temp_dir().join(format!("cathy-{}-{}", name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let config = ServerConfig {
+        drain_timeout_seconds: 1,
+        ..common::server_config()
+    };
+    let mut server = IMServer::new(config, Box::new(common::user_store())).unwrap();
+    server.set_offline_store(Box::new(FileOfflineStore::open(&dir).unwrap()));
+    server.set_retransmit_policy(RetransmitPolicy {
+        initial_delay: Duration::from_secs(60),
+        ..RetransmitPolicy::default()
+    });
+    let shutdown = server.shutdown_handle();
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || tx.send(run(&mut server).is_ok()).unwrap());
+    let address = &common::listening(&shutdown, 1)[0];
+
+    let mut sender = common::login_as(address, "cathy");
+    let mut receiver = common::login_as(address, "mars");
+    common::send(&mut sender, Action::MSG_TO_USER, &message(1, 2));
+    let msg: MsgToUser = common::expect(&mut receiver, Action::MSG_TO_USER);
+
+    shutdown.shutdown();
+    let notice: ServerClosing = common::expect(&mut receiver, Action::SERVER_CLOSING);
+    assert!(!notice.get_reason().is_empty());
+    common::expect::<ServerClosing>(&mut sender, Action::SERVER_CLOSING);
+    assert!(rx.recv_timeout(Duration::from_secs(5)).unwrap());
+
+    assert!(receiver.read_package().is_err());
+    assert!(TcpStream::connect(address).is_err());
+    let messages = FileOfflineStore::open(&dir).unwrap().take(2).unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].get_message_id(), msg.get_message_id());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_shutdown() {
+    check_shutdown("shutdown", IMServer::run);
+}
+
+#[cfg(feature = "event-loop")]
+#[test]
+fn test_shutdown_event_loop() {
+    check_shutdown("shutdown-event-loop", IMServer::run_event_loop);
+}
+
+#[test]
+fn test_shutdown_before_run() {
+    let mut server =
+        IMServer::new(common::server_config(), Box::new(common::user_store())).unwrap();
+    server.shutdown_handle().shutdown();
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || tx.send(server.run().is_ok()).unwrap());
+    assert!(rx.recv_timeout(Duration::from_secs(5)).unwrap());
+}
+
+#[test]
+fn test_run_bind_error() {
+    // 监听地址已被占用时 run 返回错误, 不会 panic
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let config = ServerConfig {
+        listen_address: address.clone(),
+        ..common::server_config()
+    };
+    let mut server = IMServer::new(config, Box::new(common::user_store())).unwrap();
+    match server.run() {
+        Err(IMError::Io(_)) => {}
+        _ => panic!("run should fail to bind {}", address),
+    }
+}
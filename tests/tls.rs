@@ -0,0 +1,107 @@
+#![cfg(feature = "tls")]
+
+mod common;
+
+use cathy::proto::{Action, MsgToUser};
+use cathy::{ClientConfig, Codec, Connection, IMServer, TlsAcceptor, TlsConnector};
+use rcgen::CertifiedKey;
+use std::net::TcpStream;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+// 测试时生成的自签名证书, 同时作为客户端的 CA
+fn certificate() -> &'static CertifiedKey {
+    static CERT: OnceLock<CertifiedKey> = OnceLock::new();
+    CERT.get_or_init(|| rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap())
+}
+
+fn address() -> &'static str {
+    static ADDRESS: OnceLock<String> = OnceLock::new();
+    ADDRESS.get_or_init(|| {
+        let cert = certificate();
+        let acceptor = TlsAcceptor::new(
+            cert.cert.pem().as_bytes(),
+            cert.key_pair.serialize_pem().as_bytes(),
+        )
+        .unwrap();
+        let mut server =
+            IMServer::new(common::server_config(), Box::new(common::user_store())).unwrap();
+        server.set_tls(acceptor);
+        common::spawn(server)
+    })
+}
+
+fn tcp_connect() -> TcpStream {
+    TcpStream::connect(address()).unwrap()
+}
+
+fn tls_connect(ca_pem: &str) -> cathy::Result<Connection> {
+    let connector = TlsConnector::new(ca_pem.as_bytes())?;
+    let stream = connector.connect("localhost", tcp_connect())?;
+    let mut connection = Connection::with_transport(Box::new(stream), Codec::default());
+    connection.handshake(Duration::from_secs(1))?;
+    Ok(connection)
+}
+
+#[test]
+fn test_tls_message() {
+    let ca = certificate().cert.pem();
+    let mut cathy = tls_connect(&ca).unwrap();
+    let mut mars = tls_connect(&ca).unwrap();
+    assert_eq!(
+        common::login(&mut cathy, "cathy", "cathy123").get_action(),
+        Action::CONNECTED
+    );
+    assert_eq!(
+        common::login(&mut mars, "mars", "mars123").get_action(),
+        Action::CONNECTED
+    );
+
+    let mut msg = MsgToUser::new();
+    msg.set_seq(1);
+    msg.set_receiver_uid(2);
+    msg.set_content("over tls".to_string());
+    common::send(&mut cathy, Action::MSG_TO_USER, &msg);
+    let received: MsgToUser = common::expect(&mut mars, Action::MSG_TO_USER);
+    assert_eq!(received.get_content(), "over tls");
+}
+
+#[test]
+fn test_tls_untrusted_certificate() {
+    // 用另一张自签名证书作为 CA, 无法校验服务端证书
+    let other = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    assert!(tls_connect(&other.cert.pem()).is_err());
+}
+
+#[test]
+fn test_tls_rejects_plaintext() {
+    // 明文客户端的握手数据不是合法的 TLS 记录, 服务端断开连接
+    let mut connection = Connection::new(tcp_connect());
+    assert!(connection.handshake(Duration::from_secs(1)).is_err());
+}
+
+#[test]
+fn test_tls_bad_pem() {
+    assert!(TlsConnector::new(b"not a certificate").is_err());
+    assert!(TlsAcceptor::new(certificate().cert.pem().as_bytes(), b"no key").is_err());
+}
+
+#[test]
+fn test_server_name() {
+    let config = ClientConfig {
+        server_address: "localhost:8099".to_string(),
+        ..ClientConfig::default()
+    };
+    assert_eq!(config.server_name(), "localhost");
+    let config = ClientConfig {
+        server_address: "[::1]:8099".to_string(),
+        tls_server_name: None,
+        ..ClientConfig::default()
+    };
+    assert_eq!(config.server_name(), "::1");
+    let config = ClientConfig {
+        tls_server_name: Some("chat.example.com".to_string()),
+        ..ClientConfig::default()
+    };
+    assert_eq!(config.server_name(), "chat.example.com");
+}
@@ -0,0 +1,81 @@
+use cathy::proto::{Action, Package};
+use cathy::{Codec, Connection, IMError, MemoryTransport, Transport, PROTOCOL_VERSION};
+use std::thread;
+use std::time::Duration;
+
+fn pair() -> (Connection, Connection) {
+    let (left, right) = MemoryTransport::pair();
+    (
+        Connection::with_transport(Box::new(left), Codec::default()),
+        Connection::with_transport(Box::new(right), Codec::default()),
+    )
+}
+
+fn package(action: Action, content: &[u8]) -> Package {
+    let mut package = Package::new();
+    package.set_action(action);
+    package.set_content(content.to_vec());
+    package
+}
+
+#[test]
+fn test_memory_transport_handshake() {
+    let (mut client, mut server) = pair();
+    let accept = thread::spawn(move || {
+        let version = server.accept_handshake(Duration::from_secs(1)).unwrap();
+        let p = server.read_package().unwrap();
+        server
+            .write_package(p, Duration::from_secs(1))
+            .expect("echo");
+        version
+    });
+    assert_eq!(
+        client.handshake(Duration::from_secs(1)).unwrap(),
+        PROTOCOL_VERSION
+    );
+
+    // 超过单帧长度的数据包被分帧后原样返回
+    let content = vec![7; 40 * 1024];
+    client
+        .write_package(
+            package(Action::MSG_TO_USER, &content),
+            Duration::from_secs(1),
+        )
+        .unwrap();
+    let p = client.read_package().unwrap();
+    assert_eq!(p.get_action(), Action::MSG_TO_USER);
+    assert_eq!(p.get_content(), &content[..]);
+    assert_eq!(accept.join().unwrap(), PROTOCOL_VERSION);
+}
+
+#[test]
+fn test_memory_transport_nonblocking() {
+    let (mut left, mut right) = pair();
+    right.set_nonblocking(true).unwrap();
+    assert!(right.try_read_package().unwrap().is_none());
+    left.write_package(package(Action::HEARTBEAT, b"PING"), Duration::from_secs(1))
+        .unwrap();
+    let p = right.try_read_package().unwrap().unwrap();
+    assert_eq!(p.get_content(), b"PING");
+
+    right.set_nonblocking(false).unwrap();
+    right
+        .set_read_timeout(Some(Duration::from_millis(20)))
+        .unwrap();
+    assert!(matches!(right.read_package(), Err(IMError::Io(_))));
+}
+
+#[test]
+fn test_memory_transport_shutdown() {
+    let (mut left, mut right) = pair();
+    let reader = thread::spawn(move || right.read_package());
+    thread::sleep(Duration::from_millis(20));
+    // 关闭后阻塞中的读取返回 EOF
+    left.shutdown();
+    assert!(matches!(reader.join().unwrap(), Err(IMError::TcpStreamEOF)));
+
+    let (a, mut b) = MemoryTransport::pair();
+    a.shutdown().unwrap();
+    assert!(std::io::Write::write(&mut b, b"x").is_err());
+    assert_eq!(a.remote_address(), "memory");
+}
@@ -0,0 +1,114 @@
+#![cfg(feature = "websocket")]
+
+mod common;
+
+use cathy::proto::{Action, ConnectedReply, Login, MsgAck, MsgToUser, Package};
+use cathy::{IMServer, ServerConfig};
+use protobuf::Message as _;
+use std::net::TcpStream;
+use std::sync::OnceLock;
+use std::thread;
+use std::time::Duration;
+use tungstenite::{Message, WebSocket};
+
+// 服务端的 TCP 地址与 WebSocket 地址
+fn addresses() -> &'static [String] {
+    static ADDRESSES: OnceLock<Vec<String>> = OnceLock::new();
+    ADDRESSES.get_or_init(|| {
+        let config = ServerConfig {
+            websocket_address: Some("127.0.0.1:0".to_string()),
+            ..common::server_config()
+        };
+        let mut server = IMServer::new(config, Box::new(common::user_store())).unwrap();
+        let shutdown = server.shutdown_handle();
+        thread::spawn(move || server.run().unwrap());
+        common::listening(&shutdown, 2)
+    })
+}
+
+// 模拟浏览器: 每个二进制消息是一个 protobuf 编码的 Package
+fn browser() -> WebSocket<TcpStream> {
+    let address = &addresses()[1];
+    let stream = TcpStream::connect(address).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let url = format!("ws://{}/", address);
+    let (socket, _) = tungstenite::client(url, stream).unwrap();
+    socket
+}
+
+fn send<M: protobuf::Message>(socket: &mut WebSocket<TcpStream>, action: Action, msg: &M) {
+    let mut package = Package::new();
+    package.set_action(action);
+    package.set_content(msg.write_to_bytes().unwrap());
+    let data = package.write_to_bytes().unwrap();
+    socket.send(Message::Binary(data.into())).unwrap();
+}
+
+// 读取下一个指定类型的数据包, 跳过心跳等其他数据包
+fn expect<M: protobuf::Message>(socket: &mut WebSocket<TcpStream>, action: Action) -> M {
+    loop {
+        if let Message::Binary(data) = socket.read().unwrap() {
+            let package = Package::parse_from_bytes(&data).unwrap();
+            if package.get_action() == action {
+                return M::parse_from_bytes(package.get_content()).unwrap();
+            }
+        }
+    }
+}
+
+fn message(seq: u64, receiver_uid: u64, content: &str) -> MsgToUser {
+    let mut msg = MsgToUser::new();
+    msg.set_seq(seq);
+    msg.set_receiver_uid(receiver_uid);
+    msg.set_content(content.to_string());
+    msg
+}
+
+#[test]
+fn test_browser_and_native_client() {
+    let mut browser = browser();
+    let mut login = Login::new();
+    login.set_username("mars".to_string());
+    login.set_password("mars123".to_string());
+    send(&mut browser, Action::LOGIN, &login);
+    let reply: ConnectedReply = expect(&mut browser, Action::CONNECTED);
+    assert_eq!(reply.get_uid(), 2);
+
+    let mut native = common::login_as(&addresses()[0], "cathy");
+
+    send(
+        &mut browser,
+        Action::MSG_TO_USER,
+        &message(1, 1, "hello from browser"),
+    );
+    let ack: MsgAck = expect(&mut browser, Action::MSG_ACK);
+    assert_eq!(ack.get_seq(), 1);
+    let msg: MsgToUser = common::expect(&mut native, Action::MSG_TO_USER);
+    assert_eq!(msg.get_content(), "hello from browser");
+    assert_eq!(msg.get_sender_uid(), 2);
+
+    common::send(
+        &mut native,
+        Action::MSG_TO_USER,
+        &message(1, 2, "hello from native"),
+    );
+    let msg: MsgToUser = expect(&mut browser, Action::MSG_TO_USER);
+    assert_eq!(msg.get_content(), "hello from native");
+    assert_eq!(msg.get_sender_uid(), 1);
+}
+
+#[test]
+fn test_malformed_package() {
+    let mut browser = browser();
+    // 不是 protobuf 编码的 Package, 服务端断开连接
+    let data = vec![0xff; 8];
+    browser.send(Message::Binary(data.into())).unwrap();
+    loop {
+        match browser.read() {
+            Ok(Message::Close(_)) | Err(_) => break,
+            Ok(_) => {}
+        }
+    }
+}
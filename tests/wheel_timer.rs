@@ -0,0 +1,243 @@
+use cathy::{IMError, TimerTask, WheelTimer};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+struct FlagTask {
+    fired: Arc<AtomicBool>,
+}
+
+impl TimerTask for FlagTask {
+    fn run(&mut self) {
+        self.fired.store(true, Ordering::SeqCst);
+    }
+}
+
+// 记录任务执行的时间
+struct RecordTask {
+    id: u64,
+    fired: Arc<Mutex<Vec<(u64, u64)>>>,
+}
+
+impl TimerTask for RecordTask {
+    fn run(&mut self) {
+        self.fired.lock().unwrap().push((self.id, now()));
+    }
+}
+
+struct PanicTask;
+
+impl TimerTask for PanicTask {
+    fn run(&mut self) {
+        panic!("timer task failed");
+    }
+}
+
+struct SleepTask(Duration);
+
+impl TimerTask for SleepTask {
+    fn run(&mut self) {
+        thread::sleep(self.0);
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+fn flag_task() -> (Box<FlagTask>, Arc<AtomicBool>) {
+    let fired = Arc::new(AtomicBool::new(false));
+    let task = FlagTask {
+        fired: fired.clone(),
+    };
+    (Box::new(task), fired)
+}
+
+fn wait_until<F: Fn() -> bool>(f: F) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while Instant::now() < deadline {
+        if f() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    false
+}
+
+#[test]
+fn test_timeout_expire() {
+    let mut timer = WheelTimer::new(10, 64).unwrap();
+    let now = now();
+    let (task, fired) = flag_task();
+    let timeout = timer.new_timeout(task, Duration::from_millis(50)).unwrap();
+    assert!(timeout.deadline() >= now + 50 && timeout.deadline() < now + 1000);
+    assert!(!timeout.is_expired());
+
+    assert!(wait_until(|| fired.load(Ordering::SeqCst)));
+    assert!(timeout.is_expired());
+    // 已经执行的任务不能取消
+    assert!(!timeout.cancel());
+    assert!(!timeout.is_cancelled());
+    timer.stop();
+}
+
+#[test]
+fn test_timeout_cancel() {
+    let mut timer = WheelTimer::new(10, 64).unwrap();
+    let (task, fired) = flag_task();
+    let timeout = timer.new_timeout(task, Duration::from_millis(100)).unwrap();
+    assert!(timeout.cancel());
+    assert!(timeout.is_cancelled());
+    assert!(!timeout.cancel());
+
+    thread::sleep(Duration::from_millis(300));
+    assert!(!fired.load(Ordering::SeqCst));
+    assert!(!timeout.is_expired());
+    timer.stop();
+}
+
+#[test]
+fn test_cancelled_timeout_reclaimed() {
+    let mut timer = WheelTimer::new(10, 8).unwrap();
+    let (task, fired) = flag_task();
+    // 多轮之后才到期的任务, 取消后立即从 bucket 中移除并释放
+    let timeout = timer.new_timeout(task, Duration::from_secs(10)).unwrap();
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(Arc::strong_count(&fired), 2);
+    timeout.cancel();
+    assert!(wait_until(|| Arc::strong_count(&fired) == 1));
+    assert!(!fired.load(Ordering::SeqCst));
+    timer.stop();
+}
+
+fn check_deadlines(levels: u32) {
+    // 每层4格, 3层时最高一层覆盖 64 个 tick, 更远的任务需要多次级联
+    let mut timer = WheelTimer::with_levels(10, 4, levels).unwrap();
+    let fired = Arc::new(Mutex::new(Vec::new()));
+    let delays = [15, 50, 170, 330, 900];
+    let mut deadlines = Vec::new();
+    for (id, delay) in delays.iter().enumerate().rev() {
+        let task = RecordTask {
+            id: id as u64,
+            fired: fired.clone(),
+        };
+        let timeout = timer
+            .new_timeout(Box::new(task), Duration::from_millis(*delay))
+            .unwrap();
+        deadlines.push((id as u64, timeout.deadline()));
+    }
+    deadlines.reverse();
+    assert!(wait_until(|| fired.lock().unwrap().len() == delays.len()));
+    assert_eq!(timer.pending_timeouts(), 0);
+
+    let fired = fired.lock().unwrap();
+    let ids: Vec<u64> = fired.iter().map(|v| v.0).collect();
+    assert_eq!(ids, vec![0, 1, 2, 3, 4]);
+    for ((_, fired_at), (_, deadline)) in fired.iter().zip(deadlines.iter()) {
+        assert!(*fired_at >= *deadline, "{} < {}", fired_at, deadline);
+        assert!(*fired_at < *deadline + 200, "{} >= {}", fired_at, deadline);
+    }
+    timer.stop();
+}
+
+#[test]
+fn test_hierarchical_deadlines() {
+    check_deadlines(3);
+}
+
+#[test]
+fn test_single_level_deadlines() {
+    check_deadlines(1);
+}
+
+#[test]
+fn test_max_pending_timeouts() {
+    let mut timer = WheelTimer::new(10, 64).unwrap();
+    timer.set_max_pending_timeouts(2);
+    let (task, _) = flag_task();
+    let first = timer.new_timeout(task, Duration::from_secs(10)).unwrap();
+    let (task, _) = flag_task();
+    timer.new_timeout(task, Duration::from_secs(10)).unwrap();
+    assert_eq!(timer.pending_timeouts(), 2);
+
+    let (task, _) = flag_task();
+    match timer.new_timeout(task, Duration::from_secs(10)) {
+        Err(IMError::TooManyTimeouts(2)) => {}
+        _ => panic!("the timeout should be rejected"),
+    }
+    // 取消的任务移除之后可以继续安排
+    first.cancel();
+    assert!(wait_until(|| timer.pending_timeouts() == 1));
+    let (task, _) = flag_task();
+    assert!(timer.new_timeout(task, Duration::from_secs(10)).is_ok());
+    timer.stop();
+}
+
+#[test]
+fn test_timeout_after_stop() {
+    let mut timer = WheelTimer::new(10, 64).unwrap();
+    timer.stop();
+    let (task, fired) = flag_task();
+    match timer.new_timeout(task, Duration::from_millis(10)) {
+        Err(IMError::TimerStopped) => {}
+        _ => panic!("the timeout should be rejected"),
+    }
+    assert_eq!(timer.pending_timeouts(), 0);
+    thread::sleep(Duration::from_millis(50));
+    assert!(!fired.load(Ordering::SeqCst));
+}
+
+fn panic_hook(timer: &WheelTimer) -> Arc<Mutex<Vec<String>>> {
+    let panics = Arc::new(Mutex::new(Vec::new()));
+    let cloned = panics.clone();
+    timer.set_panic_hook(move |message| cloned.lock().unwrap().push(message.to_string()));
+    panics
+}
+
+#[test]
+fn test_task_panic_isolated() {
+    let mut timer = WheelTimer::new(10, 64).unwrap();
+    let panics = panic_hook(&timer);
+    timer
+        .new_timeout(Box::new(PanicTask), Duration::from_millis(20))
+        .unwrap();
+    let (task, fired) = flag_task();
+    timer.new_timeout(task, Duration::from_millis(20)).unwrap();
+    assert!(wait_until(|| fired.load(Ordering::SeqCst)));
+    assert_eq!(*panics.lock().unwrap(), vec!["timer task failed"]);
+
+    // 时间轮线程仍在运行
+    let (task, fired) = flag_task();
+    timer.new_timeout(task, Duration::from_millis(20)).unwrap();
+    assert!(wait_until(|| fired.load(Ordering::SeqCst)));
+    timer.stop();
+}
+
+#[test]
+fn test_worker_threads() {
+    let mut timer = WheelTimer::new(10, 64).unwrap();
+    timer.set_worker_threads(2);
+    let panics = panic_hook(&timer);
+    // 耗时的任务不推迟之后到期的任务
+    timer
+        .new_timeout(
+            Box::new(SleepTask(Duration::from_secs(1))),
+            Duration::from_millis(10),
+        )
+        .unwrap();
+    let (task, fired) = flag_task();
+    let start = Instant::now();
+    timer.new_timeout(task, Duration::from_millis(50)).unwrap();
+    assert!(wait_until(|| fired.load(Ordering::SeqCst)));
+    assert!(start.elapsed() < Duration::from_millis(500));
+
+    timer
+        .new_timeout(Box::new(PanicTask), Duration::from_millis(10))
+        .unwrap();
+    assert!(wait_until(|| panics.lock().unwrap().len() == 1));
+    timer.stop();
+}
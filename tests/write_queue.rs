@@ -0,0 +1,251 @@
+mod common;
+
+use cathy::proto::{Action, MsgAck, MsgToUser, Package, Receipt};
+use cathy::{
+    Connection, IMError, IMServer, MemoryOfflineStore, OfflineStore, OverflowPolicy,
+    RetransmitPolicy, ServerConfig, WriteQueue,
+};
+use protobuf::Message;
+use std::collections::HashSet;
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 返回一对已连接的 Connection
+fn connection_pair() -> (Connection, Connection) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (server, _) = listener.accept().unwrap();
+    (Connection::new(server), Connection::new(client))
+}
+
+fn package(content: Vec<u8>) -> Package {
+    let mut package = Package::new();
+    package.set_action(Action::HEARTBEAT);
+    package.set_content(content);
+    package
+}
+
+#[test]
+fn test_single_writer() {
+    let (server, mut client) = connection_pair();
+    let queue = WriteQueue::start(
+        server,
+        10000,
+        OverflowPolicy::Disconnect,
+        Duration::from_secs(10),
+    );
+    let writers: Vec<_> = (0..8u8)
+        .map(|id| {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                for i in 0..200u16 {
+                    let mut content = vec![id; 1000];
+                    content[..2].copy_from_slice(&i.to_be_bytes());
+                    queue.push(package(content)).unwrap();
+                }
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.join().unwrap();
+    }
+
+    // 每个数据包都完整, 同一线程的数据包保持入队顺序
+    let mut next = [0u16; 8];
+    for _ in 0..1600 {
+        let p = client.read_package().unwrap();
+        let content = p.get_content();
+        let id = content[2] as usize;
+        assert!(content[2..].iter().all(|&v| v as usize == id));
+        assert_eq!(u16::from_be_bytes([content[0], content[1]]), next[id]);
+        next[id] += 1;
+    }
+    queue.close();
+}
+
+#[test]
+fn test_flush() {
+    let (server, mut client) = connection_pair();
+    let queue = WriteQueue::start(server, 1024, OverflowPolicy::Drop, Duration::from_secs(10));
+    // 对端不读取时, 写满 socket 缓冲区后队列无法写完
+    for _ in 0..1024 {
+        queue.push(package(vec![0; 4000])).unwrap();
+    }
+    assert!(!queue.flush(Duration::from_millis(100)));
+    let reader = thread::spawn(move || {
+        for _ in 0..1024 {
+            client.read_package().unwrap();
+        }
+    });
+    assert!(queue.flush(Duration::from_secs(10)));
+    assert!(queue.is_empty());
+    reader.join().unwrap();
+    queue.close();
+
+    // 没有写线程的队列只能等到超时, 关闭的队列立即返回
+    let queue = WriteQueue::new(16, OverflowPolicy::Drop);
+    queue.push(package(Vec::new())).unwrap();
+    assert!(!queue.flush(Duration::from_millis(10)));
+    queue.close();
+    assert!(queue.flush(Duration::from_secs(10)));
+}
+
+#[test]
+fn test_notify() {
+    let notified = Arc::new(AtomicUsize::new(0));
+    let counter = notified.clone();
+    let queue = WriteQueue::with_notify(
+        16,
+        OverflowPolicy::Drop,
+        Arc::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }),
+    );
+    // 只有空闲的队列放入数据包时通知
+    queue.push(package(vec![1])).unwrap();
+    queue.push(package(vec![2])).unwrap();
+    assert_eq!(notified.load(Ordering::SeqCst), 1);
+    assert_eq!(queue.try_pop().unwrap().get_content(), &[1]);
+    queue.push(package(vec![3])).unwrap();
+    assert_eq!(notified.load(Ordering::SeqCst), 1);
+    assert!(!queue.flush(Duration::from_millis(10)));
+    assert_eq!(queue.try_pop().unwrap().get_content(), &[2]);
+    assert_eq!(queue.try_pop().unwrap().get_content(), &[3]);
+    assert!(queue.try_pop().is_none());
+    assert!(queue.flush(Duration::from_millis(10)));
+
+    queue.push(package(vec![4])).unwrap();
+    assert_eq!(notified.load(Ordering::SeqCst), 2);
+    queue.close();
+    assert_eq!(notified.load(Ordering::SeqCst), 3);
+    assert!(queue.try_pop().is_none());
+}
+
+// 对端不读取数据, 直到发送队列溢出
+fn fill(queue: &WriteQueue) -> IMError {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while Instant::now() < deadline {
+        if let Err(e) = queue.push(package(vec![0; 4000])) {
+            return e;
+        }
+    }
+    panic!("write queue never overflowed")
+}
+
+#[test]
+fn test_overflow_policy() {
+    let (server, _client) = connection_pair();
+    let queue = WriteQueue::start(server, 16, OverflowPolicy::Drop, Duration::from_secs(10));
+    assert!(matches!(fill(&queue), IMError::WriteQueueFull));
+    assert!(!queue.is_closed());
+    assert_eq!(queue.len(), 16);
+    queue.close();
+
+    let (server, _client) = connection_pair();
+    let queue = WriteQueue::start(
+        server,
+        16,
+        OverflowPolicy::Disconnect,
+        Duration::from_secs(10),
+    );
+    assert!(matches!(fill(&queue), IMError::WriteQueueFull));
+    assert!(queue.is_closed());
+    assert!(matches!(
+        queue.push(package(Vec::new())),
+        Err(IMError::ConnectionClosed)
+    ));
+}
+
+// 记录转入离线队列的消息数量
+struct CountingOfflineStore {
+    store: MemoryOfflineStore,
+    pushed: Arc<AtomicUsize>,
+}
+
+impl OfflineStore for CountingOfflineStore {
+    fn push(&mut self, uid: u64, msg: MsgToUser) -> cathy::Result<()> {
+        self.pushed.fetch_add(1, Ordering::SeqCst);
+        self.store.push(uid, msg)
+    }
+
+    fn take(&mut self, uid: u64) -> cathy::Result<Vec<MsgToUser>> {
+        self.store.take(uid)
+    }
+
+    fn flush(&mut self) -> cathy::Result<()> {
+        Ok(())
+    }
+}
+
+// 登录后接收消息并回复送达确认, 一段时间没有新消息时返回
+fn receive(address: &str, received: &mut HashSet<u64>) {
+    let mut receiver = common::login_as(address, "mars");
+    receiver
+        .set_read_timeout(Some(Duration::from_millis(500)))
+        .unwrap();
+    while let Ok(p) = receiver.read_package() {
+        if p.get_action() != Action::MSG_TO_USER {
+            continue;
+        }
+        let msg = MsgToUser::parse_from_bytes(p.get_content()).unwrap();
+        received.insert(msg.get_message_id());
+        let mut receipt = Receipt::new();
+        receipt.set_message_id(msg.get_message_id());
+        receipt.set_sender_uid(msg.get_sender_uid());
+        common::send(&mut receiver, Action::DELIVERED_ACK, &receipt);
+    }
+}
+
+#[test]
+fn test_spill_offline() {
+    let pushed = Arc::new(AtomicUsize::new(0));
+    let config = ServerConfig {
+        write_queue_capacity: 64,
+        overflow_policy: OverflowPolicy::SpillOffline,
+        ..common::server_config()
+    };
+    let mut server = IMServer::new(config, Box::new(common::user_store())).unwrap();
+    server.set_offline_store(Box::new(CountingOfflineStore {
+        store: MemoryOfflineStore::new(),
+        pushed: pushed.clone(),
+    }));
+    server.set_retransmit_policy(RetransmitPolicy {
+        initial_delay: Duration::from_millis(200),
+        max_delay: Duration::from_millis(200),
+        max_attempts: 5,
+    });
+    let address = &common::spawn(server);
+
+    let mut sender = common::login_as(address, "cathy");
+    // 接收方不读取数据, 发送方不会被阻塞
+    let slow = common::login_as(address, "mars");
+    let total = 3000;
+    let start = Instant::now();
+    for seq in 1..=total {
+        let mut msg = MsgToUser::new();
+        msg.set_seq(seq);
+        msg.set_receiver_uid(2);
+        msg.set_content("x".repeat(4000));
+        common::send(&mut sender, Action::MSG_TO_USER, &msg);
+        let ack: MsgAck = common::expect(&mut sender, Action::MSG_ACK);
+        assert_eq!(ack.get_seq(), seq);
+    }
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert!(pushed.load(Ordering::SeqCst) > 0);
+
+    // 重新登录后收到全部消息, 在线期间溢出的消息在下次登录时投递
+    // 每次登录至少收到一个发送队列容量的离线消息, 直到没有新的消息
+    let mut received = HashSet::new();
+    loop {
+        let before = received.len();
+        receive(address, &mut received);
+        if received.len() == total as usize || received.len() == before {
+            break;
+        }
+    }
+    assert_eq!(received.len(), total as usize);
+    drop(slow);
+}